@@ -13,7 +13,7 @@ pub enum BarrierError {
 /// # Examples
 /// ```
 /// use std::sync::Arc;
-/// use ofdm_demod::barrier::Barrier;
+/// use app_helpers::barrier::Barrier;
 /// 
 /// let barrier = Arc::new(Barrier::new(false));
 /// 
@@ -30,7 +30,6 @@ pub enum BarrierError {
 /// let thread_1 = std::thread::spawn({
 ///     let barrier = barrier.clone();
 ///     move || {
-///         // Acquire lets use get a mutable reference through Arc<>
 ///         barrier.set(true).unwrap();
 ///         println!("[thread-1] updated barrier");
 ///         barrier.wait(|state| !*state).unwrap();
@@ -65,6 +64,9 @@ impl<T> Barrier<T> {
     /// Close the barrier.
     /// If there are threads waiting for or updating the barrier they will get a Closed error.
     pub fn close(&self) -> Result<(),BarrierError> {
+        // Waiters check is_closed while holding the data lock so it is taken first
+        // Otherwise a waiter could miss the notification between its check and its wait
+        let _data = self.data.lock().unwrap();
         let mut is_closed = self.is_closed.write().unwrap();
         if *is_closed {
             return Err(BarrierError::Closed);
@@ -107,6 +109,83 @@ impl<T> Barrier<T> where T: PartialEq {
     }
 }
 
+/// A barrier holding an optional payload can be used as a single slot channel.
+/// The producer blocks until the consumer has taken the previous payload so no payload is silently overwritten.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use app_helpers::barrier::Barrier;
+///
+/// let channel = Arc::new(Barrier::<Option<usize>>::new(None));
+///
+/// let producer = std::thread::spawn({
+///     let channel = channel.clone();
+///     move || {
+///         for i in 0..100 {
+///             channel.put(i).unwrap();
+///         }
+///         channel.close().unwrap();
+///     }
+/// });
+///
+/// let mut received = vec![];
+/// while let Ok(value) = channel.take() {
+///     received.push(value);
+/// }
+/// producer.join().unwrap();
+/// assert_eq!(received, (0..100).collect::<Vec<_>>());
+/// ```
+#[allow(unused)]
+impl<T> Barrier<Option<T>> {
+    /// Blocks thread until the slot is empty or the barrier is closed.
+    pub fn wait_until_empty(&self) -> Result<(),BarrierError> {
+        let mut data = self.data.lock().unwrap();
+        loop {
+            if *self.is_closed.read().unwrap() {
+                return Err(BarrierError::Closed);
+            }
+            if data.is_none() {
+                return Ok(());
+            }
+            data = self.on_change.wait(data).unwrap();
+        }
+    }
+
+    /// Blocks thread until the slot is empty and then fills it with the payload.
+    pub fn put(&self, value: T) -> Result<(),BarrierError> {
+        let mut data = self.data.lock().unwrap();
+        loop {
+            if *self.is_closed.read().unwrap() {
+                return Err(BarrierError::Closed);
+            }
+            if data.is_none() {
+                break;
+            }
+            data = self.on_change.wait(data).unwrap();
+        }
+        *data = Some(value);
+        self.on_change.notify_all();
+        Ok(())
+    }
+
+    /// Blocks thread until the slot is filled and then takes the payload out of it.
+    /// A payload that was put before the barrier was closed can still be taken.
+    pub fn take(&self) -> Result<T,BarrierError> {
+        let mut data = self.data.lock().unwrap();
+        loop {
+            if let Some(value) = data.take() {
+                self.on_change.notify_all();
+                return Ok(value);
+            }
+            if *self.is_closed.read().unwrap() {
+                return Err(BarrierError::Closed);
+            }
+            data = self.on_change.wait(data).unwrap();
+        }
+    }
+}
+
 impl<T> Drop for Barrier<T> {
     /// Close the barrier when it falls out of scope.
     fn drop(&mut self) {
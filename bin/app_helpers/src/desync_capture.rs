@@ -0,0 +1,88 @@
+use ofdm::circular_bucket::CircularBucket;
+use std::collections::VecDeque;
+use std::io::Write;
+
+/// Keeps the most recent raw input bytes so they can be saved as a reproducer once the demodulator starts desyncing rapidly.
+/// A desync storm is when the number of desyncs within a window of input reaches a threshold.
+/// The window is measured in input bytes instead of wall time so replaying a file faster than real time behaves the same.
+///
+/// # Examples
+/// ```
+/// use app_helpers::desync_capture::DesyncCapture;
+///
+/// // Keep the last 8 bytes and trigger on 3 desyncs within 10 bytes of input
+/// let mut capture = DesyncCapture::new(8, 3, 10);
+/// capture.push(&[0, 1, 2, 3]);
+/// assert!(!capture.update(1));
+///
+/// // Desyncs that are spread out don't trigger a capture
+/// capture.push(&[4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+/// assert!(!capture.update(2));
+/// capture.push(&[16, 17, 18, 19]);
+/// assert!(!capture.update(3));
+///
+/// // Several desyncs in one chunk of input are all counted
+/// capture.push(&[20, 21]);
+/// assert!(capture.update(5));
+/// let mut file = Vec::new();
+/// capture.write_to(&mut file).unwrap();
+/// assert_eq!(file, [14, 15, 16, 17, 18, 19, 20, 21]);
+///
+/// // The same desyncs don't trigger another capture
+/// capture.push(&[22]);
+/// assert!(!capture.update(5));
+/// ```
+pub struct DesyncCapture {
+    bytes: CircularBucket<u8>,
+    desync_positions: VecDeque<u64>,
+    trigger_desyncs: usize,
+    trigger_window_bytes: u64,
+    total_bytes: u64,
+    last_total_desyncs: u32,
+}
+
+impl DesyncCapture {
+    /// Panics if the capacity is zero.
+    pub fn new(capacity_bytes: usize, trigger_desyncs: usize, trigger_window_bytes: u64) -> Self {
+        assert!(capacity_bytes > 0, "Desync capture needs to hold at least one byte");
+        Self {
+            bytes: CircularBucket::new(capacity_bytes),
+            desync_positions: VecDeque::new(),
+            trigger_desyncs,
+            trigger_window_bytes,
+            total_bytes: 0,
+            last_total_desyncs: 0,
+        }
+    }
+
+    /// Appends raw input bytes and drops the oldest bytes once the capacity is reached.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.bytes.push_slice_overwrite(bytes);
+        self.total_bytes += bytes.len() as u64;
+    }
+
+    /// Updates the capture with the total number of desyncs after the pushed bytes were demodulated.
+    /// Returns true if the desyncs within the window reached the threshold.
+    /// The desyncs that triggered a capture are forgotten so the same storm is only reported once.
+    pub fn update(&mut self, total_desyncs: u32) -> bool {
+        let total_new_desyncs = total_desyncs.saturating_sub(self.last_total_desyncs);
+        self.last_total_desyncs = total_desyncs;
+        self.desync_positions.extend((0..total_new_desyncs).map(|_| self.total_bytes));
+        let window_start = self.total_bytes.saturating_sub(self.trigger_window_bytes);
+        while self.desync_positions.front().is_some_and(|position| *position <= window_start) {
+            self.desync_positions.pop_front();
+        }
+        if self.desync_positions.len() < self.trigger_desyncs {
+            return false;
+        }
+        self.desync_positions.clear();
+        true
+    }
+
+    /// Writes the stored bytes from oldest to newest.
+    pub fn write_to(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        let (head, tail) = self.bytes.as_slices();
+        writer.write_all(head)?;
+        writer.write_all(tail)
+    }
+}
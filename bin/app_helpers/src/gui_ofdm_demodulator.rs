@@ -1,7 +1,10 @@
-use ofdm::ofdm_demodulator::OfdmDemodulator;
+use ofdm::ofdm_demodulator::{OfdmDemodulator, DemodStats, FineTimeCorrelationMethod, FineTimeImpulseScale};
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use egui::Color32;
 use egui::plot::VLine;
-use egui::plot::{Plot, PlotPoints, Line, LineStyle, Corner, CoordinatesFormatter, Legend, Points};
+use egui::plot::{Plot, PlotPoints, PlotPoint, PlotImage, Line, LineStyle, Corner, CoordinatesFormatter, Legend, Points};
+use num::complex::Complex32;
 
 #[derive(PartialEq, Eq)]
 enum SelectedPlot {
@@ -9,7 +12,9 @@ enum SelectedPlot {
     NullPrs,
     FineTimeImpulseResponse,
     CoarseFrequencyImpulseResponse,
+    ChannelResponse,
     DqpskConstellation,
+    ConstellationHeatmap,
     BitsConstellation,
 }
 
@@ -17,33 +22,52 @@ enum SelectedPlot {
 pub struct GuiOfdmDemodulator {
     selected_dqpsk_symbol: usize,
     selected_plot: SelectedPlot,
+    is_paused: Arc<AtomicBool>,
+    heatmap_resolution: usize,
+    heatmap_is_all_symbols: bool,
+    heatmap_texture: Option<egui::TextureHandle>,
+    /// Plots of larger buffers only show every Nth sample so the number of points allocated each repaint is bounded.
+    plot_max_points: usize,
 }
 
 impl Default for GuiOfdmDemodulator {
     fn default() -> Self {
+        Self::new(Arc::new(AtomicBool::new(false)))
+    }
+}
+
+impl GuiOfdmDemodulator {
+    /// Creates the gui with a flag that is set when the user pauses the demodulator.
+    /// The thread feeding samples to the demodulator should skip processing while this is set.
+    pub fn new(is_paused: Arc<AtomicBool>) -> Self {
         Self {
             selected_dqpsk_symbol: 0,
             selected_plot: SelectedPlot::DqpskConstellation,
+            is_paused,
+            heatmap_resolution: 64,
+            heatmap_is_all_symbols: false,
+            heatmap_texture: None,
+            plot_max_points: 2048,
         }
     }
-}
 
-impl GuiOfdmDemodulator {
     /// Draws everything in demodulator.
-    pub fn draw_all(&mut self, demod: &mut OfdmDemodulator, ui: &mut egui::Ui) {
+    /// The statistics are drawn from a snapshot so the demodulator is only locked for the controls and plots.
+    pub fn draw_all(&mut self, demod: &RwLock<OfdmDemodulator>, ui: &mut egui::Ui) {
         ui.heading("DAB OFDM Demodulator");
         ui.separator();
-        self.draw_state(demod, ui);
+        let stats = demod.read().unwrap().stats_snapshot();
+        self.draw_state(&stats, ui);
         ui.separator();
+        let demod = &mut *demod.write().unwrap();
         self.draw_controls(demod, ui);
         ui.separator();
         self.draw_plots(demod, ui);
     }
 
     /// Draws current state of demodulator.
-    pub fn draw_state(&self, demod: &OfdmDemodulator, ui: &mut egui::Ui) {
-        let net_frequency_offset = demod.coarse_frequency_offset + demod.fine_frequency_offset;
-        let sample_rate: f32 = 2.048e6;
+    pub fn draw_state(&self, demod: &DemodStats, ui: &mut egui::Ui) {
+        let sync = &demod.sync_state;
 
         egui::Grid::new("Statistics")
             .num_columns(2)
@@ -55,28 +79,129 @@ impl GuiOfdmDemodulator {
                     ui.end_row();
                 };
 
-                create_label("State", format!("{:?}", demod.state));
+                create_label("State", demod.state.name().to_string());
+                create_label("Locked", format!("{}", demod.is_locked));
+                create_label("Samples to first lock", match demod.samples_to_first_lock {
+                    Some(total_samples) => format!("{}", total_samples),
+                    None => "Not locked".to_string(),
+                });
                 create_label("Total frames read", format!("{}", demod.total_frames_read));
                 create_label("Total frames desync", format!("{}", demod.total_frames_desync));
-                create_label("Fine frequency offset", format!("{:.2}", demod.fine_frequency_offset * sample_rate));
-                create_label("Coarse frequency offset", format!("{:.2}", demod.coarse_frequency_offset * sample_rate));
-                create_label("Net frequency offset", format!("{:.2}", net_frequency_offset * sample_rate));
-                create_label("Fine time offset", format!("{}", demod.fine_time_offset));
+                create_label("Non-finite samples", format!("{}", demod.total_non_finite_samples));
+                create_label("Consecutive desyncs", format!("{}", demod.total_consecutive_desyncs));
+                create_label("NULL search widened", format!("{}", demod.is_null_search_widened));
+                create_label("Fine frequency offset", format!("{:.2}", sync.fine_frequency_offset_hz));
+                create_label("Fine frequency drift", format!("{:.2}", demod.fine_frequency_drift_hz));
+                create_label("Coarse frequency offset", format!("{:.2}", sync.coarse_frequency_offset_hz));
+                create_label("Coarse frequency saturated", format!("{}", demod.coarse_frequency_saturated));
+                create_label("Net frequency offset", format!("{:.2}", sync.net_frequency_offset_hz));
+                create_label("Frequency offset jitter", format!("{:.2}", demod.frequency_offset_jitter_hz));
+                create_label("Fine time offset", format!("{} ({:.2})", sync.fine_time_offset, sync.fine_time_offset_subsample));
+                create_label("Fine time peak height", format!("{:.2} dB", demod.fine_time_peak_height_db));
                 create_label("Signal L1 average", format!("{}", demod.signal_l1_average));
+                create_label("NULL noise power", format!("{}", demod.null_symbol_noise_power));
+                create_label("DC offset", format!("{:.3}", demod.dc_offset));
+                create_label("IQ gain ratio", format!("{:.3}", demod.iq_imbalance_gain_ratio));
+                create_label("IQ phase error", format!("{:.2}°", demod.iq_imbalance_phase_error.to_degrees()));
+                create_label("AGC gain", format!("{:.3}", demod.agc_gain));
+                create_label("Raw BER estimate", format!("{:.4}", demod.raw_ber_estimate));
+                ui.strong("Frame progress");
+                ui.add(egui::ProgressBar::new(demod.frame_progress).show_percentage());
+                ui.end_row();
             });
     }
 
     /// Draws controls for demodulator.
     pub fn draw_controls(&self, demod: &mut OfdmDemodulator, ui: &mut egui::Ui) {
+        let is_paused = self.is_paused.load(Ordering::Relaxed);
+        if ui.button(if is_paused { "Resume" } else { "Pause" }).clicked() {
+            self.is_paused.store(!is_paused, Ordering::Relaxed);
+        }
+        let max_fine_time_search_radius = demod.params.nb_fft/2;
+        let params = demod.params;
         let settings = &mut demod.settings;
         ui.add(egui::Slider::new(&mut settings.null_power_threshold_start, 0.0..=settings.null_power_threshold_end).text("Null threshold start"));
         ui.add(egui::Slider::new(&mut settings.null_power_threshold_end, settings.null_power_threshold_start..=1.0).text("Null threshold end"));
+        ui.add(egui::Slider::new(&mut settings.null_power_min_dip_blocks, 1..=20).text("Null minimum dip blocks"));
+        ui.add(egui::Slider::new(&mut settings.null_power_widened_threshold_start, 0.0..=settings.null_power_threshold_end).text("Null widened threshold start"));
         ui.add(egui::Slider::new(&mut settings.null_power_update_beta, 0.0..=1.0).text("Null power update beta"));
         ui.add(egui::Slider::new(&mut settings.fine_frequency_update_beta, 0.0..=1.0).text("Fine frequency update beta"));
+        ui.checkbox(&mut settings.fine_frequency_pi_is_enabled, "Fine frequency PI loop");
+        ui.add_enabled(settings.fine_frequency_pi_is_enabled, egui::Slider::new(&mut settings.fine_frequency_pi_proportional_gain, 0.0..=1.0).text("Fine frequency proportional gain"));
+        ui.add_enabled(settings.fine_frequency_pi_is_enabled, egui::Slider::new(&mut settings.fine_frequency_pi_integral_gain, 0.0..=1.0).text("Fine frequency integral gain"));
+        ui.add(egui::Slider::new(&mut settings.fine_frequency_cyclic_prefix_fraction, 0.0..=1.0).text("Fine frequency cyclic prefix fraction"));
+        ui.horizontal(|ui| {
+            let mut is_wrapped = settings.fine_frequency_wrap_margin.is_some();
+            let mut margin = settings.fine_frequency_wrap_margin.unwrap_or(1.01);
+            ui.checkbox(&mut is_wrapped, "Fine frequency wrap");
+            ui.add_enabled(is_wrapped, egui::Slider::new(&mut margin, 1.0..=2.0).text("Margin"));
+            settings.fine_frequency_wrap_margin = is_wrapped.then_some(margin);
+        });
+        ui.add(egui::Slider::new(&mut settings.prefix_window_rolloff, 0.0..=1.0).text("Prefix window rolloff"));
+        ui.checkbox(&mut settings.symbol_streaming_is_enabled, "Stream symbols");
+        ui.checkbox(&mut settings.soft_bits_fixed_point_is_enabled, "Fixed point soft bits");
+        ui.checkbox(&mut settings.frequency_deinterleaving_is_enabled, "Frequency deinterleaving");
+        ui.checkbox(&mut settings.fine_time_fractional_delay_is_enabled, "Fractional fine time delay");
         ui.add(egui::Slider::new(&mut settings.coarse_frequency_slow_update_beta, 0.0..=1.0).text("Coarse frequency update beta"));
         ui.add(egui::Slider::new(&mut settings.coarse_frequency_max_range, 0.0..=0.95).text("Coarse frequency max range"));
+        ui.add(egui::Slider::new(&mut settings.coarse_frequency_peak_distance_penalty, 0.0..=0.99).text("Coarse frequency peak distance penalty"));
         ui.add(egui::Slider::new(&mut settings.fine_time_impulse_peak_threshold_db, 0.0..=100.0).text("Fine time impulse peak threshold dB"));
         ui.add(egui::Slider::new(&mut settings.fine_time_impulse_peak_distance_probability, 0.0..=1.0).text("Fine time impulse peak distance probability"));
+        ui.horizontal(|ui| {
+            ui.label("Fine time impulse scale");
+            ui.radio_value(&mut settings.fine_time_impulse_scale, FineTimeImpulseScale::Decibel, "Decibel");
+            ui.radio_value(&mut settings.fine_time_impulse_scale, FineTimeImpulseScale::Linear, "Linear");
+            ui.radio_value(&mut settings.fine_time_impulse_scale, FineTimeImpulseScale::Power, "Power");
+        });
+        ui.horizontal(|ui| {
+            let mut is_windowed = settings.fine_time_search_radius.is_some();
+            let mut radius = settings.fine_time_search_radius.unwrap_or(max_fine_time_search_radius/4);
+            ui.checkbox(&mut is_windowed, "Fine time search window");
+            ui.add_enabled(is_windowed, egui::Slider::new(&mut radius, 0..=max_fine_time_search_radius).text("Radius"));
+            settings.fine_time_search_radius = is_windowed.then_some(radius);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Fine time correlation");
+            ui.radio_value(&mut settings.fine_time_correlation_method, FineTimeCorrelationMethod::FftMultiply, "FFT multiply");
+            ui.radio_value(&mut settings.fine_time_correlation_method, FineTimeCorrelationMethod::DirectTime, "Direct time");
+        });
+        ui.add(egui::Slider::new(&mut settings.prs_averaging_beta, 0.0..=1.0).text("PRS averaging beta"));
+        ui.add(egui::Slider::new(&mut settings.max_consecutive_desyncs, 1..=10).text("Max consecutive desyncs"));
+        ui.checkbox(&mut settings.non_finite_sanitisation_enabled, "Replace non-finite samples");
+        ui.checkbox(&mut settings.dc_offset_correction_enabled, "DC offset correction");
+        ui.checkbox(&mut settings.iq_imbalance_correction_enabled, "IQ imbalance correction");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.agc_enabled, "AGC");
+            ui.add_enabled(settings.agc_enabled, egui::Slider::new(&mut settings.agc_target_level, 1.0..=128.0).text("Target level"));
+        });
+        // Frequency offsets are entered in Hz to match the statistics
+        ui.horizontal(|ui| {
+            let mut frequency = params.normalised_to_hz(settings.manual_coarse_frequency_offset);
+            ui.checkbox(&mut settings.freeze_coarse_frequency, "Freeze coarse frequency");
+            ui.add(egui::DragValue::new(&mut frequency).speed(100.0).suffix(" Hz"));
+            settings.manual_coarse_frequency_offset = params.hz_to_normalised(frequency);
+        });
+        ui.horizontal(|ui| {
+            let mut frequency = params.normalised_to_hz(settings.manual_fine_frequency_offset);
+            ui.checkbox(&mut settings.freeze_fine_frequency, "Freeze fine frequency");
+            ui.add(egui::DragValue::new(&mut frequency).speed(1.0).suffix(" Hz"));
+            settings.manual_fine_frequency_offset = params.hz_to_normalised(frequency);
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.freeze_fine_time, "Freeze fine time");
+            ui.add(egui::DragValue::new(&mut settings.manual_fine_time_offset).speed(1.0).suffix(" samples"));
+        });
+        if demod.replay_length() > 0 && ui.button("Replay last samples").clicked() {
+            demod.reprocess_last();
+        }
+        if ui.button("Copy settings").clicked() {
+            let text: String = demod.settings
+                .describe()
+                .iter()
+                .map(|(name, value)| format!("{} = {}\n", name, value))
+                .collect();
+            ui.output_mut(|output| output.copied_text = text);
+        }
     }
 
     /// Draws selected plot of some internal buffer for the demodulator.
@@ -97,9 +222,13 @@ impl GuiOfdmDemodulator {
             create_button(SelectedPlot::NullPrs, "NULL PRS");
             create_button(SelectedPlot::CoarseFrequencyImpulseResponse, "Coarse frequency");
             create_button(SelectedPlot::FineTimeImpulseResponse, "Fine time");
+            create_button(SelectedPlot::ChannelResponse, "Channel response");
             create_button(SelectedPlot::DqpskConstellation, "DQPSK constellation");
+            create_button(SelectedPlot::ConstellationHeatmap, "DQPSK heatmap");
             create_button(SelectedPlot::BitsConstellation, "Bits");
         });
+        ui.add(egui::Slider::new(&mut self.plot_max_points, 256..=16384).logarithmic(true).text("Max plot points"));
+        let max_points = self.plot_max_points;
 
         if self.selected_plot != SelectedPlot::None {
             ui.ctx().request_repaint();
@@ -109,10 +238,12 @@ impl GuiOfdmDemodulator {
             SelectedPlot::None => (),
             SelectedPlot::NullPrs => {
                 let buffer = demod.null_prs_buffer.raw_slice();
+                let step = get_decimation_step(buffer.len(), max_points);
 
                 let real_points: PlotPoints = buffer
                     .iter()
                     .enumerate()
+                    .step_by(step)
                     .map(|(x,y)| [ x as f64, y.re as f64 ])
                     .collect();
                 let real_line = Line::new(real_points);
@@ -120,6 +251,7 @@ impl GuiOfdmDemodulator {
                 let imag_points: PlotPoints = buffer
                     .iter()
                     .enumerate()
+                    .step_by(step)
                     .map(|(x,y)| [ x as f64, y.im as f64 ])
                     .collect();
                 let imag_line = Line::new(imag_points);
@@ -188,16 +320,38 @@ impl GuiOfdmDemodulator {
                         plot_ui.vline(vline_time_offset);
                     });
             },
-            SelectedPlot::DqpskConstellation => {
-                let buffer = &demod.data_dqpsk_buffer;
+            SelectedPlot::ChannelResponse => {
+                // Shift the FFT so the DC bin is centered
+                let buffer = &demod.channel_response_buffer;
+                let nb_fft = buffer.len();
+                let plot_points: PlotPoints = (0..nb_fft)
+                    .map(|i| (i, buffer[(i + nb_fft/2) % nb_fft]))
+                    .map(|(x,y)| [ x as f64, y as f64 ])
+                    .collect();
+                let plot_line = Line::new(plot_points);
 
+                let freq_center = params.nb_fft as f64 / 2.0;
+                let freq_half_width = params.nb_fft_data_carriers as f64 / 2.0;
+                let vline_freq_left  = VLine::new(freq_center - freq_half_width).color(Color32::DARK_BLUE);
+                let vline_freq_right = VLine::new(freq_center + freq_half_width).color(Color32::DARK_BLUE);
+
+                Plot::new("Channel response")
+                    .legend(Legend::default())
+                    .coordinates_formatter(Corner::LeftBottom, CoordinatesFormatter::default())
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(plot_line);
+                        plot_ui.vline(vline_freq_left);
+                        plot_ui.vline(vline_freq_right);
+                    });
+            },
+            SelectedPlot::DqpskConstellation => {
                 let total_symbols = params.nb_symbols-1;
-                let length = params.nb_fft_data_carriers;
                 let i = self.selected_dqpsk_symbol;
-                let data = &buffer[i*length..(i+1)*length];
+                let data = demod.dqpsk_symbols().nth(i).unwrap_or_default();
 
                 let points: PlotPoints = data 
                     .iter()
+                    .step_by(get_decimation_step(data.len(), max_points))
                     .map(|x| [ x.im as f64, x.re as f64 ])
                     .collect();
 
@@ -217,18 +371,54 @@ impl GuiOfdmDemodulator {
                         plot_ui.points(markers);
                     });
             },
-            SelectedPlot::BitsConstellation => {
-                let buffer = &demod.data_out_bits_buffer;
+            SelectedPlot::ConstellationHeatmap => {
+                let total_symbols = params.nb_symbols-1;
+                let i = self.selected_dqpsk_symbol;
+                let data = match self.heatmap_is_all_symbols {
+                    true => &demod.data_dqpsk_buffer[..],
+                    false => demod.dqpsk_symbols().nth(i).unwrap_or_default(),
+                };
 
+                ui.horizontal(|ui| {
+                    ui.add_enabled(
+                        !self.heatmap_is_all_symbols,
+                        egui::widgets::Slider::new(
+                            &mut self.selected_dqpsk_symbol, 
+                            0..=total_symbols-1)
+                            .text("DQPSK Symbol"));
+                    ui.checkbox(&mut self.heatmap_is_all_symbols, "All symbols");
+                });
+                ui.add(egui::widgets::Slider::new(&mut self.heatmap_resolution, 8..=256).text("Grid resolution"));
+
+                let (image, range) = create_heatmap_image(data, self.heatmap_resolution);
+                let texture = match &mut self.heatmap_texture {
+                    Some(texture) => {
+                        texture.set(image, egui::TextureOptions::NEAREST);
+                        texture
+                    },
+                    None => self.heatmap_texture.insert(ui.ctx().load_texture("DQPSK heatmap", image, egui::TextureOptions::NEAREST)),
+                };
+                let size = 2.0*range;
+                let plot_image = PlotImage::new(texture.id(), PlotPoint::new(0.0, 0.0), [size, size]);
+
+                Plot::new("DQPSK heatmap")
+                    .coordinates_formatter(Corner::LeftBottom, CoordinatesFormatter::default())
+                    .data_aspect(1.0)
+                    .show(ui, |plot_ui| {
+                        plot_ui.image(plot_image);
+                    });
+            },
+            SelectedPlot::BitsConstellation => {
                 let total_symbols = params.nb_symbols-1;
                 let i = self.selected_dqpsk_symbol;
-                let length = params.nb_fft_data_carriers*2;
-                let data = &buffer[i*length..(i+1)*length];
-                let real_data = &data[0..params.nb_fft_data_carriers];
-                let imag_data = &data[params.nb_fft_data_carriers..];
+                let data = demod.frame_soft_bits_by_symbol().nth(i).unwrap_or_default();
+                let (real_data, imag_data) = data.split_at(data.len()/2);
 
-                let points: PlotPoints = (0..params.nb_fft_data_carriers)
-                    .map(|i| [ real_data[i] as f64, imag_data[i] as f64 ] )
+                let points: PlotPoints = real_data
+                    .iter()
+                    .zip(imag_data.iter())
+                    .step_by(get_decimation_step(real_data.len(), max_points))
+                    .map(|(re, im)| [ *re as f64, *im as f64 ] )
                     .collect();
 
                 let markers = Points::new(points)
@@ -251,3 +441,49 @@ impl GuiOfdmDemodulator {
         };
     }
 }
+
+/// Returns the stride between plotted samples so a buffer is drawn with at most max_points points.
+fn get_decimation_step(length: usize, max_points: usize) -> usize {
+    length.div_ceil(max_points.max(1)).max(1)
+}
+
+/// Bins the DQPSK symbols into a square grid and colours each cell by the number of symbols in it.
+/// Returns the image and the range of values along each axis that the grid covers, centered at zero.
+fn create_heatmap_image(data: &[Complex32], resolution: usize) -> (egui::ColorImage, f32) {
+    let range = data
+        .iter()
+        .map(|x| x.re.abs().max(x.im.abs()))
+        .filter(|x| x.is_finite())
+        .fold(0.0f32, f32::max)
+        .max(f32::EPSILON);
+
+    // Same axes as the DQPSK constellation where the imaginary component is plotted along x
+    let mut counts = vec![0u32; resolution*resolution];
+    for x in data {
+        let to_cell = |v: f32| ((((v + range) / (2.0*range)) * resolution as f32) as isize).min(resolution as isize - 1);
+        let column = to_cell(x.im);
+        let row = (resolution as isize - 1) - to_cell(x.re);
+        if column < 0 || row < 0 || column >= resolution as isize || row >= resolution as isize {
+            continue;
+        }
+        counts[row as usize * resolution + column as usize] += 1;
+    }
+
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let pixels: Vec<Color32> = counts
+        .iter()
+        .map(|count| {
+            let t = (*count as f32) / max_count;
+            let r = (255.0 * t.sqrt()) as u8;
+            let g = (255.0 * t * t) as u8;
+            let b = (255.0 * (4.0 * t * (1.0-t)).min(1.0)) as u8;
+            Color32::from_rgb(r, g, b)
+        })
+        .collect();
+
+    let image = egui::ColorImage {
+        size: [resolution, resolution],
+        pixels,
+    };
+    (image, range)
+}
@@ -0,0 +1,106 @@
+use ofdm::ofdm_parameters::OfdmParameters;
+use num::complex::Complex32;
+use std::sync::Mutex;
+
+/// Scratch buffers used by the reader thread to convert and resample each chunk of raw IQ samples.
+/// They are reused between chunks and only reallocated if the chunk size grows.
+pub struct InputBuffers {
+    /// Raw unsigned 8bit IQ samples read from the input.
+    pub input_samples: Vec<[u8; 2]>,
+    /// Raw samples converted to complex samples before resampling.
+    pub complex_samples: Vec<Complex32>,
+    /// Complex samples after resampling to the demodulator sample rate.
+    pub resampled_samples: Vec<Complex32>,
+}
+
+impl InputBuffers {
+    pub fn new(number_of_input_samples: usize) -> Self {
+        let mut buffers = Self {
+            input_samples: vec![],
+            complex_samples: vec![],
+            resampled_samples: vec![],
+        };
+        buffers.resize(number_of_input_samples);
+        buffers
+    }
+
+    /// Resizes the raw input buffer to read a different number of samples in each chunk.
+    /// Capacity is kept when shrinking so going back to a larger chunk size doesn't reallocate.
+    pub fn resize(&mut self, number_of_input_samples: usize) {
+        self.input_samples.resize(number_of_input_samples, [0u8; 2]);
+        self.complex_samples.reserve(number_of_input_samples);
+        self.resampled_samples.reserve(number_of_input_samples);
+    }
+}
+
+/// A pool of soft bit buffers for handing OFDM frames from the reader thread to the writer thread.
+/// Each frame is copied into a buffer taken from the pool which the writer returns once it has written it.
+/// The buffers are sized from the OFDM parameters so a mode change only reallocates them if the frames get longer.
+///
+/// # Examples
+/// ```
+/// use app_helpers::io_buffers::FrameBuffers;
+/// use ofdm::ofdm_parameters::OfdmParameters;
+///
+/// let params_small = OfdmParameters::new(4, 80, 72, 64, 48);
+/// let params_large = OfdmParameters::new(8, 80, 72, 64, 48);
+/// let pool = FrameBuffers::new(&params_small);
+///
+/// let frame = pool.copy_frame(&[1, -2, 3]);
+/// assert_eq!(frame, [1, -2, 3]);
+/// let ptr = frame.as_ptr();
+/// pool.recycle(frame);
+///
+/// // Returned buffers are reused for the next frame
+/// let frame = pool.copy_frame(&[4, 5]);
+/// assert_eq!(frame, [4, 5]);
+/// assert_eq!(frame.as_ptr(), ptr);
+/// pool.recycle(frame);
+///
+/// // Longer frames of a new transmission mode fit without growing the buffer
+/// pool.resize(&params_large);
+/// let frame = pool.copy_frame(&vec![0; params_large.nb_output_bits]);
+/// assert!(frame.capacity() >= params_large.nb_output_bits);
+/// let ptr = frame.as_ptr();
+/// pool.recycle(frame);
+/// pool.resize(&params_small);
+/// let frame = pool.copy_frame(&vec![0; params_small.nb_output_bits]);
+/// assert_eq!(frame.as_ptr(), ptr);
+/// ```
+pub struct FrameBuffers {
+    nb_output_bits: Mutex<usize>,
+    spare_buffers: Mutex<Vec<Vec<i8>>>,
+}
+
+impl FrameBuffers {
+    pub fn new(params: &OfdmParameters) -> Self {
+        Self {
+            nb_output_bits: Mutex::new(params.nb_output_bits),
+            spare_buffers: Mutex::new(vec![]),
+        }
+    }
+
+    /// Grows the spare buffers so they fit a whole frame of a new transmission mode.
+    pub fn resize(&self, params: &OfdmParameters) {
+        *self.nb_output_bits.lock().unwrap() = params.nb_output_bits;
+        for buffer in self.spare_buffers.lock().unwrap().iter_mut() {
+            buffer.reserve(params.nb_output_bits.saturating_sub(buffer.len()));
+        }
+    }
+
+    /// Copies the soft bits of a frame into a spare buffer or a new one if they are all in use.
+    pub fn copy_frame(&self, bits: &[i8]) -> Vec<i8> {
+        let mut buffer = match self.spare_buffers.lock().unwrap().pop() {
+            Some(buffer) => buffer,
+            None => Vec::with_capacity(*self.nb_output_bits.lock().unwrap()),
+        };
+        buffer.clear();
+        buffer.extend_from_slice(bits);
+        buffer
+    }
+
+    /// Returns a buffer from copy_frame() to the pool once its frame has been used.
+    pub fn recycle(&self, buffer: Vec<i8>) {
+        self.spare_buffers.lock().unwrap().push(buffer);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod barrier;
+pub mod desync_capture;
+pub mod io_buffers;
 pub mod gui_ofdm_demodulator;
\ No newline at end of file
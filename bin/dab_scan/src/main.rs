@@ -0,0 +1,167 @@
+use ofdm::ofdm_demodulator::{OfdmDemodulator, FrameInfo};
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
+use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
+use dab_ofdm::dab_ofdm_parameters::get_dab_ofdm_parameters;
+use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+use dab_radio::fic::fic_decoder::FicDecoder;
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use num::complex::Complex32;
+use clap::Parser;
+use log::{debug, info, error};
+
+/// Reads a capture and prints the ensemble and service labels from the fast information channel (FIC) as they are decoded.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct AppArguments {
+    /// DAB transmission mode. Valid modes are \[1,2,3,4\]
+    #[arg(short, long, default_value_t = 1)]
+    mode: u32,
+    /// Number of samples to read in chunks from input file
+    #[arg(short, long, default_value_t = 4096*8)]
+    number_of_input_samples: usize,
+    /// Input filepath of unsigned 8bit IQ samples at 2.048MHz. If not provided uses stdin by default.
+    #[arg(short, long)]
+    input_filepath: Option<String>,
+    /// Maximum number of OFDM frames to decode before giving up on describing the ensemble
+    #[arg(long, default_value_t = 1000)]
+    max_frames: u64,
+    /// Number of OFDM frames without a new label after which the ensemble is considered fully described.
+    /// Labels are spread across many frames so this should cover a full cycle of the FIC
+    #[arg(long, default_value_t = 200)]
+    settle_frames: u64,
+}
+
+/// What has been printed about the ensemble so far.
+#[derive(Default)]
+struct EnsembleDescription {
+    ensemble_id: Option<u16>,
+    ensemble_label: Option<String>,
+    service_ids: BTreeSet<u32>,
+    last_change_frame: u64,
+}
+
+impl EnsembleDescription {
+    /// Prints anything in the FIC decoder that hasn't been printed yet.
+    fn update(&mut self, decoder: &FicDecoder, frame_counter: u64) {
+        if let (None, Some(info)) = (self.ensemble_id, decoder.ensemble_info()) {
+            println!("Ensemble ID: 0x{:04X}", info.ensemble_id);
+            self.ensemble_id = Some(info.ensemble_id);
+            self.last_change_frame = frame_counter;
+        }
+        if let (None, Some(label)) = (&self.ensemble_label, decoder.ensemble_label()) {
+            println!("Ensemble label: {}", label);
+            self.ensemble_label = Some(label);
+            self.last_change_frame = frame_counter;
+        }
+        for (id, label) in decoder.service_labels() {
+            if self.service_ids.insert(id) {
+                println!("Service 0x{:04X}: {}", id, label);
+                self.last_change_frame = frame_counter;
+            }
+        }
+    }
+
+    /// The services aren't listed anywhere that is decoded yet so the ensemble is complete once its labels stop changing.
+    fn is_complete(&self, frame_counter: u64, settle_frames: u64) -> bool {
+        self.ensemble_id.is_some() &&
+        self.ensemble_label.is_some() &&
+        !self.service_ids.is_empty() &&
+        frame_counter >= self.last_change_frame + settle_frames
+    }
+}
+
+fn main() -> Result<(), String> {
+    // Logs at the info level by default which can be changed with RUST_LOG
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = AppArguments::parse();
+
+    // Parse arguments
+    let transmission_mode = match args.mode {
+        1 => DabTransmissionMode::I,
+        2 => DabTransmissionMode::II,
+        3 => DabTransmissionMode::III,
+        4 => DabTransmissionMode::IV,
+        mode => return Err(format!("Invalid transmission mode index {}", mode)),
+    };
+    let number_of_input_samples = match args.number_of_input_samples {
+        0 => return Err("Number of input samples cannot be zero.".into()),
+        length => length,
+    };
+    let mut input_file: Box<dyn Read> = match &args.input_filepath {
+        None => Box::new(std::io::stdin()),
+        Some(filepath) => match std::fs::File::open(filepath) {
+            Ok(file) => Box::new(file),
+            Err(err) => return Err(format!("Failed to open input file {}: {}", filepath, err)),
+        },
+    };
+
+    // Setup OFDM demodulator
+    // Only the FIC symbols at the start of each frame are needed to describe the ensemble
+    let ofdm_params = get_dab_ofdm_parameters(transmission_mode);
+    let radio_params = get_dab_radio_parameters(transmission_mode);
+    let mut carrier_map = vec![0usize; ofdm_params.nb_fft_data_carriers];
+    let mut prs_fft = vec![Complex32::default(); ofdm_params.nb_fft];
+    get_dab_ofdm_carrier_map(&mut carrier_map, ofdm_params.nb_fft);
+    get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, transmission_mode);
+    let mut ofdm_demodulator = OfdmDemodulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    ofdm_demodulator.settings.max_output_symbols = Some(radio_params.nb_fic_symbols);
+
+    // This callback is invoked through ofdm_demod.process(...) in the same thread
+    let fic_decoder = Arc::new(Mutex::new(FicDecoder::new(radio_params)));
+    let total_frames = Arc::new(Mutex::new(0u64));
+    ofdm_demodulator.subscribe_frames_out({
+        let fic_decoder = fic_decoder.clone();
+        let total_frames = total_frames.clone();
+        move |bits: &[i8], info: &FrameInfo| {
+            debug!("[scan] Decoding FIC of frame {}", info.frame_counter);
+            fic_decoder.lock().unwrap().decode_fic(bits);
+            *total_frames.lock().unwrap() += 1;
+        }
+    });
+
+    let bytes_per_sample = 2;
+    let mut input_samples_buffer = vec![[0u8; 2]; number_of_input_samples];
+    let mut total_leftover_bytes = 0;
+    let mut description = EnsembleDescription::default();
+    loop {
+        let total_read = match input_file.read(&mut input_samples_buffer.as_flattened_mut()[total_leftover_bytes..]) {
+            Ok(0) => {
+                info!("[scan] Finished reading samples from input");
+                break;
+            },
+            Ok(length) => length,
+            Err(err) => {
+                error!("[scan] Error while reading from input: {}", err);
+                break;
+            },
+        };
+        // A short read can end partway through a sample so its bytes are carried over to the next read
+        // Otherwise the I and Q values of every sample after it would be swapped
+        let total_bytes = total_leftover_bytes + total_read;
+        let total_samples = total_bytes/bytes_per_sample;
+        ofdm_demodulator.process_raw(&input_samples_buffer[..total_samples]);
+        input_samples_buffer.as_flattened_mut().copy_within(total_samples*bytes_per_sample..total_bytes, 0);
+        total_leftover_bytes = total_bytes - total_samples*bytes_per_sample;
+
+        let frame_counter = *total_frames.lock().unwrap();
+        description.update(&fic_decoder.lock().unwrap(), frame_counter);
+        if description.is_complete(frame_counter, args.settle_frames) {
+            info!("[scan] Ensemble fully described after {} frames", frame_counter);
+            break;
+        }
+        if frame_counter >= args.max_frames {
+            info!("[scan] Stopped after {} frames", frame_counter);
+            break;
+        }
+    }
+
+    let fic_decoder = fic_decoder.lock().unwrap();
+    info!(
+        "[scan] {} FIBs passed and {} FIBs failed the CRC check",
+        fic_decoder.total_fibs_valid, fic_decoder.total_fibs_invalid,
+    );
+    Ok(())
+}
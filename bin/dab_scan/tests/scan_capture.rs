@@ -0,0 +1,121 @@
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
+use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
+use dab_ofdm::dab_ofdm_parameters::get_dab_ofdm_parameters;
+use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+use dab_radio::fic::fic_encoder::{FicEncoder, create_fib};
+use ofdm::ofdm_modulator::OfdmModulator;
+use num::complex::Complex32;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const TOTAL_FRAMES: usize = 6;
+const FREQUENCY_OFFSET_HZ: f32 = 600.0;
+/// Root mean square amplitude of the capture in 8bit steps which leaves headroom for the peaks of the OFDM signal.
+const CAPTURE_RMS: f32 = 32.0;
+
+/// FIG 1 label in the EBU Latin character set.
+fn fig_1(extension: u8, id: u16, label: &[u8; 16]) -> Vec<u8> {
+    let mut fig = vec![0x35, extension];
+    fig.extend_from_slice(&id.to_be_bytes());
+    fig.extend_from_slice(label);
+    fig.extend_from_slice(&[0xFF, 0x00]);
+    fig
+}
+
+/// Modulates a mode I capture of unsigned 8bit IQ samples whose FIC describes an ensemble with two services.
+/// Every FIB carries FIG 0/0 followed by one of the labels and the MSC is left as zeros.
+fn create_mode_i_capture() -> Vec<u8> {
+    let mode = DabTransmissionMode::I;
+    let ofdm_params = get_dab_ofdm_parameters(mode);
+    let radio_params = get_dab_radio_parameters(mode);
+    let mut carrier_map = vec![0usize; ofdm_params.nb_fft_data_carriers];
+    let mut prs_fft = vec![Complex32::default(); ofdm_params.nb_fft];
+    get_dab_ofdm_carrier_map(&mut carrier_map, ofdm_params.nb_fft);
+    get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, mode);
+
+    let labels = [
+        fig_1(0, 0xCE15, b"BBC National DAB"),
+        fig_1(1, 0xC221, b"BBC Radio 1     "),
+        fig_1(1, 0xC2A1, b"Radio \xC2ireann   "),
+    ];
+    let fibs: Vec<u8> = (0..radio_params.nb_fibs_in_fic)
+        .flat_map(|i| {
+            let mut figs = vec![0x05, 0x00, 0xCE, 0x15, 0x00, 0x00];
+            figs.extend_from_slice(&labels[i % labels.len()]);
+            create_fib(&figs)
+        })
+        .collect();
+    let mut bits = vec![false; ofdm_params.nb_output_bits];
+    FicEncoder::new(get_dab_radio_parameters(mode)).encode_fic(&fibs, &mut bits[..radio_params.nb_bits_in_fic]);
+
+    let mut modulator = OfdmModulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    let mut signal = Vec::new();
+    for _ in 0..TOTAL_FRAMES {
+        modulator.modulate_frame(&bits, &mut signal);
+    }
+    signal.extend((0..ofdm_params.nb_null_period+1).map(|_| Complex32::default()));
+
+    let rms = (signal.iter().map(|x| x.norm_sqr()).sum::<f32>() / (signal.len() as f32)).sqrt();
+    let step = 2.0*std::f32::consts::PI*ofdm_params.hz_to_normalised(FREQUENCY_OFFSET_HZ);
+    signal
+        .iter()
+        .enumerate()
+        .flat_map(|(i, x)| {
+            let y = x * Complex32::from_polar(CAPTURE_RMS/rms, step*(i as f32));
+            [y.re, y.im].map(|v| (v + 128.0).round().clamp(0.0, 255.0) as u8)
+        })
+        .collect()
+}
+
+const EXPECTED_LINES: [&str; 4] = [
+    "Ensemble ID: 0xCE15",
+    "Ensemble label: BBC National DAB",
+    "Service 0xC221: BBC Radio 1",
+    "Service 0xC2A1: Radio Éireann",
+];
+
+#[test]
+fn prints_labels_of_modulated_capture() {
+    let filepath = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("dab_scan_mode_i.raw");
+    std::fs::write(&filepath, create_mode_i_capture()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dab_scan"))
+        .arg("--input-filepath").arg(&filepath)
+        .args(["--mode", "1", "--settle-frames", "2"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, EXPECTED_LINES);
+}
+
+#[test]
+fn carries_partial_samples_over_between_reads_from_stdin() {
+    let capture = create_mode_i_capture();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dab_scan"))
+        .args(["--mode", "1", "--settle-frames", "2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Writing the first byte by itself makes the first read end partway through a sample
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        stdin.write_all(&capture[..1]).and_then(|_| stdin.flush()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        // The scan can finish before the whole capture is written
+        let _ = stdin.write_all(&capture[1..]);
+    });
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, EXPECTED_LINES);
+}
@@ -1,11 +1,44 @@
 use app_helpers::gui_ofdm_demodulator::GuiOfdmDemodulator;
 use app_helpers::barrier::Barrier; 
-use ofdm::ofdm_demodulator::OfdmDemodulator;
+use app_helpers::io_buffers::{InputBuffers, FrameBuffers};
+use app_helpers::desync_capture::DesyncCapture;
+use ofdm::ofdm_demodulator::{OfdmDemodulator, FrameInfo, DesyncInfo, pack_hard_bits};
+use ofdm::iq_sample::{IqSample, SampleFormat};
+use ofdm::resample::Resampler;
+use dab_core::dab_parameters::DAB_SAMPLE_RATE;
 use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_core::dab_parameters::get_dab_parameters;
+use dab_ofdm::dab_ofdm_mode_detection::detect_transmission_mode;
 use std::io::{Read, Write, BufWriter};
 use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 use num::complex::Complex32;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use log::{debug, info, warn, error};
+
+mod self_test;
+#[cfg(feature = "rtlsdr")]
+mod rtlsdr_reader;
+#[cfg(feature = "rtlsdr")]
+use rtlsdr_reader::RtlSdrReader;
+
+/// Number of frames of the longest transmission mode to read from the start of the input when detecting the mode.
+const AUTO_MODE_TOTAL_FRAMES: usize = 2;
+
+/// Magic bytes at the start of each framed record.
+const FRAME_HEADER_MAGIC: [u8; 4] = *b"DABF";
+
+/// How the soft bits of each OFDM frame are written to the output.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputFormat {
+    /// Soft bits of each frame are written back to back with no delimiters.
+    Raw,
+    /// Soft bits of each frame are prefixed with a header so consumers can resynchronise.
+    /// | magic "DABF" (4) | mode (1) | frame counter (4, LE) | length in bytes (4, LE) | soft bits (length) |
+    Framed,
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -13,6 +46,9 @@ struct AppArguments {
     /// DAB transmission mode. Valid modes are \[1,2,3,4\] 
     #[arg(short, long, default_value_t = 1)]
     mode: u32,
+    /// Detect the transmission mode from the start of the input. Uses --mode if no mode is detected
+    #[arg(long)]
+    auto_mode: bool,
     /// Number of samples to read in chunks from input file
     #[arg(short, long, default_value_t = 4096*8)]
     number_of_input_samples: usize,
@@ -25,6 +61,102 @@ struct AppArguments {
     /// Start the application without a GUI
     #[arg(long)]
     nogui: bool,
+    /// Output filepath for demodulator metrics as JSON lines. Use "-" for stdout if the soft bits are written to a file.
+    #[arg(long)]
+    metrics_json: Option<String>,
+    /// Number of milliseconds between each line of metrics
+    #[arg(long, default_value_t = 1000)]
+    stats_interval: u64,
+    /// Disable coarse frequency correction if the input is already frequency accurate
+    #[arg(long)]
+    no_coarse_freq: bool,
+    /// Maximum coarse frequency offset to search as a fraction of the sampling frequency. Valid range is [0,1)
+    #[arg(long)]
+    coarse_freq_range: Option<f32>,
+    /// Format of the soft bits written to the output
+    #[arg(long, value_enum, default_value_t = OutputFormat::Raw)]
+    output_format: OutputFormat,
+    /// Output hard decision bits packed MSB first into bytes instead of soft decision bits
+    #[arg(long)]
+    hard_bits: bool,
+    /// Only demodulate and output the fast information channel (FIC) of each frame for scanning the ensemble structure.
+    /// The whole frame is still read for synchronisation
+    #[arg(long)]
+    fic_only: bool,
+    /// Output the soft bits of each symbol in the physical carrier order instead of undoing the frequency interleaving
+    #[arg(long)]
+    physical_carrier_order: bool,
+    /// Output filepath to append the NULL and PRS samples of each failed fine time synchronisation.
+    /// Each sample is written as a pair of 32bit little endian floats for the real and imaginary parts.
+    #[arg(long)]
+    dump_desync: Option<String>,
+    /// Output filepath to save the most recent raw input bytes to once the demodulator starts desyncing rapidly.
+    /// This is a reproducer for bug reports that is only written for the first burst of desyncs
+    #[arg(long)]
+    capture_desync: Option<String>,
+    /// Number of megabytes of the most recent raw input to keep for --capture-desync
+    #[arg(long, default_value_t = 8)]
+    capture_desync_mb: usize,
+    /// Number of desyncs within --capture-desync-window that triggers --capture-desync
+    #[arg(long, default_value_t = 5)]
+    capture_desync_count: usize,
+    /// Number of seconds of input that --capture-desync-count desyncs need to occur within to trigger --capture-desync
+    #[arg(long, default_value_t = 10.0)]
+    capture_desync_window: f32,
+    /// Sampling frequency of the input in Hz. Inputs that aren't sampled at 2.048MHz are resampled
+    #[arg(long, default_value_t = DAB_SAMPLE_RATE as u32)]
+    input_sample_rate: u32,
+    /// Run the reference modulator through the demodulator for each transmission mode and exit.
+    /// Prints whether the random bits were recovered and the processing throughput. Exits with an error on any mismatch
+    #[arg(long)]
+    self_test: bool,
+    /// Index of the RTL-SDR dongle to receive from instead of the input file. Requires --frequency
+    #[cfg(feature = "rtlsdr")]
+    #[arg(long)]
+    device: Option<u32>,
+    /// Centre frequency in Hz to tune the RTL-SDR dongle to. Requires --device
+    #[cfg(feature = "rtlsdr")]
+    #[arg(long)]
+    frequency: Option<u32>,
+}
+
+/// A snapshot of the demodulator statistics for monitoring.
+#[derive(Serialize)]
+struct DemodulatorMetrics {
+    state: String,
+    state_id: u8,
+    total_frames_read: u32,
+    total_frames_desync: u32,
+    total_frames_dropped: u64,
+    total_non_finite_samples: u64,
+    signal_l1_average: f32,
+    coarse_frequency_offset: f32,
+    coarse_frequency_saturated: bool,
+    fine_frequency_offset: f32,
+    net_frequency_offset_hz: f32,
+    frequency_offset_jitter_hz: f32,
+    fine_time_offset: isize,
+}
+
+impl DemodulatorMetrics {
+    fn new(demod: &OfdmDemodulator, total_frames_dropped: u64) -> Self {
+        let sync = demod.sync_state();
+        Self {
+            state: format!("{:?}", demod.state),
+            state_id: demod.state.as_u8(),
+            total_frames_read: demod.total_frames_read,
+            total_frames_desync: demod.total_frames_desync,
+            total_frames_dropped,
+            total_non_finite_samples: demod.total_non_finite_samples,
+            signal_l1_average: demod.signal_l1_average,
+            coarse_frequency_offset: sync.coarse_frequency_offset,
+            coarse_frequency_saturated: demod.coarse_frequency_saturated,
+            fine_frequency_offset: sync.fine_frequency_offset,
+            net_frequency_offset_hz: sync.net_frequency_offset_hz,
+            frequency_offset_jitter_hz: demod.frequency_offset_jitter_hz(),
+            fine_time_offset: sync.fine_time_offset,
+        }
+    }
 }
 
 struct AppGui {
@@ -33,7 +165,12 @@ struct AppGui {
 }
 
 fn main() -> Result<(), String> {
+    // Logs at the info level by default which can be changed with RUST_LOG
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
     let args = AppArguments::parse();
+    if args.self_test {
+        return self_test::run_self_test();
+    }
 
     // Parse arguments
     let transmission_mode = match args.mode {
@@ -44,12 +181,22 @@ fn main() -> Result<(), String> {
         mode => return Err(format!("Invalid transmission mode index {}", mode)),
     };
     let number_of_input_samples = match args.number_of_input_samples {
-        length if length == 0 => return Err("Number of input samples cannot be zero.".into()),
+        0 => return Err("Number of input samples cannot be zero.".into()),
         length => length,
     };
-    let mut input_file: Box<dyn Read + Send + Sync> = match &args.input_filepath {
-        None => Box::new(std::io::stdin()),
-        Some(filepath) => match std::fs::File::open(filepath) {
+    #[cfg(feature = "rtlsdr")]
+    let device_input: Option<Box<dyn Read + Send>> = match (args.device, args.frequency) {
+        (None, None) => None,
+        (Some(_), Some(_)) if args.input_filepath.is_some() => return Err("An input file cannot be used while receiving from an RTL-SDR dongle.".into()),
+        (Some(index), Some(frequency)) => Some(Box::new(RtlSdrReader::open(index, frequency, args.input_sample_rate)?)),
+        _ => return Err("Both --device and --frequency are needed to receive from an RTL-SDR dongle.".into()),
+    };
+    #[cfg(not(feature = "rtlsdr"))]
+    let device_input: Option<Box<dyn Read + Send>> = None;
+    let mut input_file: Box<dyn Read + Send> = match (device_input, &args.input_filepath) {
+        (Some(device_input), _) => device_input,
+        (None, None) => Box::new(std::io::stdin()),
+        (None, Some(filepath)) => match std::fs::File::open(filepath) {
             Ok(file) => Box::new(file),
             Err(err) => return Err(format!("Failed to open input file {}: {}", filepath, err)),
         },
@@ -62,6 +209,63 @@ fn main() -> Result<(), String> {
         },
     };
 
+    let metrics_file: Option<Box<dyn Write + Send + Sync>> = match &args.metrics_json {
+        None => None,
+        Some(filepath) if filepath == "-" => match &args.output_filepath {
+            None => return Err("Metrics cannot be written to stdout since it is used for the soft bits output.".into()),
+            Some(_) => Some(Box::new(std::io::stdout())),
+        },
+        Some(filepath) => match std::fs::File::create(filepath) {
+            Ok(file) => Some(Box::new(BufWriter::new(file))),
+            Err(err) => return Err(format!("Failed to open metrics file {}: {}", filepath, err)),
+        },
+    };
+    let desync_file: Option<BufWriter<std::fs::File>> = match &args.dump_desync {
+        None => None,
+        Some(filepath) => match std::fs::OpenOptions::new().create(true).append(true).open(filepath) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(err) => return Err(format!("Failed to open desync dump file {}: {}", filepath, err)),
+        },
+    };
+    let desync_capture = match &args.capture_desync {
+        None => None,
+        Some(_) if args.capture_desync_mb == 0 => return Err("Desync capture size cannot be zero.".into()),
+        Some(_) if args.capture_desync_count == 0 => return Err("Desync capture count cannot be zero.".into()),
+        Some(_) if args.capture_desync_window <= 0.0 => return Err(format!("Invalid desync capture window {}", args.capture_desync_window)),
+        Some(filepath) => Some((filepath.clone(), args.capture_desync_mb*1024*1024)),
+    };
+    let stats_interval = match args.stats_interval {
+        0 => return Err("Stats interval cannot be zero.".into()),
+        interval => Duration::from_millis(interval),
+    };
+    let input_sample_rate = match args.input_sample_rate {
+        0 => return Err("Input sample rate cannot be zero.".into()),
+        rate => rate,
+    };
+    let coarse_freq_range = match args.coarse_freq_range {
+        Some(range) if !(0.0..1.0).contains(&range) => return Err(format!("Invalid coarse frequency range {}", range)),
+        range => range,
+    };
+
+    // The samples read to detect the transmission mode are put back in front of the input
+    let transmission_mode = match args.auto_mode {
+        false => transmission_mode,
+        true => {
+            let (input_prefix, detected_mode) = read_and_detect_transmission_mode(&mut input_file, input_sample_rate)?;
+            input_file = Box::new(std::io::Cursor::new(input_prefix).chain(input_file));
+            match detected_mode {
+                Some(mode) => {
+                    info!("[main_thread] Detected transmission mode {}", get_transmission_mode_index(mode));
+                    mode
+                },
+                None => {
+                    warn!("[main_thread] Couldn't detect the transmission mode, using mode {}", args.mode);
+                    transmission_mode
+                },
+            }
+        },
+    };
+
     // Setup OFDM demodulator
     use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
     use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
@@ -71,118 +275,305 @@ fn main() -> Result<(), String> {
     let mut prs_fft = vec![Complex32::default(); ofdm_params.nb_fft];
     get_dab_ofdm_carrier_map(&mut carrier_map, ofdm_params.nb_fft);
     get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, transmission_mode);
-    let ofdm_demodulator = Arc::new(RwLock::new(OfdmDemodulator::new(&ofdm_params, &carrier_map, &prs_fft)));
+    let mut ofdm_demodulator = OfdmDemodulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    ofdm_demodulator.settings.coarse_frequency_is_enabled = !args.no_coarse_freq;
+    if let Some(range) = coarse_freq_range {
+        ofdm_demodulator.settings.coarse_frequency_max_range = range;
+    }
+    ofdm_demodulator.settings.frequency_deinterleaving_is_enabled = !args.physical_carrier_order;
+    if args.fic_only {
+        ofdm_demodulator.settings.max_output_symbols = Some(get_dab_parameters(transmission_mode).nb_fic_symbols);
+    }
+    if let Some(mut desync_file) = desync_file {
+        // The dump only holds samples so the offset of each capture is logged to match them up
+        ofdm_demodulator.subscribe_desync_events(move |buf: &[Complex32], info: &DesyncInfo| {
+            debug!(
+                "[reader_thread_desync] Captured desync with PRS offset {} and impulse peak {:.2}dB",
+                info.prs_start_offset, info.peak_height_db,
+            );
+            let res = buf
+                .iter()
+                .try_for_each(|x| desync_file.write_all(&x.re.to_le_bytes()).and_then(|_| desync_file.write_all(&x.im.to_le_bytes())))
+                .and_then(|_| desync_file.flush());
+            if let Err(err) = res {
+                error!("[reader_thread_desync] Error while writing desync capture: {}", err);
+            }
+        });
+    }
+    if !args.nogui {
+        // Store enough samples to replay at least one full frame when tuning settings in the gui
+        ofdm_demodulator.set_replay_length(ofdm_params.nb_input_samples*2);
+    }
+    // The settings are logged so bug reports include the exact configuration
+    let settings_description: Vec<String> = ofdm_demodulator.settings
+        .describe()
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    info!(
+        "[main_thread] Demodulating transmission mode {} at {}Hz with settings: {}",
+        get_transmission_mode_index(transmission_mode), input_sample_rate, settings_description.join(", "),
+    );
+    let ofdm_demodulator = Arc::new(RwLock::new(ofdm_demodulator));
 
     // Setup input and output buffers
     let bytes_per_sample = 2;
-    let mut input_bytes_buffer = vec![0u8; number_of_input_samples*bytes_per_sample];
-    let mut input_samples_buffer = vec![Complex32::default(); number_of_input_samples];
-    let intermediate_buffer = Arc::new(RwLock::new(vec![0i8; ofdm_params.nb_output_bits]));
-    let intermediate_buffer_barrier = Arc::new(Barrier::new(false));
+    let mut input_buffers = InputBuffers::new(number_of_input_samples);
+    // The demodulator only accepts the sample rate of its parameters so other sample rates go through a resampler first
+    // The passband covers the data carriers so they aren't distorted by the transition band
+    let output_sample_rate = ofdm_params.sample_rate as u32;
+    let passband = (ofdm_params.nb_fft_data_carriers as f32) / (ofdm_params.nb_fft as f32);
+    let mut resampler = (input_sample_rate != output_sample_rate)
+        .then(|| Resampler::new(input_sample_rate, output_sample_rate, passband));
+    // The trigger window is converted to raw input bytes so it is measured in input time
+    let mut desync_capture = desync_capture.map(|(filepath, capacity_bytes)| {
+        let trigger_window_bytes = (args.capture_desync_window * (input_sample_rate as f32)) as u64 * (bytes_per_sample as u64);
+        (filepath, DesyncCapture::new(capacity_bytes, args.capture_desync_count, trigger_window_bytes))
+    });
+    // The demodulator hands each frame to the writer through a single slot so frames are never silently overwritten
+    // The soft bits of each frame are copied into buffers that the writer returns so they aren't reallocated every frame
+    let frame_buffers = Arc::new(FrameBuffers::new(&ofdm_params));
+    let intermediate_buffer_barrier = Arc::new(Barrier::<Option<(Vec<i8>, FrameInfo)>>::new(None));
+
+    let is_paused = Arc::new(AtomicBool::new(false));
+    // The reader blocks until the writer has taken each frame so this only counts frames lost to a closed output
+    let total_frames_dropped = Arc::new(AtomicU64::new(0));
+
+    // This callback is invoked through ofdm_demod.process(...) in the same thread
+    // It is subscribed before the reader thread starts so the first frames aren't missed
+    ofdm_demodulator.write().unwrap().subscribe_frames_out({
+        let intermediate_buffer_barrier = intermediate_buffer_barrier.clone();
+        let frame_buffers = frame_buffers.clone();
+        let total_frames_dropped = total_frames_dropped.clone();
+        move |x: &[i8], info: &FrameInfo| {
+            if let Err(err) = intermediate_buffer_barrier.put((frame_buffers.copy_frame(x), *info)) {
+                total_frames_dropped.fetch_add(1, Ordering::Relaxed);
+                warn!("[reader_thread_bits_out] Intermediate buffer couldn't be updated: {:?}", err);
+            }
+        }
+    });
 
     // Setup threads
     let reader_thread = std::thread::spawn({
         let ofdm_demodulator = ofdm_demodulator.clone();
         let intermediate_buffer_barrier = intermediate_buffer_barrier.clone();
+        let is_paused = is_paused.clone();
         move || {
+            let mut is_coarse_frequency_saturated = false;
             loop {
-                let total_samples = match input_file.read(&mut input_bytes_buffer) {
+                let total_samples = match input_file.read(input_buffers.input_samples.as_flattened_mut()) {
                     Ok(0) => {
-                        eprintln!("[reader_thread] Finished reading samples from input");
+                        info!("[reader_thread] Finished reading samples from input");
                         break;
                     },
                     Ok(length) => length/bytes_per_sample,
                     Err(err) => {
-                        eprintln!("[reader_thread] Error while reading from input: {}", err);
+                        error!("[reader_thread] Error while reading from input: {}", err);
                         break;
                     },
                 };
-                input_bytes_buffer[0..total_samples*bytes_per_sample]
-                    .chunks_exact(bytes_per_sample)
-                    .enumerate()
-                    .for_each(|(i, x)| {
-                    let dc_offset = 128.0;
-                        input_samples_buffer[i].re = x[0] as f32 - dc_offset;
-                        input_samples_buffer[i].im = x[1] as f32 - dc_offset;
-                    });
-                if let Err(err) = intermediate_buffer_barrier.wait(|is_full| !is_full) {
-                    eprintln!("[reader_thread] Intermediate buffer stopped responding: {:?}", err);
+                // Keep reading while paused so the input doesn't back up
+                if is_paused.load(Ordering::Relaxed) {
+                    continue;
+                }
+                if let Err(err) = intermediate_buffer_barrier.wait_until_empty() {
+                    warn!("[reader_thread] Intermediate buffer stopped responding: {:?}", err);
                     break;
                 }
-                ofdm_demodulator.write().unwrap().process(&input_samples_buffer[..total_samples]);
+                let InputBuffers { input_samples, complex_samples, resampled_samples } = &mut input_buffers;
+                let input_samples = &input_samples[..total_samples];
+                if let Some((_, capture)) = &mut desync_capture {
+                    capture.push(input_samples.as_flattened());
+                }
+                let mut demod = ofdm_demodulator.write().unwrap();
+                match &mut resampler {
+                    None => demod.process_raw(input_samples),
+                    Some(resampler) => {
+                        complex_samples.clear();
+                        complex_samples.extend(input_samples.iter().map(|x| x.to_complex32()));
+                        resampled_samples.clear();
+                        resampler.process(complex_samples, resampled_samples);
+                        demod.process(resampled_samples);
+                    },
+                }
+                if demod.coarse_frequency_saturated && !is_coarse_frequency_saturated {
+                    warn!("[reader_thread] Coarse frequency offset is stuck at the edge of the search range, try increasing --coarse-freq-range");
+                }
+                is_coarse_frequency_saturated = demod.coarse_frequency_saturated;
+                let is_desync_storm = desync_capture
+                    .as_mut()
+                    .is_some_and(|(_, capture)| capture.update(demod.total_frames_desync));
+                if is_desync_storm {
+                    if let Some((filepath, capture)) = desync_capture.take() {
+                        let res = std::fs::File::create(&filepath)
+                            .and_then(|file| {
+                                let mut file = BufWriter::new(file);
+                                capture.write_to(&mut file).and_then(|_| file.flush())
+                            });
+                        match res {
+                            Ok(()) => warn!("[reader_thread] Demodulator is desyncing rapidly, saved the recent raw input to {}", filepath),
+                            Err(err) => error!("[reader_thread] Error while writing desync capture {}: {}", filepath, err),
+                        }
+                    }
+                }
+            }
+            // Output the symbols of a frame that the input ended partway through
+            if intermediate_buffer_barrier.wait_until_empty().is_ok() && ofdm_demodulator.write().unwrap().flush() {
+                info!("[reader_thread] Flushed the last frame of the input");
             }
             if let Err(err) = intermediate_buffer_barrier.close() {
-                eprintln!("[reader_thread] Error while closing intermediate buffer: {:?}", err);
+                debug!("[reader_thread] Error while closing intermediate buffer: {:?}", err);
             } else {
-                eprintln!("[reader_thread] Successfully closed intermediate buffer");
-            }
-        }
-    });
-
-    // This callback is invoked through ofdm_demod.process(...) in the same thread
-    ofdm_demodulator.write().unwrap().subscribe_bits_out({
-        let intermediate_buffer = intermediate_buffer.clone();
-        let intermediate_buffer_barrier = intermediate_buffer_barrier.clone();
-        move |x: &[i8]| {
-            let soft_bits = &mut *intermediate_buffer.write().unwrap();
-            soft_bits.copy_from_slice(x);
-            if let Err(err) = intermediate_buffer_barrier.set(true) {
-                eprintln!("[reader_thread_bits_out] Intermediate buffer couldn't be updated: {:?}", err);
+                debug!("[reader_thread] Successfully closed intermediate buffer");
             }
         }
     });
 
     let writer_thread = std::thread::spawn({
-        let intermediate_buffer = intermediate_buffer.clone();
         let intermediate_buffer_barrier = intermediate_buffer_barrier.clone();
+        let output_format = args.output_format;
+        let mode = get_transmission_mode_index(transmission_mode);
+        let is_hard_bits = args.hard_bits;
+        let frame_buffers = frame_buffers.clone();
+        let total_frames_dropped = total_frames_dropped.clone();
         move || {
+            let mut last_frame_counter: Option<u64> = None;
+            let mut hard_bits = vec![];
             loop {
-                if let Err(err) = intermediate_buffer_barrier.wait(|is_full| *is_full) {
-                    eprintln!("[writer_thread] Intermediate buffer stopped responding: {:?}", err);
-                    break;
-                }
-                let soft_bits = &*intermediate_buffer.read().unwrap();
-                let data_out = unsafe { 
-                    std::slice::from_raw_parts(soft_bits.as_ptr() as *const u8, soft_bits.len()) 
+                let (soft_bits, frame_info) = match intermediate_buffer_barrier.take() {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        debug!("[writer_thread] Intermediate buffer stopped responding: {:?}", err);
+                        break;
+                    },
                 };
-                if let Err(err) = output_file.write_all(data_out) {
-                    eprintln!("[writer_thread] Error while writing to output: {}", err);
-                    break;
+                if let Some(last_frame_counter) = last_frame_counter {
+                    let total_dropped = frame_info.frame_counter.saturating_sub(last_frame_counter + 1);
+                    if total_dropped > 0 {
+                        total_frames_dropped.fetch_add(total_dropped, Ordering::Relaxed);
+                        warn!("[writer_thread] Dropped {} frames before frame {}", total_dropped, frame_info.frame_counter);
+                    }
                 }
-                if let Err(err) = intermediate_buffer_barrier.set(false) {
-                    eprintln!("[writer_thread] Intermediate buffer couldn't be released: {:?}", err);
+                last_frame_counter = Some(frame_info.frame_counter);
+                let data_out = if is_hard_bits {
+                    hard_bits.resize(soft_bits.len().div_ceil(8), 0u8);
+                    pack_hard_bits(&soft_bits, &mut hard_bits);
+                    &hard_bits[..]
+                } else {
+                    unsafe { 
+                        std::slice::from_raw_parts(soft_bits.as_ptr() as *const u8, soft_bits.len()) 
+                    }
+                };
+                let res = match output_format {
+                    OutputFormat::Raw => Ok(()),
+                    OutputFormat::Framed => write_frame_header(&mut output_file, mode, frame_info.frame_counter as u32, data_out.len()),
+                }.and_then(|_| output_file.write_all(data_out));
+                frame_buffers.recycle(soft_bits);
+                if let Err(err) = res {
+                    error!("[writer_thread] Error while writing to output: {}", err);
                     break;
                 }
             }
             if let Err(err) = intermediate_buffer_barrier.close() {
-                eprintln!("[writer_thread] Error while closing intermediate buffer: {:?}", err);
+                debug!("[writer_thread] Error while closing intermediate buffer: {:?}", err);
             } else {
-                eprintln!("[writer_thread] Successfully closed intermediate buffer");
+                debug!("[writer_thread] Successfully closed intermediate buffer");
             }
         }
     });
 
+    let is_running = Arc::new(AtomicBool::new(true));
+    let metrics_thread = metrics_file.map(|mut metrics_file| std::thread::spawn({
+        let ofdm_demodulator = ofdm_demodulator.clone();
+        let is_running = is_running.clone();
+        let total_frames_dropped = total_frames_dropped.clone();
+        move || {
+            while is_running.load(Ordering::Relaxed) {
+                std::thread::sleep(stats_interval);
+                let metrics = DemodulatorMetrics::new(&ofdm_demodulator.read().unwrap(), total_frames_dropped.load(Ordering::Relaxed));
+                let res = serde_json::to_writer(&mut metrics_file, &metrics)
+                    .map_err(std::io::Error::from)
+                    .and_then(|_| writeln!(metrics_file))
+                    .and_then(|_| metrics_file.flush());
+                if let Err(err) = res {
+                    error!("[metrics_thread] Error while writing metrics: {}", err);
+                    break;
+                }
+            }
+        }
+    }));
+
     // Handle closing
     if !args.nogui {
-        if let Err(err) = launch_gui(ofdm_demodulator.clone()) {
-            eprintln!("[main_thread] Error while running gui: {}", err);
+        if let Err(err) = launch_gui(ofdm_demodulator.clone(), is_paused.clone()) {
+            error!("[main_thread] Error while running gui: {}", err);
         }
         if let Err(err) = intermediate_buffer_barrier.close() {
-            eprintln!("[main_thread] Error while closing intermediate buffer: {:?}", err);
+            debug!("[main_thread] Error while closing intermediate buffer: {:?}", err);
         } else {
-            eprintln!("[main_thread] Successfully closed intermediate buffer");
+            debug!("[main_thread] Successfully closed intermediate buffer");
         }
     }
     if let Err(err) = reader_thread.join() {
-        eprintln!("[main_thread] Reader thread should terminate gracefully: {:?}", err);
+        error!("[main_thread] Reader thread should terminate gracefully: {:?}", err);
     };
     if let Err(err) = writer_thread.join() {
-        eprintln!("[main_thread] Writer thread should terminate gracefully: {:?}", err);
+        error!("[main_thread] Writer thread should terminate gracefully: {:?}", err);
+    }
+    match total_frames_dropped.load(Ordering::Relaxed) {
+        0 => (),
+        total => warn!("[main_thread] {} frames were dropped before they were written to the output", total),
+    }
+    is_running.store(false, Ordering::Relaxed);
+    if let Some(Err(err)) = metrics_thread.map(|thread| thread.join()) {
+        error!("[main_thread] Metrics thread should terminate gracefully: {:?}", err);
     }
     Ok(())
 }
 
-fn launch_gui(demod: Arc<RwLock<OfdmDemodulator>>) -> Result<(), eframe::Error> {
+fn get_transmission_mode_index(transmission_mode: DabTransmissionMode) -> u8 {
+    match transmission_mode {
+        DabTransmissionMode::I => 1,
+        DabTransmissionMode::II => 2,
+        DabTransmissionMode::III => 3,
+        DabTransmissionMode::IV => 4,
+    }
+}
+
+/// Reads enough of the input to detect the transmission mode and returns the bytes that were read.
+fn read_and_detect_transmission_mode(input: &mut dyn Read, input_sample_rate: u32) -> Result<(Vec<u8>, Option<DabTransmissionMode>), String> {
+    let params = get_dab_parameters(DabTransmissionMode::I);
+    let output_sample_rate = DAB_SAMPLE_RATE as u32;
+    let total_output_samples = AUTO_MODE_TOTAL_FRAMES*params.samples_per_frame();
+    let total_input_samples = ((total_output_samples as u64) * (input_sample_rate as u64)).div_ceil(output_sample_rate as u64);
+    let sample_format = SampleFormat::U8;
+
+    let mut input_prefix = Vec::new();
+    if let Err(err) = input.take(total_input_samples * (sample_format.bytes_per_sample() as u64)).read_to_end(&mut input_prefix) {
+        return Err(format!("Failed to read input for transmission mode detection: {}", err));
+    }
+    let mut samples = Vec::new();
+    sample_format.convert_bytes(&input_prefix, &mut samples);
+    if input_sample_rate != output_sample_rate {
+        let passband = (params.nb_fft_data_carriers as f32) / (params.nb_fft as f32);
+        let mut resampler = Resampler::new(input_sample_rate, output_sample_rate, passband);
+        let mut resampled_samples = Vec::new();
+        resampler.process(&samples, &mut resampled_samples);
+        samples = resampled_samples;
+    }
+    Ok((input_prefix, detect_transmission_mode(&samples)))
+}
+
+fn write_frame_header(writer: &mut dyn Write, mode: u8, frame_counter: u32, length: usize) -> std::io::Result<()> {
+    let length = u32::try_from(length).map_err(|_| std::io::Error::other("Frame is too long for header"))?;
+    writer.write_all(&FRAME_HEADER_MAGIC)?;
+    writer.write_all(&[mode])?;
+    writer.write_all(&frame_counter.to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())?;
+    Ok(())
+}
+
+fn launch_gui(demod: Arc<RwLock<OfdmDemodulator>>, is_paused: Arc<AtomicBool>) -> Result<(), eframe::Error> {
     let app_name = "DAB OFDM Demodulator";
     let native_options = eframe::NativeOptions {
         initial_window_size: Some(egui::Vec2::new(500.0, 900.0)),
@@ -191,7 +582,7 @@ fn launch_gui(demod: Arc<RwLock<OfdmDemodulator>>) -> Result<(), eframe::Error>
 
     let app_gui = AppGui {
         ref_demodulator: demod,
-        ui_demodulator: GuiOfdmDemodulator::default(),
+        ui_demodulator: GuiOfdmDemodulator::new(is_paused),
     };
 
     eframe::run_native(
@@ -204,8 +595,7 @@ fn launch_gui(demod: Arc<RwLock<OfdmDemodulator>>) -> Result<(), eframe::Error>
 impl eframe::App for AppGui {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
-            let demod = &mut *self.ref_demodulator.write().unwrap();
-            self.ui_demodulator.draw_all(demod, ui);
+            self.ui_demodulator.draw_all(&self.ref_demodulator, ui);
         });
     }
 }
\ No newline at end of file
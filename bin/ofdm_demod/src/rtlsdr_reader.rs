@@ -0,0 +1,52 @@
+use rtlsdr::{RTLSDRDevice, RTLSDRError};
+use std::io::Read;
+
+/// Number of bytes requested from the dongle in each synchronous read. This must be a multiple of 512.
+const READ_BLOCK_BYTES: usize = 16*16384;
+
+/// Reads unsigned 8bit IQ samples from an RTL-SDR dongle.
+/// This produces the same format as the rtl_sdr command line tool so it can replace reading from a pipe.
+pub struct RtlSdrReader {
+    device: RTLSDRDevice,
+    block: Vec<u8>,
+    block_offset: usize,
+}
+
+// SAFETY: The device handle is only used through &mut self so it is never accessed from two threads at once.
+unsafe impl Send for RtlSdrReader {}
+
+impl RtlSdrReader {
+    /// Opens the dongle at the sampling frequency in Hz and tunes it to the centre frequency in Hz with automatic gain.
+    pub fn open(index: u32, frequency: u32, sample_rate: u32) -> Result<Self, String> {
+        let with_context = |context: &str| {
+            let context = context.to_string();
+            move |err: RTLSDRError| format!("{} for RTL-SDR device {}: {}", context, index, err)
+        };
+        let mut device = rtlsdr::open(index as i32).map_err(with_context("Failed to open"))?;
+        device.set_sample_rate(sample_rate).map_err(with_context("Failed to set sample rate"))?;
+        device.set_center_freq(frequency).map_err(with_context("Failed to set frequency"))?;
+        device.set_tuner_gain_mode(false).map_err(with_context("Failed to enable automatic gain"))?;
+        device.reset_buffer().map_err(with_context("Failed to reset buffer"))?;
+        Ok(Self {
+            device,
+            block: vec![],
+            block_offset: 0,
+        })
+    }
+}
+
+impl Read for RtlSdrReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.block_offset >= self.block.len() {
+            self.block = self.device
+                .read_sync(READ_BLOCK_BYTES)
+                .map_err(|err| std::io::Error::other(err.to_string()))?;
+            self.block_offset = 0;
+        }
+        let block = &self.block[self.block_offset..];
+        let length = block.len().min(buf.len());
+        buf[..length].copy_from_slice(&block[..length]);
+        self.block_offset += length;
+        Ok(length)
+    }
+}
@@ -0,0 +1,124 @@
+use ofdm::ofdm_demodulator::OfdmDemodulator;
+use ofdm::ofdm_modulator::OfdmModulator;
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
+use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
+use dab_ofdm::dab_ofdm_parameters::get_dab_ofdm_parameters;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use num::complex::Complex32;
+use crate::get_transmission_mode_index;
+
+/// Number of frames modulated for each transmission mode.
+const SELF_TEST_TOTAL_FRAMES: usize = 4;
+
+/// Number of samples passed to the demodulator at a time like the default for reading the input.
+const SELF_TEST_CHUNK_SAMPLES: usize = 4096*8;
+
+const SELF_TEST_MODES: [DabTransmissionMode; 4] = [
+    DabTransmissionMode::I,
+    DabTransmissionMode::II,
+    DabTransmissionMode::III,
+    DabTransmissionMode::IV,
+];
+
+/// The outcome of running random bits through the modulator and demodulator for one transmission mode.
+struct SelfTestResult {
+    total_frames_matched: usize,
+    total_frames_mismatched: usize,
+    total_samples: usize,
+    sample_rate: f32,
+    elapsed_seconds: f32,
+}
+
+impl SelfTestResult {
+    fn is_pass(&self) -> bool {
+        self.total_frames_mismatched == 0 && self.total_frames_matched == SELF_TEST_TOTAL_FRAMES
+    }
+}
+
+/// Modulates random bits for each transmission mode and checks that the demodulator recovers them from the noise free signal.
+/// The pass or fail and the processing throughput of each transmission mode is printed to stdout.
+/// Returns an error if any transmission mode failed.
+pub fn run_self_test() -> Result<(), String> {
+    let mut total_failed = 0;
+    for transmission_mode in SELF_TEST_MODES {
+        let result = run_transmission_mode_self_test(transmission_mode);
+        let samples_per_second = (result.total_samples as f32) / result.elapsed_seconds;
+        println!(
+            "Mode {}: {} ({}/{} frames matched, {} mismatched) {:.2} MS/s ({:.1}x real time)",
+            get_transmission_mode_index(transmission_mode),
+            if result.is_pass() { "PASS" } else { "FAIL" },
+            result.total_frames_matched, SELF_TEST_TOTAL_FRAMES, result.total_frames_mismatched,
+            samples_per_second*1e-6, samples_per_second/result.sample_rate,
+        );
+        if !result.is_pass() {
+            total_failed += 1;
+        }
+    }
+    match total_failed {
+        0 => Ok(()),
+        n => Err(format!("Self test failed for {} of {} transmission modes", n, SELF_TEST_MODES.len())),
+    }
+}
+
+fn run_transmission_mode_self_test(transmission_mode: DabTransmissionMode) -> SelfTestResult {
+    let ofdm_params = get_dab_ofdm_parameters(transmission_mode);
+    let mut carrier_map = vec![0usize; ofdm_params.nb_fft_data_carriers];
+    let mut prs_fft = vec![Complex32::default(); ofdm_params.nb_fft];
+    get_dab_ofdm_carrier_map(&mut carrier_map, ofdm_params.nb_fft);
+    get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, transmission_mode);
+
+    // Random bits from a linear congruential generator so each run is reproducible
+    let mut seed = get_transmission_mode_index(transmission_mode) as u32;
+    let tx_frames: Vec<Vec<bool>> = (0..SELF_TEST_TOTAL_FRAMES)
+        .map(|_| (0..ofdm_params.nb_output_bits)
+            .map(|_| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                (seed >> 31) != 0
+            })
+            .collect())
+        .collect();
+
+    // The NULL symbol of the next frame and an extra sample are needed to read the last frame
+    let mut modulator = OfdmModulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    let mut signal = Vec::with_capacity((SELF_TEST_TOTAL_FRAMES+1)*ofdm_params.nb_input_samples);
+    for bits in &tx_frames {
+        modulator.modulate_frame(bits, &mut signal);
+    }
+    signal.extend((0..ofdm_params.nb_null_period+1).map(|_| Complex32::default()));
+
+    // Frames are matched to what was transmitted by where their PRS starts
+    let rx_frames = Arc::new(Mutex::new(Vec::new()));
+    let mut demod = OfdmDemodulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    demod.subscribe_frames_out({
+        let rx_frames = rx_frames.clone();
+        move |bits, info| {
+            let prs_offset = (info.prs_start_sample as usize + ofdm_params.nb_input_samples/2).saturating_sub(ofdm_params.nb_null_period);
+            let frame_index = prs_offset / ofdm_params.nb_input_samples;
+            rx_frames.lock().unwrap().push((frame_index, bits.to_vec()));
+        }
+    });
+
+    let start = Instant::now();
+    for chunk in signal.chunks(SELF_TEST_CHUNK_SAMPLES) {
+        demod.process(chunk);
+    }
+    let elapsed_seconds = start.elapsed().as_secs_f32();
+
+    let rx_frames = rx_frames.lock().unwrap();
+    let total_frames_matched = rx_frames
+        .iter()
+        .filter(|(frame_index, rx_bits)| match tx_frames.get(*frame_index) {
+            Some(tx_bits) => tx_bits.len() == rx_bits.len() && tx_bits.iter().zip(rx_bits.iter()).all(|(tx, rx)| *tx == (*rx > 0)),
+            None => false,
+        })
+        .count();
+    SelfTestResult {
+        total_frames_matched,
+        total_frames_mismatched: rx_frames.len() - total_frames_matched,
+        total_samples: signal.len(),
+        sample_rate: ofdm_params.sample_rate,
+        elapsed_seconds,
+    }
+}
@@ -21,62 +21,175 @@ pub struct DabParameters {
     pub nb_fibs_in_fic: usize,
     /// Number of common interleaved frames (CIF) in the MSC.
     pub nb_cifs_in_msc: usize,
+    /// Sampling frequency in Hz that the sample counts are measured at.
+    pub sample_rate: f32,
 }
 
-/// These constants are defined for a sampling frequency of 2.048MHz.
-pub fn get_dab_parameters(transmission_mode: DabTransmissionMode) -> DabParameters {
-    let params = match transmission_mode {
-        DabTransmissionMode::I => DabParameters { 
-            nb_symbols: 76, 
-            nb_null_period: 2656, 
-            nb_symbol_period: 2552, 
-            nb_fft: 2048, 
-            nb_fft_data_carriers: 1536, 
-            nb_fic_symbols: 3, 
-            nb_msc_symbols: 72, 
-            nb_fibs_in_fic: 12, 
-            nb_cifs_in_msc: 4, 
-        },
-        DabTransmissionMode::II => DabParameters { 
-            nb_symbols: 76, 
-            nb_null_period: 664, 
-            nb_symbol_period: 638, 
-            nb_fft: 512, 
-            nb_fft_data_carriers: 384, 
-            nb_fic_symbols: 3, 
-            nb_msc_symbols: 72, 
-            nb_fibs_in_fic: 3, 
-            nb_cifs_in_msc: 1, 
-        },
-        DabTransmissionMode::III => DabParameters { 
-            nb_symbols: 153, 
-            nb_null_period: 345, 
-            nb_symbol_period: 319, 
-            nb_fft: 256, 
-            nb_fft_data_carriers: 192, 
-            nb_fic_symbols: 8, 
-            nb_msc_symbols: 144, 
-            nb_fibs_in_fic: 4, 
-            nb_cifs_in_msc: 1, 
-        },
-        DabTransmissionMode::IV => DabParameters { 
-            nb_symbols: 76, 
-            nb_null_period: 1328, 
-            nb_symbol_period: 1276, 
-            nb_fft: 1024, 
-            nb_fft_data_carriers: 768, 
-            nb_fic_symbols: 3, 
-            nb_msc_symbols: 72, 
-            nb_fibs_in_fic: 6, 
-            nb_cifs_in_msc: 2, 
-        },
-    };
+/// Sampling frequency in Hz that the DAB frame structure is defined for.
+pub const DAB_SAMPLE_RATE: f32 = 2.048e6;
+
+impl DabParameters {
+    /// Number of complex samples spanned by one transmission frame including the NULL symbol.
+    pub fn samples_per_frame(&self) -> usize {
+        self.nb_null_period + self.nb_symbol_period*self.nb_symbols
+    }
+
+    /// Duration of one transmission frame in seconds at the sample rate of the parameters.
+    /// Mode I frames are 96ms long, mode II and III frames are 24ms long and mode IV frames are 48ms long.
+    ///
+    /// # Examples
+    /// ```
+    /// use dab_core::dab_parameters::{get_dab_parameters, DabParameters};
+    /// use dab_core::dab_transmission_modes::DabTransmissionMode;
+    ///
+    /// let params = get_dab_parameters(DabTransmissionMode::I);
+    /// assert_eq!(params.samples_per_frame(), 196608);
+    /// assert!((params.frame_duration_secs() - 0.096).abs() < 1e-6);
+    ///
+    /// // Twice the samples at twice the sample rate span the same time
+    /// let params_2x = DabParameters {
+    ///     nb_null_period: 2*params.nb_null_period,
+    ///     nb_symbol_period: 2*params.nb_symbol_period,
+    ///     sample_rate: 2.0*params.sample_rate,
+    ///     ..params
+    /// };
+    /// assert!((params_2x.frame_duration_secs() - 0.096).abs() < 1e-6);
+    /// ```
+    pub fn frame_duration_secs(&self) -> f32 {
+        (self.samples_per_frame() as f32) / self.sample_rate
+    }
+}
+
+/// DAB parameters of a transmission mode as associated constants so generic code can be monomorphised on the mode.
+/// Refer to DabParameters for an explanation of what these constants mean.
+/// The sample counts are measured at DAB_SAMPLE_RATE.
+///
+/// # Examples
+/// ```
+/// use dab_core::dab_parameters::{DabModeParams, ModeI, ModeIII};
+///
+/// // The buffer length is known at compile time so no runtime branch on the transmission mode is needed
+/// fn frame_buffer<M: DabModeParams>() -> Vec<i8> {
+///     vec![0; M::NB_OUTPUT_BITS]
+/// }
+/// const MODE_I_FRAME_SAMPLES: usize = ModeI::NB_NULL_PERIOD + ModeI::NB_SYMBOL_PERIOD*ModeI::NB_SYMBOLS;
+/// assert_eq!(MODE_I_FRAME_SAMPLES, 196608);
+/// assert_eq!(frame_buffer::<ModeIII>().len(), 2*192*152);
+/// assert_eq!(ModeIII::dab_parameters().nb_fft, ModeIII::NB_FFT);
+/// ```
+pub trait DabModeParams {
+    const TRANSMISSION_MODE: DabTransmissionMode;
+    const NB_SYMBOLS: usize;
+    const NB_NULL_PERIOD: usize;
+    const NB_SYMBOL_PERIOD: usize;
+    const NB_FFT: usize;
+    const NB_FFT_DATA_CARRIERS: usize;
+    const NB_FIC_SYMBOLS: usize;
+    const NB_MSC_SYMBOLS: usize;
+    const NB_FIBS_IN_FIC: usize;
+    const NB_CIFS_IN_MSC: usize;
+    /// Number of soft decision bits in a transmission frame since each DQPSK symbol carries 2 bits per data carrier.
+    const NB_OUTPUT_BITS: usize = 2*Self::NB_FFT_DATA_CARRIERS*(Self::NB_SYMBOLS-1);
+
+    /// Returns the constants as the runtime parameters from get_dab_parameters().
+    fn dab_parameters() -> DabParameters {
+        DabParameters {
+            nb_symbols: Self::NB_SYMBOLS,
+            nb_null_period: Self::NB_NULL_PERIOD,
+            nb_symbol_period: Self::NB_SYMBOL_PERIOD,
+            nb_fft: Self::NB_FFT,
+            nb_fft_data_carriers: Self::NB_FFT_DATA_CARRIERS,
+            nb_fic_symbols: Self::NB_FIC_SYMBOLS,
+            nb_msc_symbols: Self::NB_MSC_SYMBOLS,
+            nb_fibs_in_fic: Self::NB_FIBS_IN_FIC,
+            nb_cifs_in_msc: Self::NB_CIFS_IN_MSC,
+            sample_rate: DAB_SAMPLE_RATE,
+        }
+    }
+}
+
+/// DAB transmission mode I.
+pub struct ModeI;
+/// DAB transmission mode II.
+pub struct ModeII;
+/// DAB transmission mode III.
+pub struct ModeIII;
+/// DAB transmission mode IV.
+pub struct ModeIV;
+
+impl DabModeParams for ModeI {
+    const TRANSMISSION_MODE: DabTransmissionMode = DabTransmissionMode::I;
+    const NB_SYMBOLS: usize = 76;
+    const NB_NULL_PERIOD: usize = 2656;
+    const NB_SYMBOL_PERIOD: usize = 2552;
+    const NB_FFT: usize = 2048;
+    const NB_FFT_DATA_CARRIERS: usize = 1536;
+    const NB_FIC_SYMBOLS: usize = 3;
+    const NB_MSC_SYMBOLS: usize = 72;
+    const NB_FIBS_IN_FIC: usize = 12;
+    const NB_CIFS_IN_MSC: usize = 4;
+}
 
-    assert!(params.nb_symbols >= 2, "Number of symbols must be at least 2 due to differential QPSK encoding");
-    assert!(params.nb_symbol_period >= params.nb_fft, "Number of samples in symbol is less than FFT resolution");
-    assert!(params.nb_fft >= params.nb_fft_data_carriers, "Number of data carriers is limited to FFT resolution");
-    assert!((params.nb_symbols-1) == (params.nb_fic_symbols + params.nb_msc_symbols), "Number of data symbols after DQPSK doesn't match number of FIC and MSC symbols");
-    assert!(params.nb_fibs_in_fic % params.nb_cifs_in_msc == 0, "The number of FIBs in the FIC must be a multiple of the number of CIFs in the MSC.");
+impl DabModeParams for ModeII {
+    const TRANSMISSION_MODE: DabTransmissionMode = DabTransmissionMode::II;
+    const NB_SYMBOLS: usize = 76;
+    const NB_NULL_PERIOD: usize = 664;
+    const NB_SYMBOL_PERIOD: usize = 638;
+    const NB_FFT: usize = 512;
+    const NB_FFT_DATA_CARRIERS: usize = 384;
+    const NB_FIC_SYMBOLS: usize = 3;
+    const NB_MSC_SYMBOLS: usize = 72;
+    const NB_FIBS_IN_FIC: usize = 3;
+    const NB_CIFS_IN_MSC: usize = 1;
+}
+
+impl DabModeParams for ModeIII {
+    const TRANSMISSION_MODE: DabTransmissionMode = DabTransmissionMode::III;
+    const NB_SYMBOLS: usize = 153;
+    const NB_NULL_PERIOD: usize = 345;
+    const NB_SYMBOL_PERIOD: usize = 319;
+    const NB_FFT: usize = 256;
+    const NB_FFT_DATA_CARRIERS: usize = 192;
+    const NB_FIC_SYMBOLS: usize = 8;
+    const NB_MSC_SYMBOLS: usize = 144;
+    const NB_FIBS_IN_FIC: usize = 4;
+    const NB_CIFS_IN_MSC: usize = 1;
+}
+
+impl DabModeParams for ModeIV {
+    const TRANSMISSION_MODE: DabTransmissionMode = DabTransmissionMode::IV;
+    const NB_SYMBOLS: usize = 76;
+    const NB_NULL_PERIOD: usize = 1328;
+    const NB_SYMBOL_PERIOD: usize = 1276;
+    const NB_FFT: usize = 1024;
+    const NB_FFT_DATA_CARRIERS: usize = 768;
+    const NB_FIC_SYMBOLS: usize = 3;
+    const NB_MSC_SYMBOLS: usize = 72;
+    const NB_FIBS_IN_FIC: usize = 6;
+    const NB_CIFS_IN_MSC: usize = 2;
+}
 
-    params
-}
\ No newline at end of file
+/// Checks the constants of a transmission mode are consistent. This is evaluated at compile time for each mode below.
+const fn check_dab_mode_params<M: DabModeParams>() {
+    assert!(M::NB_SYMBOLS >= 2, "Number of symbols must be at least 2 due to differential QPSK encoding");
+    assert!(M::NB_SYMBOL_PERIOD >= M::NB_FFT, "Number of samples in symbol is less than FFT resolution");
+    assert!(M::NB_FFT >= M::NB_FFT_DATA_CARRIERS, "Number of data carriers is limited to FFT resolution");
+    assert!((M::NB_SYMBOLS-1) == (M::NB_FIC_SYMBOLS + M::NB_MSC_SYMBOLS), "Number of data symbols after DQPSK doesn't match number of FIC and MSC symbols");
+    assert!(M::NB_FIBS_IN_FIC.is_multiple_of(M::NB_CIFS_IN_MSC), "The number of FIBs in the FIC must be a multiple of the number of CIFs in the MSC.");
+}
+
+const _: () = check_dab_mode_params::<ModeI>();
+const _: () = check_dab_mode_params::<ModeII>();
+const _: () = check_dab_mode_params::<ModeIII>();
+const _: () = check_dab_mode_params::<ModeIV>();
+
+/// The sample counts are measured at the sample rate of the returned parameters which is DAB_SAMPLE_RATE.
+/// Use the DabModeParams implementations instead if the transmission mode is known at compile time.
+pub fn get_dab_parameters(transmission_mode: DabTransmissionMode) -> DabParameters {
+    match transmission_mode {
+        DabTransmissionMode::I => ModeI::dab_parameters(),
+        DabTransmissionMode::II => ModeII::dab_parameters(),
+        DabTransmissionMode::III => ModeIII::dab_parameters(),
+        DabTransmissionMode::IV => ModeIV::dab_parameters(),
+    }
+}
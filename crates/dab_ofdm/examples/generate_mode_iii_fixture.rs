@@ -0,0 +1,89 @@
+//! Generates the synthetic mode III fixtures used by the golden tests.
+//! - fixtures/mode_iii_frame.iq holds little endian 16bit IQ samples of the last two symbols of a frame, a whole frame and the next PRS.
+//! - fixtures/mode_iii_frame_bits.bin holds the soft bits that the demodulator outputs for that frame with its default settings.
+//!
+//! Each DQPSK symbol is rotated by an extra 10° so the soft bits stay clear of quantisation boundaries.
+//! The golden soft bits should only be regenerated when a change to the demodulator is meant to change its output.
+//!
+//! Run with: cargo run -p dab_ofdm --example generate_mode_iii_fixture
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_ofdm::dab_ofdm_parameters::get_dab_ofdm_parameters;
+use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
+use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
+use ofdm::ofdm_demodulator::OfdmDemodulator;
+use ofdm::iq_sample::SampleFormat;
+use num::complex::Complex32;
+use rustfft::FftPlanner;
+use std::sync::{Arc, Mutex};
+
+const EXTRA_ROTATION_DEGREES: f32 = 10.0;
+/// The largest component is scaled to this value so there is headroom below the 16bit limit.
+const PEAK_AMPLITUDE: f32 = 20000.0;
+
+fn main() {
+    let mode = DabTransmissionMode::III;
+    let params = get_dab_ofdm_parameters(mode);
+    let mut carrier_map = vec![0usize; params.nb_fft_data_carriers];
+    let mut prs_fft = vec![Complex32::default(); params.nb_fft];
+    get_dab_ofdm_carrier_map(&mut carrier_map, params.nb_fft);
+    get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, mode);
+    let ifft = FftPlanner::<f32>::new().plan_fft_inverse(params.nb_fft);
+
+    // Random DQPSK phases from a linear congruential generator so the fixture is reproducible
+    let mut seed: u32 = 12345;
+    let mut get_random_phase = move || {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        let quadrant = ((seed >> 16) & 0b11) as f32;
+        std::f32::consts::FRAC_PI_4 + quadrant*std::f32::consts::FRAC_PI_2 + EXTRA_ROTATION_DEGREES.to_radians()
+    };
+    let push_symbol = |symbol_fft: &[Complex32], out: &mut Vec<Complex32>| {
+        let mut symbol = symbol_fft.to_vec();
+        ifft.process(&mut symbol);
+        out.extend_from_slice(&symbol[params.nb_fft-params.nb_cyclic_prefix..]);
+        out.extend_from_slice(&symbol);
+    };
+    // Each frame starts with the PRS and every symbol after it differentially modulates every carrier
+    let mut push_symbols = |out: &mut Vec<Complex32>, total_symbols: usize| {
+        let mut symbol_fft = prs_fft.clone();
+        push_symbol(&symbol_fft, out);
+        for _ in 1..total_symbols {
+            for x in symbol_fft.iter_mut().filter(|x| x.norm() > 0.0) {
+                *x *= Complex32::from_polar(1.0, get_random_phase());
+            }
+            push_symbol(&symbol_fft, out);
+        }
+    };
+
+    // The PRS of the lead in is dropped so only the last two symbols of the previous frame remain
+    let mut lead_in = Vec::new();
+    push_symbols(&mut lead_in, 3);
+    let mut signal = lead_in[params.nb_symbol_period..].to_vec();
+    signal.extend(std::iter::repeat_n(Complex32::default(), params.nb_null_period));
+    push_symbols(&mut signal, params.nb_symbols);
+    signal.extend(std::iter::repeat_n(Complex32::default(), params.nb_null_period));
+    push_symbols(&mut signal, 1);
+
+    let peak = signal.iter().map(|x| x.re.abs().max(x.im.abs())).fold(0.0f32, f32::max);
+    let scale = PEAK_AMPLITUDE/peak;
+    let samples: Vec<u8> = signal
+        .iter()
+        .flat_map(|x| [x.re, x.im])
+        .flat_map(|x| ((x*scale).round() as i16).to_le_bytes())
+        .collect();
+
+    let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_bits_out({
+        let frames = frames.clone();
+        move |bits: &[i8]| frames.lock().unwrap().push(bits.to_vec())
+    });
+    demod.process_reader(&mut samples.as_slice(), SampleFormat::I16).unwrap();
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), 1, "Demodulator should output the single frame in the fixture");
+    let golden_bits: Vec<u8> = frames[0].iter().map(|x| *x as u8).collect();
+
+    let fixtures_path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+    std::fs::write(format!("{}/mode_iii_frame.iq", fixtures_path), &samples).expect("Fixture should be writable");
+    std::fs::write(format!("{}/mode_iii_frame_bits.bin", fixtures_path), &golden_bits).expect("Fixture should be writable");
+    println!("Wrote {} samples and {} soft bits to {}", signal.len(), golden_bits.len(), fixtures_path);
+}
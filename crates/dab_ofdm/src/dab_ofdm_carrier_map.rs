@@ -8,7 +8,7 @@ pub fn get_dab_ofdm_carrier_map(carrier_map: &mut[usize], total_fft: usize) {
     let total_carriers = carrier_map.len();
     assert!(total_carriers > 0);
     assert!(total_fft > 0);
-    assert!(total_fft % 4 == 0, "FFT length must be a multiple of 4");
+    assert!(total_fft.is_multiple_of(4), "FFT length must be a multiple of 4");
     assert!(total_carriers <= total_fft, "Number of requested carriers must be less than or equal to total fft bins");
 
     let fft_index_dc = total_fft/2;
@@ -0,0 +1,82 @@
+use num::complex::Complex32;
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_core::dab_parameters::get_dab_parameters;
+
+/// Minimum normalised guard correlation for a transmission mode to be detected.
+/// The cyclic prefix is about a fifth of the symbol period in every mode so a clean signal correlates to roughly 0.2.
+/// Noise lowers this value while the wrong modes correlate close to zero for long enough captures.
+pub const MODE_DETECTION_THRESHOLD: f32 = 0.1;
+
+const CANDIDATE_MODES: [DabTransmissionMode; 4] = [
+    DabTransmissionMode::I,
+    DabTransmissionMode::II,
+    DabTransmissionMode::III,
+    DabTransmissionMode::IV,
+];
+
+/// Guesses the transmission mode of a capture at 2.048MHz from the correlation of its cyclic prefixes.
+/// The cyclic prefix of each OFDM symbol is a copy of the last nb_fft samples of that symbol.
+/// Thus correlating the samples against themselves delayed by nb_fft samples only adds up coherently for the correct mode.
+/// A frequency offset rotates every product by the same phase so this works before frequency synchronisation.
+/// Returns None if no mode correlates above MODE_DETECTION_THRESHOLD or there are too few samples.
+/// At least a few symbols of mode I should be provided so the wrong modes average out.
+///
+/// # Examples
+/// ```
+/// use dab_core::dab_transmission_modes::DabTransmissionMode;
+/// use dab_ofdm::dab_ofdm_mode_detection::detect_transmission_mode;
+/// use ofdm::iq_sample::SampleFormat;
+/// use num::complex::Complex32;
+///
+/// let mut samples = Vec::new();
+/// SampleFormat::I16.convert_bytes(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/mode_iii_frame.iq")), &mut samples);
+/// assert!(detect_transmission_mode(&samples) == Some(DabTransmissionMode::III));
+///
+/// // A frequency offset doesn't change which mode is detected
+/// let shifted: Vec<Complex32> = samples.iter()
+///     .enumerate()
+///     .map(|(i, x)| x * Complex32::from_polar(1.0, 0.1 * (i as f32)))
+///     .collect();
+/// assert!(detect_transmission_mode(&shifted) == Some(DabTransmissionMode::III));
+///
+/// // A DC offset doesn't change which mode is detected
+/// let offset: Vec<Complex32> = samples.iter().map(|x| x + Complex32::new(20.0, -10.0)).collect();
+/// assert!(detect_transmission_mode(&offset) == Some(DabTransmissionMode::III));
+///
+/// // There is nothing to detect in silence or a constant input
+/// assert!(detect_transmission_mode(&vec![Complex32::default(); samples.len()]).is_none());
+/// assert!(detect_transmission_mode(&vec![Complex32::new(-127.5, -127.5); samples.len()]).is_none());
+/// ```
+pub fn detect_transmission_mode(samples: &[Complex32]) -> Option<DabTransmissionMode> {
+    // A DC offset correlates at every lag so it is removed first
+    let dc_offset = samples.iter().sum::<Complex32>() / (samples.len().max(1) as f32);
+    CANDIDATE_MODES
+        .iter()
+        .filter_map(|mode| {
+            let params = get_dab_parameters(*mode);
+            if samples.len() < params.nb_symbol_period + params.nb_fft {
+                return None;
+            }
+            let correlation = calculate_guard_correlation(samples, dc_offset, params.nb_fft);
+            Some((*mode, correlation))
+        })
+        // NaN correlations from a silent input are also rejected here
+        .filter(|(_, correlation)| *correlation >= MODE_DETECTION_THRESHOLD)
+        .max_by(|(_, x), (_, y)| x.total_cmp(y))
+        .map(|(mode, _)| mode)
+}
+
+/// Calculates the magnitude of the autocorrelation at a lag normalised by the power of the samples after removing the DC offset.
+/// This is 1 if the samples are periodic with the lag and close to 0 if they are uncorrelated.
+fn calculate_guard_correlation(samples: &[Complex32], dc_offset: Complex32, lag: usize) -> f32 {
+    let x0 = &samples[..samples.len()-lag];
+    let x1 = &samples[lag..];
+    let mut correlation = Complex32::default();
+    let mut power = 0.0;
+    for (a, b) in x0.iter().zip(x1.iter()) {
+        let (a, b) = (a - dc_offset, b - dc_offset);
+        correlation += a * b.conj();
+        power += a.norm() * b.norm();
+    }
+    correlation.norm() / power
+}
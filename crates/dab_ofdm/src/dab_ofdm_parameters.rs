@@ -11,5 +11,5 @@ pub fn get_dab_ofdm_parameters(transmission_mode: DabTransmissionMode) -> OfdmPa
         params.nb_symbol_period,
         params.nb_fft,
         params.nb_fft_data_carriers,
-    )
+    ).with_sample_rate(params.sample_rate)
 }
\ No newline at end of file
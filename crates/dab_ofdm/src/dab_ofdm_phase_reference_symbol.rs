@@ -12,13 +12,21 @@ use dab_core::dab_transmission_modes::{DabTransmissionMode};
 
 /// A phase reference symbol consists of multiple segments.
 /// The parameters i and n are used to calculate the phase at that FFT bin in conjuction with the H_table.
-struct PrsSegment {
-    fft_bin_start: i32,
-    fft_bin_end: i32,
-    h_table_row: usize,
-    phase_multiple: usize,
+#[derive(Debug, Clone, Copy)]
+pub struct PrsSegment {
+    /// First FFT bin of the segment relative to the DC bin.
+    pub fft_bin_start: i32,
+    /// Last FFT bin of the segment relative to the DC bin.
+    pub fft_bin_end: i32,
+    /// The row of the H table that the segment uses. This is the parameter i.
+    pub h_table_row: usize,
+    /// The additional multiple of pi/2 applied to each phase in the segment. This is the parameter n.
+    pub phase_multiple: usize,
 }
 
+/// Each row contains the phase multiples of pi/2 for consecutive FFT bins in a segment.
+pub type HTable = [[usize;32]];
+
 const PRS_MODE_I: [PrsSegment; 48] = [
     PrsSegment { fft_bin_start: -768, fft_bin_end:-737, h_table_row: 0, phase_multiple: 1 },
     PrsSegment { fft_bin_start: -736, fft_bin_end:-705, h_table_row: 1, phase_multiple: 2 },
@@ -136,14 +144,43 @@ pub fn get_dab_ofdm_phase_reference_symbol_fft(prs_fft: &mut[Complex<f32>], tran
         DabTransmissionMode::III => &PRS_MODE_III,
         DabTransmissionMode::IV  => &PRS_MODE_IV,
     };
+    get_dab_ofdm_phase_reference_symbol_fft_custom(prs_fft, prs_segments, &H_TABLE);
+}
 
+/// Creates the FFT result of a phase reference symbol in OFDM from user supplied segments and H table.
+/// This uses the same construction as DAB radio and can be used for other OFDM standards with different pilots.
+/// 
+/// # Examples
+/// ```
+/// use dab_ofdm::dab_ofdm_phase_reference_symbol::{get_dab_ofdm_phase_reference_symbol_fft_custom, PrsSegment};
+/// use num::complex::Complex;
+/// 
+/// let h_table = [[0usize; 32], [1usize; 32]];
+/// let segments = [
+///     PrsSegment { fft_bin_start: -32, fft_bin_end: -1, h_table_row: 0, phase_multiple: 0 },
+///     PrsSegment { fft_bin_start:   1, fft_bin_end: 32, h_table_row: 1, phase_multiple: 1 },
+/// ];
+/// let mut prs_fft = vec![Complex::<f32>::default(); 128];
+/// get_dab_ofdm_phase_reference_symbol_fft_custom(&mut prs_fft, &segments, &h_table);
+/// 
+/// assert!((prs_fft[128-1] - Complex::new(1.0, 0.0)).norm() < 1e-6);
+/// assert!((prs_fft[1] - Complex::new(-1.0, 0.0)).norm() < 1e-6);
+/// assert_eq!(prs_fft[0], Complex::default());
+/// ```
+pub fn get_dab_ofdm_phase_reference_symbol_fft_custom(prs_fft: &mut[Complex<f32>], prs_segments: &[PrsSegment], h_table: &HTable) {
     let total_fft = prs_fft.len();
 
     // NOTE: PRS symbol is symmetrical along frequency axis and FFT buffer should have the DC bin at the start
     let total_segments = prs_segments.len();
+    assert!(total_segments > 0, "PRS must have at least one segment");
     let total_carriers = (prs_segments[total_segments-1].fft_bin_end - prs_segments[0].fft_bin_start + 1) as usize;
     assert!(prs_segments[total_segments-1].fft_bin_end == -prs_segments[0].fft_bin_start, "FFT bins must be centered and symmetrical");
     assert!(total_fft >= total_carriers, "PRS FFT buffer is not large enough to fit phase reference symbol. {} < {}", total_fft, total_carriers);
+    for segment in prs_segments {
+        let total_bins = (segment.fft_bin_end - segment.fft_bin_start + 1) as usize;
+        assert!(segment.h_table_row < h_table.len(), "PRS segment uses H table row {} but only {} rows are available", segment.h_table_row, h_table.len());
+        assert!(total_bins <= h_table[segment.h_table_row].len(), "PRS segment has {} bins but H table rows only have {} columns", total_bins, h_table[segment.h_table_row].len());
+    }
 
     // Zero out FFT bins that might not be initialised in this call
     for value in prs_fft.iter_mut() {
@@ -158,7 +195,7 @@ pub fn get_dab_ofdm_phase_reference_symbol_fft(prs_fft: &mut[Complex<f32>], tran
     for segment in prs_segments {
         let fft_bins = segment.fft_bin_start..=segment.fft_bin_end;
         for (h_table_column, fft_bin) in fft_bins.enumerate() {
-            let h_value = H_TABLE[segment.h_table_row][h_table_column];
+            let h_value = h_table[segment.h_table_row][h_table_column];
             let phase_multiple = h_value+segment.phase_multiple;
 
             use std::f32::consts::FRAC_PI_2;
@@ -1,3 +1,4 @@
 pub mod dab_ofdm_carrier_map;
 pub mod dab_ofdm_phase_reference_symbol;
-pub mod dab_ofdm_parameters;
\ No newline at end of file
+pub mod dab_ofdm_parameters;
+pub mod dab_ofdm_mode_detection;
\ No newline at end of file
@@ -0,0 +1,30 @@
+mod common;
+
+use num::complex::Complex32;
+
+#[test]
+fn flags_offset_on_edge_of_search_range() {
+    let (params, _) = common::mode_iii_demodulator();
+
+    // Repeat the frame in the fixture and shift it by 10 FFT bins
+    let fixture = common::mode_iii_frame_samples();
+    let lead_in = 2*params.nb_symbol_period;
+    let frame = &fixture[lead_in..lead_in+params.nb_input_samples];
+    let samples: Vec<Complex32> = fixture[..lead_in].iter()
+        .chain(frame.iter().cycle().take(5*params.nb_input_samples))
+        .chain(fixture[lead_in+params.nb_input_samples..].iter())
+        .enumerate()
+        .map(|(i, x)| x * Complex32::from_polar(1.0, std::f32::consts::TAU * 10.0 * (i as f32) / (params.nb_fft as f32)))
+        .collect();
+
+    let run_demodulator = |max_range_bins: f32| {
+        let (_, mut demod) = common::mode_iii_demodulator();
+        demod.settings.coarse_frequency_max_range = 2.0 * max_range_bins / (params.nb_fft as f32);
+        demod.settings.coarse_frequency_peak_distance_penalty = 0.0;
+        demod.process(&samples);
+        assert_eq!(demod.coarse_frequency_offset * (params.nb_fft as f32), -10.0);
+        demod.coarse_frequency_saturated
+    };
+    assert!(!run_demodulator(12.5));
+    assert!(run_demodulator(10.5));
+}
@@ -0,0 +1,39 @@
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_ofdm::dab_ofdm_parameters::get_dab_ofdm_parameters;
+use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
+use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
+use ofdm::ofdm_demodulator::OfdmDemodulator;
+use ofdm::ofdm_parameters::OfdmParameters;
+use ofdm::iq_sample::SampleFormat;
+use num::complex::Complex32;
+
+// The fixtures are generated by examples/generate_mode_iii_fixture.rs
+pub const MODE_III_FRAME_BYTES: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/mode_iii_frame.iq"));
+pub const MODE_III_FRAME_BITS: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/mode_iii_frame_bits.bin"));
+
+/// Returns the samples of the mode III fixture.
+/// This is the last two symbols of a frame, a whole frame and the next PRS.
+pub fn mode_iii_frame_samples() -> Vec<Complex32> {
+    let mut samples = Vec::new();
+    SampleFormat::I16.convert_bytes(MODE_III_FRAME_BYTES, &mut samples);
+    samples
+}
+
+/// Returns the golden soft bits of the frame in the mode III fixture.
+pub fn mode_iii_frame_bits() -> Vec<i8> {
+    MODE_III_FRAME_BITS.iter().map(|x| *x as i8).collect()
+}
+
+/// Creates a mode III demodulator with the default settings.
+pub fn mode_iii_demodulator() -> (OfdmParameters, OfdmDemodulator) {
+    let mode = DabTransmissionMode::III;
+    let params = get_dab_ofdm_parameters(mode);
+    let mut carrier_map = vec![0usize; params.nb_fft_data_carriers];
+    let mut prs_fft = vec![Complex32::default(); params.nb_fft];
+    get_dab_ofdm_carrier_map(&mut carrier_map, params.nb_fft);
+    get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, mode);
+    (params, OfdmDemodulator::new(&params, &carrier_map, &prs_fft))
+}
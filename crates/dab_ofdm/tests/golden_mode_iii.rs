@@ -0,0 +1,25 @@
+mod common;
+
+use ofdm::iq_sample::SampleFormat;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn reproduces_golden_soft_bits() {
+    let (params, mut demod) = common::mode_iii_demodulator();
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_frames_out({
+        let frames = frames.clone();
+        move |bits, info| frames.lock().unwrap().push((bits.to_vec(), *info))
+    });
+    let mut samples = common::MODE_III_FRAME_BYTES;
+    demod.process_reader(&mut samples, SampleFormat::I16).unwrap();
+
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), 1);
+    let (bits, info) = &frames[0];
+    assert_eq!(bits.len(), params.nb_output_bits);
+    assert!(*bits == common::mode_iii_frame_bits());
+
+    // The PRS starts after the two symbols from the last frame and the NULL symbol
+    assert_eq!(info.prs_start_sample, (2*params.nb_symbol_period + params.nb_null_period) as u64);
+}
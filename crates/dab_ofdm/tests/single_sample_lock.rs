@@ -0,0 +1,12 @@
+mod common;
+
+#[test]
+fn locks_when_fed_one_sample_at_a_time() {
+    let (_, mut demod) = common::mode_iii_demodulator();
+    let mut frames = Vec::new();
+    for sample in common::mode_iii_frame_samples().chunks(1) {
+        demod.process_into(sample, &mut |bits| frames.push(bits.to_vec())).unwrap();
+    }
+    assert!(demod.is_locked());
+    assert!(frames == [common::mode_iii_frame_bits()]);
+}
@@ -0,0 +1,127 @@
+//! Generates fixtures/fic_mode_i.bin which holds the soft bits of the fast information channel (FIC) of one mode I frame.
+//! The FIC describes an ensemble with a label and three services and is transmitted through the OFDM modulator.
+//! Noise and a frequency offset are added before it is demodulated so the soft bits carry errors that the FIC decoder has to correct.
+//!
+//! Run with: cargo run -p dab_radio --example generate_fic_fixture
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_ofdm::dab_ofdm_carrier_map::get_dab_ofdm_carrier_map;
+use dab_ofdm::dab_ofdm_phase_reference_symbol::get_dab_ofdm_phase_reference_symbol_fft;
+use dab_ofdm::dab_ofdm_parameters::get_dab_ofdm_parameters;
+use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+use dab_radio::fic::fic_decoder::FIB_LENGTH;
+use dab_radio::fic::fic_encoder::{FicEncoder, create_fib};
+use ofdm::ofdm_demodulator::{OfdmDemodulator, FrameInfo};
+use ofdm::ofdm_modulator::OfdmModulator;
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+const ENSEMBLE_ID: u16 = 0xCE15;
+const ENSEMBLE_LABEL: &[u8; 16] = b"BBC National DAB";
+// 0xC2 is an upper case E with an acute accent in the EBU Latin character set
+const SERVICE_LABELS: [(u16, &[u8; 16]); 3] = [
+    (0xC221, b"BBC Radio 1     "),
+    (0xC222, b"BBC Radio 2     "),
+    (0xC2A1, b"Radio \xC2ireann   "),
+];
+const TOTAL_FRAMES: usize = 8;
+const SAVED_FRAME: usize = 2;
+const EBN0_DB: f32 = 8.0;
+const FREQUENCY_OFFSET_HZ: f32 = 1234.0;
+const NOISE_SEED: u64 = 1293;
+
+/// FIG 0/0 with the ensemble identifier and the CIF count with no changes signalled and alarms disabled.
+fn fig_0_0(cif_count: usize) -> Vec<u8> {
+    let [eid_high, eid_low] = ENSEMBLE_ID.to_be_bytes();
+    vec![0x05, 0x00, eid_high, eid_low, ((cif_count / 250) & 0x1F) as u8, (cif_count % 250) as u8]
+}
+
+/// FIG 1 label in the EBU Latin character set with the first 8 characters flagged for the abbreviated label.
+fn fig_1(extension: u8, id: u16, label: &[u8; 16]) -> Vec<u8> {
+    let mut fig = vec![0x35, extension];
+    fig.extend_from_slice(&id.to_be_bytes());
+    fig.extend_from_slice(label);
+    fig.extend_from_slice(&[0xFF, 0x00]);
+    fig
+}
+
+/// Each FIG starts with FIG 0/0 for its CIF and the labels fill the remaining FIBs in a cycle.
+fn create_frame_fibs(frame: usize, nb_fibs_per_fig: usize, nb_cifs: usize) -> Vec<u8> {
+    let mut labels = vec![fig_1(0, ENSEMBLE_ID, ENSEMBLE_LABEL)];
+    labels.extend(SERVICE_LABELS.iter().map(|(id, label)| fig_1(1, *id, label)));
+    let mut labels = labels.into_iter().cycle();
+    let mut fibs = Vec::new();
+    for cif in 0..nb_cifs {
+        for i in 0..nb_fibs_per_fig {
+            let mut figs = if i == 0 { fig_0_0(frame*nb_cifs + cif) } else { Vec::new() };
+            figs.extend(labels.next().unwrap());
+            fibs.extend_from_slice(&create_fib(&figs));
+        }
+    }
+    fibs
+}
+
+fn main() {
+    let mode = DabTransmissionMode::I;
+    let ofdm_params = get_dab_ofdm_parameters(mode);
+    let radio_params = get_dab_radio_parameters(mode);
+    let mut carrier_map = vec![0usize; ofdm_params.nb_fft_data_carriers];
+    let mut prs_fft = vec![Complex32::default(); ofdm_params.nb_fft];
+    get_dab_ofdm_carrier_map(&mut carrier_map, ofdm_params.nb_fft);
+    get_dab_ofdm_phase_reference_symbol_fft(&mut prs_fft, mode);
+
+    // The main service channel (MSC) is filled with pseudo random bits from a xorshift generator
+    let mut fic_encoder = FicEncoder::new(get_dab_radio_parameters(mode));
+    let mut modulator = OfdmModulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    let mut rng_state: u32 = 0x1293;
+    let mut signal = Vec::new();
+    let mut tx_fic_frames = Vec::new();
+    for frame in 0..TOTAL_FRAMES {
+        let fibs = create_frame_fibs(frame, radio_params.nb_fibs_per_fig(), radio_params.nb_cifs_in_msc);
+        assert_eq!(fibs.len(), radio_params.nb_fibs_in_fic*FIB_LENGTH);
+        let mut bits = vec![false; ofdm_params.nb_output_bits];
+        fic_encoder.encode_fic(&fibs, &mut bits[..radio_params.nb_bits_in_fic]);
+        for bit in bits[radio_params.nb_bits_in_fic..].iter_mut() {
+            rng_state ^= rng_state << 13;
+            rng_state ^= rng_state >> 17;
+            rng_state ^= rng_state << 5;
+            *bit = rng_state & 0b1 == 0b1;
+        }
+        modulator.modulate_frame(&bits, &mut signal);
+        tx_fic_frames.push(bits[..radio_params.nb_bits_in_fic].to_vec());
+    }
+    signal.extend((0..ofdm_params.nb_null_period+1).map(|_| Complex32::default()));
+    modulator.add_noise(&mut signal, EBN0_DB, NOISE_SEED);
+    let step = 2.0*std::f32::consts::PI*ofdm_params.hz_to_normalised(FREQUENCY_OFFSET_HZ);
+    for (i, x) in signal.iter_mut().enumerate() {
+        *x *= Complex32::from_polar(1.0, step*(i as f32));
+    }
+
+    // The demodulator starts with the default settings and reads the signal in chunks like it would a capture
+    // Frames are matched to what was transmitted by where their PRS starts
+    let mut demodulator = OfdmDemodulator::new(&ofdm_params, &carrier_map, &prs_fft);
+    let nb_bits_in_fic = radio_params.nb_bits_in_fic;
+    let rx_fic_frames = Arc::new(Mutex::new(Vec::new()));
+    demodulator.subscribe_frames_out({
+        let rx_fic_frames = rx_fic_frames.clone();
+        move |bits: &[i8], info: &FrameInfo| {
+            let prs_offset = (info.prs_start_sample as usize + ofdm_params.nb_input_samples/2).saturating_sub(ofdm_params.nb_null_period);
+            let frame_index = prs_offset / ofdm_params.nb_input_samples;
+            rx_fic_frames.lock().unwrap().push((frame_index, bits[..nb_bits_in_fic].to_vec()));
+        }
+    });
+    for chunk in signal.chunks(4096*8) {
+        demodulator.process(chunk);
+    }
+
+    let rx_fic_frames = rx_fic_frames.lock().unwrap();
+    let fic_bits = rx_fic_frames
+        .iter()
+        .find_map(|(frame_index, bits)| (*frame_index == SAVED_FRAME).then_some(bits))
+        .expect("Demodulator should lock onto the frame that is saved");
+    let total_errors = fic_bits.iter().zip(tx_fic_frames[SAVED_FRAME].iter()).filter(|(rx, tx)| (**rx > 0) != **tx).count();
+    println!("Demodulated {} frames with {}/{} bit errors in the saved FIC", rx_fic_frames.len(), total_errors, fic_bits.len());
+    let filepath = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/fic_mode_i.bin");
+    let bytes: Vec<u8> = fic_bits.iter().map(|x| *x as u8).collect();
+    std::fs::write(filepath, bytes).expect("Fixture should be writable");
+    println!("Wrote {} soft bits to {}", fic_bits.len(), filepath);
+}
@@ -0,0 +1,99 @@
+// DOC: ETSI TS 101 756
+// Referring to clause 5.2 - Character sets
+// Labels are transmitted with a 4bit character set identifier
+
+/// Complete EBU Latin based repertoire.
+pub const CHARSET_EBU_LATIN: u8 = 0b0000;
+/// ISO/IEC 10646 using UTF-8 encoding.
+pub const CHARSET_UTF8: u8 = 0b1111;
+
+/// Marks the control codes and unused positions of the EBU Latin character set.
+const UNMAPPED: char = char::REPLACEMENT_CHARACTER;
+
+// Referring to annex C - Complete EBU Latin based repertoire
+// Each row holds the 16 characters that share the upper 4 bits of their code.
+// 0x00 is kept as the null character so the padding of a label can be trimmed.
+// The rest of 0x00 to 0x1F are control codes that don't appear in labels.
+// 0x20 to 0x7F mostly follows ASCII except for 0x24, 0x5E, 0x60 and 0x7E.
+const EBU_LATIN: [char; 256] = [
+    // 0x00 - Control codes
+    '\0',     UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED,
+    UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED,
+    // 0x10 - Control codes
+    UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED,
+    UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED, UNMAPPED,
+    // 0x20 - Punctuation with the currency sign in place of the dollar sign
+    ' ', '!', '"', '#', '¤', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
+    // 0x30 - Digits
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    // 0x40 - Upper case letters
+    '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+    // 0x50 - Upper case letters with a horizontal bar in place of the caret
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '―', '_',
+    // 0x60 - Lower case letters with a double vertical line in place of the grave accent
+    '‖', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    // 0x70 - Lower case letters with an overline in place of the tilde
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '¯', UNMAPPED,
+    // 0x80 - Accented letters
+    'á', 'à', 'é', 'è', 'í', 'ì', 'ó', 'ò', 'ú', 'ù', 'Ñ', 'Ç', 'Ş', 'ß', '¡', 'Ĳ',
+    // 0x90 - Accented letters
+    'â', 'ä', 'ê', 'ë', 'î', 'ï', 'ô', 'ö', 'û', 'ü', 'ñ', 'ç', 'ş', 'ğ', 'ı', 'ĳ',
+    // 0xA0 - Symbols and accented letters
+    'ª', 'α', '©', '‰', 'Ǧ', 'ě', 'ň', 'ő', 'π', '€', '£', '$', '←', '↑', '→', '↓',
+    // 0xB0 - Symbols and accented letters
+    'º', '¹', '²', '³', '±', 'İ', 'ń', 'ű', 'µ', '¿', '÷', '°', '¼', '½', '¾', '§',
+    // 0xC0 - Upper case accented letters
+    'Á', 'À', 'É', 'È', 'Í', 'Ì', 'Ó', 'Ò', 'Ú', 'Ù', 'Ř', 'Č', 'Š', 'Ž', 'Ð', 'Ŀ',
+    // 0xD0 - Upper and lower case accented letters
+    'Â', 'Ä', 'Ê', 'Ë', 'Î', 'Ï', 'Ô', 'Ö', 'Û', 'Ü', 'ř', 'č', 'š', 'ž', 'đ', 'ŀ',
+    // 0xE0 - Upper case accented letters
+    'Ã', 'Å', 'Æ', 'Œ', 'ŷ', 'Ý', 'Õ', 'Ø', 'Þ', 'Ŋ', 'Ŕ', 'Ć', 'Ś', 'Ź', 'Ŧ', 'ð',
+    // 0xF0 - Lower case accented letters
+    'ã', 'å', 'æ', 'œ', 'ŵ', 'ý', 'õ', 'ø', 'þ', 'ŋ', 'ŕ', 'ć', 'ś', 'ź', 'ŧ', UNMAPPED,
+];
+
+/// Converts a single byte from the EBU Latin character set into a unicode character.
+/// Control codes and unused positions are replaced with the unicode replacement character.
+///
+/// # Examples
+/// ```
+/// use dab_radio::charset::convert_ebu_latin_to_char;
+///
+/// // Most of the printable range matches ASCII
+/// assert_eq!(convert_ebu_latin_to_char(b'A'), 'A');
+/// assert_eq!(convert_ebu_latin_to_char(b'z'), 'z');
+/// assert_eq!(convert_ebu_latin_to_char(b'\\'), '\\');
+///
+/// // Positions that differ from ASCII
+/// assert_eq!(convert_ebu_latin_to_char(0x24), '¤');
+/// assert_eq!(convert_ebu_latin_to_char(0x5E), '―');
+/// assert_eq!(convert_ebu_latin_to_char(0x60), '‖');
+/// assert_eq!(convert_ebu_latin_to_char(0x7E), '¯');
+///
+/// // Accented letters and symbols above 0x7F
+/// assert_eq!(convert_ebu_latin_to_char(0x8D), 'ß');
+/// assert_eq!(convert_ebu_latin_to_char(0x8F), 'Ĳ');
+/// assert_eq!(convert_ebu_latin_to_char(0x9F), 'ĳ');
+/// assert_eq!(convert_ebu_latin_to_char(0xA9), '€');
+/// assert_eq!(convert_ebu_latin_to_char(0xAB), '$');
+/// assert_eq!(convert_ebu_latin_to_char(0xCC), 'Š');
+/// assert_eq!(convert_ebu_latin_to_char(0xFE), 'ŧ');
+///
+/// // Control codes and unused positions
+/// assert_eq!(convert_ebu_latin_to_char(0x0A), char::REPLACEMENT_CHARACTER);
+/// assert_eq!(convert_ebu_latin_to_char(0x7F), char::REPLACEMENT_CHARACTER);
+/// assert_eq!(convert_ebu_latin_to_char(0xFF), char::REPLACEMENT_CHARACTER);
+/// ```
+pub fn convert_ebu_latin_to_char(x: u8) -> char {
+    EBU_LATIN[x as usize]
+}
+
+/// Decodes a label into a string given its character set.
+/// Trailing padding is removed from the label.
+pub fn decode_label(charset: u8, buf: &[u8]) -> String {
+    let label: String = match charset {
+        CHARSET_UTF8 => String::from_utf8_lossy(buf).into_owned(),
+        _ => buf.iter().map(|x| convert_ebu_latin_to_char(*x)).collect(),
+    };
+    label.trim_end_matches([' ', '\0']).to_string()
+}
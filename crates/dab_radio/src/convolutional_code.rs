@@ -0,0 +1,125 @@
+// DOC: ETSI EN 300 401
+// Referring to clause 11.1.1 - Mother code
+// The mother code is a 1/4 rate convolutional code with a constraint length of 7.
+// Each input bit produces 4 output bits from the generator polynomials 133, 171, 145 and 133 in octal.
+// The shift register starts at zero and 6 zero tail bits are appended to return it to zero.
+
+/// Number of input bits that each output bit depends on.
+pub const CONSTRAINT_LENGTH: usize = 7;
+/// Number of zero bits appended to the input to flush the shift register.
+pub const TAIL_LENGTH: usize = CONSTRAINT_LENGTH-1;
+/// Number of output bits for each input bit.
+pub const CODE_RATE: usize = 4;
+/// The generator polynomials where the MSB is applied to the newest input bit.
+pub const GENERATOR_POLYNOMIALS: [u8; CODE_RATE] = [0o133, 0o171, 0o145, 0o133];
+
+const TOTAL_STATES: usize = 1 << TAIL_LENGTH;
+
+/// Returns the number of mother code bits for a number of input bits including the tail.
+pub fn get_encoded_length(nb_input_bits: usize) -> usize {
+    (nb_input_bits + TAIL_LENGTH)*CODE_RATE
+}
+
+/// Returns the output bits of the mother code for a shift register holding the newest bit in bit 6.
+#[inline(always)]
+fn get_output_bits(register: u8) -> [bool; CODE_RATE] {
+    GENERATOR_POLYNOMIALS.map(|poly| (register & poly).count_ones() % 2 == 1)
+}
+
+/// Encodes bits with the mother code and appends the tail bits.
+/// The output must have the length given by get_encoded_length().
+pub fn encode(input: &[bool], output: &mut [bool]) {
+    let nb_output = get_encoded_length(input.len());
+    assert!(output.len() == nb_output, "Mother code must have {} bits but got {} bits", nb_output, output.len());
+
+    let tail = [false; TAIL_LENGTH];
+    let mut state: u8 = 0;
+    for (bit, out) in input.iter().chain(tail.iter()).zip(output.chunks_exact_mut(CODE_RATE)) {
+        let register = ((*bit as u8) << TAIL_LENGTH) | state;
+        out.copy_from_slice(&get_output_bits(register));
+        state = register >> 1;
+    }
+}
+
+/// A soft decision viterbi decoder for the mother code.
+/// The soft bits are positive for a 1, negative for a 0 and zero for a bit that was punctured.
+/// The buffers for the path decisions are kept between calls so decoding codewords of the same length doesn't allocate.
+///
+/// # Examples
+/// ```
+/// use dab_radio::convolutional_code::{encode, get_encoded_length, ViterbiDecoder};
+///
+/// let input: Vec<bool> = (0..200).map(|i| (i*i + 3*i) % 7 < 3).collect();
+/// let mut encoded = vec![false; get_encoded_length(input.len())];
+/// encode(&input, &mut encoded);
+/// let mut soft_bits: Vec<i8> = encoded.iter().map(|x| if *x { 100 } else { -100 }).collect();
+///
+/// // Flip some bits and erase others so they have to be corrected
+/// for i in (0..soft_bits.len()).step_by(37) {
+///     soft_bits[i] = -soft_bits[i];
+/// }
+/// for i in (5..soft_bits.len()).step_by(4) {
+///     soft_bits[i] = 0;
+/// }
+///
+/// let mut decoder = ViterbiDecoder::default();
+/// let mut output = vec![false; input.len()];
+/// decoder.decode(&soft_bits, &mut output);
+/// assert_eq!(output, input);
+/// ```
+#[derive(Default)]
+pub struct ViterbiDecoder {
+    path_metrics: Vec<i32>,
+    next_path_metrics: Vec<i32>,
+    decisions: Vec<u64>,
+}
+
+impl ViterbiDecoder {
+    /// Decodes the soft bits of a whole codeword including its tail into the input bits.
+    /// The output must have the number of input bits that were encoded.
+    pub fn decode(&mut self, input: &[i8], output: &mut [bool]) {
+        let nb_input = get_encoded_length(output.len());
+        assert!(input.len() == nb_input, "Mother code must have {} bits but got {} bits", nb_input, input.len());
+
+        // Paths that don't start from the zero state are never chosen
+        self.path_metrics.clear();
+        self.path_metrics.resize(TOTAL_STATES, i32::MIN/2);
+        self.path_metrics[0] = 0;
+        self.next_path_metrics.resize(TOTAL_STATES, 0);
+        self.decisions.clear();
+
+        for soft_bits in input.chunks_exact(CODE_RATE) {
+            // Each state has two predecessors that only differ in the oldest bit that is shifted out
+            let mut decisions: u64 = 0;
+            for next_state in 0..TOTAL_STATES {
+                let bit = (next_state >> (TAIL_LENGTH-1)) as u8;
+                let prev_state = ((next_state << 1) & (TOTAL_STATES-1)) as u8;
+                let [metric_0, metric_1] = [prev_state, prev_state | 1].map(|state| {
+                    let register = (bit << TAIL_LENGTH) | state;
+                    let branch_metric: i32 = get_output_bits(register)
+                        .iter()
+                        .zip(soft_bits.iter())
+                        .map(|(expected, soft_bit)| if *expected { *soft_bit as i32 } else { -(*soft_bit as i32) })
+                        .sum();
+                    self.path_metrics[state as usize] + branch_metric
+                });
+                if metric_1 > metric_0 {
+                    decisions |= 1 << next_state;
+                }
+                self.next_path_metrics[next_state] = metric_0.max(metric_1);
+            }
+            std::mem::swap(&mut self.path_metrics, &mut self.next_path_metrics);
+            self.decisions.push(decisions);
+        }
+
+        // The tail returns the shift register to the zero state so the traceback starts there
+        let mut state: usize = 0;
+        for (i, decisions) in self.decisions.iter().enumerate().rev() {
+            let bit = state >> (TAIL_LENGTH-1);
+            if let Some(out) = output.get_mut(i) {
+                *out = bit == 1;
+            }
+            state = ((state << 1) & (TOTAL_STATES-1)) | ((decisions >> state) & 1) as usize;
+        }
+    }
+}
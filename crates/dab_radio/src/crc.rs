@@ -0,0 +1,20 @@
+/// Calculates the 16bit CRC used by DAB to protect fast information blocks (FIB).
+///
+/// DOC: ETSI EN 300 401
+/// Referring to clause 5.2.1 - Fast Information Block (FIB)
+/// The generator polynomial is G(x) = x^16 + x^12 + x^5 + 1 with all registers initialised to 1.
+/// The transmitted CRC is the ones complement of the remainder.
+pub fn calculate_crc16_ccitt(buf: &[u8]) -> u16 {
+    let poly: u16 = 0x1021;
+    let mut crc: u16 = 0xFFFF;
+    for byte in buf {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = match crc & 0x8000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ poly,
+            };
+        }
+    }
+    !crc
+}
@@ -49,6 +49,94 @@ pub struct DabRadioParameters {
     pub nb_bits_per_fig: usize,
     /// Number of bits per CIF
     pub nb_bits_per_cif: usize,
+    /// Number of complex samples spanned by each frame including the NULL symbol.
+    pub nb_samples_per_frame: usize,
+    /// Sampling frequency in Hz that nb_samples_per_frame is measured at.
+    pub sample_rate: f32,
+}
+
+impl DabRadioParameters {
+    /// Returns the soft bits of the fast information channel (FIC) from the soft bits of a frame.
+    /// The FIC occupies the first nb_fic_symbols symbols of the frame.
+    ///
+    /// # Examples
+    /// ```
+    /// use dab_core::dab_transmission_modes::DabTransmissionMode;
+    /// use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+    ///
+    /// let params = get_dab_radio_parameters(DabTransmissionMode::I);
+    /// let frame_bits: Vec<i8> = (0..params.nb_bits_per_frame)
+    ///     .map(|i| if i < params.nb_bits_in_fic { 1 } else { -1 })
+    ///     .collect();
+    ///
+    /// let fic_bits = params.fic_bits(&frame_bits);
+    /// let msc_bits = params.msc_bits(&frame_bits);
+    /// assert_eq!(fic_bits.len(), params.nb_bits_in_fic);
+    /// assert_eq!(msc_bits.len(), params.nb_bits_in_msc);
+    /// assert!(fic_bits.iter().all(|x| *x == 1));
+    /// assert!(msc_bits.iter().all(|x| *x == -1));
+    ///
+    /// // Only whole frames can be split
+    /// assert!(std::panic::catch_unwind(|| params.fic_bits(&frame_bits[1..])).is_err());
+    /// ```
+    pub fn fic_bits<'a>(&self, frame_bits: &'a [i8]) -> &'a [i8] {
+        self.check_frame_bits(frame_bits);
+        &frame_bits[..self.nb_bits_in_fic]
+    }
+
+    /// Returns the soft bits of the main service channel (MSC) from the soft bits of a frame.
+    /// The MSC occupies the nb_msc_symbols symbols that follow the FIC.
+    pub fn msc_bits<'a>(&self, frame_bits: &'a [i8]) -> &'a [i8] {
+        self.check_frame_bits(frame_bits);
+        &frame_bits[self.nb_bits_in_fic..]
+    }
+
+    /// Duration of each frame in seconds at the sample rate of the parameters.
+    pub fn frame_duration_secs(&self) -> f64 {
+        (self.nb_samples_per_frame as f64) / (self.sample_rate as f64)
+    }
+
+    /// Returns the bitrate of the main service channel (MSC) in bits per second.
+    /// This is the coded rate before forward error correction (FEC) is removed by depuncturing and Viterbi decoding.
+    /// The net bitrate available to services depends on the protection level of each subchannel and is lower.
+    ///
+    /// # Examples
+    /// ```
+    /// use dab_core::dab_transmission_modes::DabTransmissionMode;
+    /// use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+    ///
+    /// // Every transmission mode carries the same MSC capacity
+    /// // The FIC has 96kb/s except for mode III which has 8 FIC symbols instead of 3
+    /// let modes = [
+    ///     (DabTransmissionMode::I,   96e3),
+    ///     (DabTransmissionMode::II,  96e3),
+    ///     (DabTransmissionMode::III, 128e3),
+    ///     (DabTransmissionMode::IV,  96e3),
+    /// ];
+    /// for (mode, fic_bitrate_bps) in modes {
+    ///     let params = get_dab_radio_parameters(mode);
+    ///     assert!((params.msc_bitrate_bps() - 2.304e6).abs() < 1e-3);
+    ///     assert!((params.fic_bitrate_bps() - fic_bitrate_bps).abs() < 1e-3);
+    /// }
+    /// ```
+    pub fn msc_bitrate_bps(&self) -> f64 {
+        (self.nb_bits_in_msc as f64) / self.frame_duration_secs()
+    }
+
+    /// Returns the bitrate of the fast information channel (FIC) in bits per second.
+    /// This is the coded rate before forward error correction (FEC) is removed by depuncturing and Viterbi decoding.
+    pub fn fic_bitrate_bps(&self) -> f64 {
+        (self.nb_bits_in_fic as f64) / self.frame_duration_secs()
+    }
+
+    /// Returns the number of fast information blocks (FIB) that are coded together in each FIG.
+    pub fn nb_fibs_per_fig(&self) -> usize {
+        self.nb_fibs_in_fic / self.nb_cifs_in_msc
+    }
+
+    fn check_frame_bits(&self, frame_bits: &[i8]) {
+        assert!(frame_bits.len() == self.nb_bits_per_frame, "Expected {} soft bits for a frame but got {}", self.nb_bits_per_frame, frame_bits.len());
+    }
 }
 
 /// Returns useful parameters used in DAB digital decoding for a given transmission mode
@@ -68,9 +156,11 @@ pub fn get_dab_radio_parameters(transmission_mode: DabTransmissionMode) -> DabRa
     let nb_bits_per_fib = nb_bits_in_fic/nb_fibs_in_fic;
     let nb_bits_per_fig = nb_bits_in_fic/nb_cifs_in_msc;
     let nb_bits_per_cif = nb_bits_in_msc/nb_cifs_in_msc;
+    let nb_samples_per_frame = params.samples_per_frame();
+    let sample_rate = params.sample_rate;
 
     assert!(nb_symbols == (nb_fic_symbols + nb_msc_symbols), "Number of data symbols in frame doesn't match number of FIC and MSC symbols");
-    assert!(nb_fibs_in_fic % nb_cifs_in_msc == 0, "The number of FIBs in the FIC must be a multiple of the number of CIFs in the MSC.");
+    assert!(nb_fibs_in_fic.is_multiple_of(nb_cifs_in_msc), "The number of FIBs in the FIC must be a multiple of the number of CIFs in the MSC.");
 
     DabRadioParameters {
         nb_symbols,
@@ -85,6 +175,8 @@ pub fn get_dab_radio_parameters(transmission_mode: DabTransmissionMode) -> DabRa
         nb_bits_per_fib,
         nb_bits_per_fig,
         nb_bits_per_cif,
+        nb_samples_per_frame,
+        sample_rate,
     }
 }
 
@@ -0,0 +1,33 @@
+// DOC: ETSI EN 300 401
+// Referring to clause 10 - Energy dispersal
+// The data is scrambled by adding a pseudo random binary sequence (PRBS) modulo 2.
+// The PRBS is generated by the polynomial x^9 + x^5 + 1 with all registers initialised to 1.
+// It restarts at the start of each FIC codeword and each CIF of a sub-channel.
+
+/// Applies the energy dispersal sequence to bytes where the first bit is the MSB of the first byte.
+/// Applying it twice restores the original bytes so the same function descrambles them.
+///
+/// # Examples
+/// ```
+/// use dab_radio::energy_dispersal::apply_energy_dispersal;
+///
+/// // The spec gives the first 16 bits of the PRBS as 0000 0111 1011 1110
+/// let mut buf = [0u8; 4];
+/// apply_energy_dispersal(&mut buf);
+/// assert_eq!(buf[..2], [0b0000_0111, 0b1011_1110]);
+///
+/// apply_energy_dispersal(&mut buf);
+/// assert_eq!(buf, [0u8; 4]);
+/// ```
+pub fn apply_energy_dispersal(buf: &mut [u8]) {
+    let mut register: u16 = 0x1FF;
+    for byte in buf.iter_mut() {
+        let mut prbs: u8 = 0;
+        for _ in 0..8 {
+            let bit = ((register >> 8) ^ (register >> 4)) & 0b1;
+            register = ((register << 1) | bit) & 0x1FF;
+            prbs = (prbs << 1) | (bit as u8);
+        }
+        *byte ^= prbs;
+    }
+}
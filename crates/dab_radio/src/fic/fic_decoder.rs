@@ -1,10 +1,51 @@
 use crate::dab_radio_parameters::DabRadioParameters;
+use crate::crc::calculate_crc16_ccitt;
+use crate::puncturing::{depuncture_fic, get_fic_depunctured_length};
+use crate::convolutional_code::ViterbiDecoder;
+use crate::energy_dispersal::apply_energy_dispersal;
+use crate::fic::fig_0::{parse_fig_0_header, parse_fig_0_0, EnsembleInfo};
+use crate::fic::fig_1::{parse_fig_1, Fig1Identifier};
+use std::collections::BTreeMap;
+
+/// Number of bytes in a fast information block (FIB) including the CRC.
+pub const FIB_LENGTH: usize = 32;
+/// Number of bytes in a fast information block (FIB) used for fast information groups (FIG).
+pub const FIB_DATA_LENGTH: usize = 30;
+/// A FIG header with this value marks the end of the useful data in a FIB.
+const FIB_END_MARKER: u8 = 0xFF;
 
 pub struct FicDecoder {
     params: DabRadioParameters,
+    viterbi_decoder: ViterbiDecoder,
+    depunctured_bits_buffer: Vec<i8>,
+    decoded_bits_buffer: Vec<bool>,
+    fib_bytes_buffer: Vec<u8>,
+    /// The number of FIBs that passed the CRC check.
+    pub total_fibs_valid: u32,
+    /// The number of FIBs that failed the CRC check.
+    pub total_fibs_invalid: u32,
+    ensemble_info: Option<EnsembleInfo>,
+    ensemble_label: Option<String>,
+    service_labels: BTreeMap<u32, String>,
 }
 
 impl FicDecoder {
+    pub fn new(params: DabRadioParameters) -> Self {
+        let nb_fib_bytes = params.nb_fibs_per_fig()*FIB_LENGTH;
+        Self {
+            viterbi_decoder: ViterbiDecoder::default(),
+            depunctured_bits_buffer: vec![0i8; get_fic_depunctured_length(&params)],
+            decoded_bits_buffer: vec![false; nb_fib_bytes*8],
+            fib_bytes_buffer: vec![0u8; nb_fib_bytes],
+            params,
+            total_fibs_valid: 0,
+            total_fibs_invalid: 0,
+            ensemble_info: None,
+            ensemble_label: None,
+            service_labels: BTreeMap::new(),
+        }
+    }
+
     pub fn decode_fic(&mut self, buf: &[i8]) {
         assert!(buf.len() == self.params.nb_bits_in_fic);
         for fig in buf.chunks_exact(self.params.nb_bits_per_fig) {
@@ -14,5 +55,144 @@ impl FicDecoder {
 
     fn decode_fig(&mut self, buf: &[i8]) {
         assert!(buf.len() == self.params.nb_bits_per_fig);
+
+        // Referring to clause 11.2 - Coding in the fast information channel
+        // The FIBs of each FIG are scrambled, convolutionally encoded and punctured as a single codeword
+        depuncture_fic(buf, &mut self.depunctured_bits_buffer);
+        self.viterbi_decoder.decode(&self.depunctured_bits_buffer, &mut self.decoded_bits_buffer);
+        let mut fib_bytes = std::mem::take(&mut self.fib_bytes_buffer);
+        for (bits, byte) in self.decoded_bits_buffer.chunks_exact(8).zip(fib_bytes.iter_mut()) {
+            *byte = bits.iter().fold(0u8, |acc, bit| (acc << 1) | (*bit as u8));
+        }
+        // Referring to clause 10.2 - Energy dispersal of the FIC
+        apply_energy_dispersal(&mut fib_bytes);
+        for fib in fib_bytes.chunks_exact(FIB_LENGTH) {
+            self.decode_fib(fib);
+        }
+        self.fib_bytes_buffer = fib_bytes;
+    }
+
+    /// Decodes a single fast information block (FIB) consisting of 30 bytes of data followed by a 2 byte CRC.
+    /// Returns false if the CRC check failed and the FIB was discarded.
+    pub fn decode_fib(&mut self, buf: &[u8]) -> bool {
+        assert!(buf.len() == FIB_LENGTH, "FIB must have {} bytes but got {} bytes", FIB_LENGTH, buf.len());
+
+        // DOC: ETSI EN 300 401
+        // Referring to clause 5.2.1 - Fast Information Block (FIB)
+        let data = &buf[..FIB_DATA_LENGTH];
+        let crc_rx = u16::from_be_bytes([buf[FIB_DATA_LENGTH], buf[FIB_DATA_LENGTH+1]]);
+        let crc_calc = calculate_crc16_ccitt(data);
+        if crc_rx != crc_calc {
+            self.total_fibs_invalid += 1;
+            return false;
+        }
+        self.total_fibs_valid += 1;
+
+        // Referring to clause 5.2.2 - Fast Information Group (FIG)
+        // | FIG type (3) | FIG length (5) | FIG data field (8*length) |
+        let mut curr_buf = data;
+        while let Some(&header) = curr_buf.first() {
+            if header == FIB_END_MARKER {
+                break;
+            }
+            let fig_type = (header & 0b1110_0000) >> 5;
+            let fig_length = (header & 0b0001_1111) as usize;
+            let Some(fig_data) = curr_buf.get(1..1+fig_length) else {
+                break;
+            };
+            self.decode_fig_data(fig_type, fig_data);
+            curr_buf = &curr_buf[1+fig_length..];
+        }
+        true
+    }
+
+    fn decode_fig_data(&mut self, fig_type: u8, buf: &[u8]) {
+        // TODO: Decode the other FIG types for the ensemble configuration
+        match fig_type {
+            0 => self.decode_fig_0(buf),
+            1 => self.decode_fig_1(buf),
+            _ => (),
+        }
     }
-}
\ No newline at end of file
+
+    fn decode_fig_0(&mut self, buf: &[u8]) {
+        let Some((header, buf)) = parse_fig_0_header(buf) else {
+            return;
+        };
+        if header.is_other_ensemble {
+            return;
+        }
+        // TODO: Decode the other extensions for the sub-channel and service organisation
+        if header.extension == 0 {
+            if let Some(info) = parse_fig_0_0(buf) {
+                self.ensemble_info = Some(info);
+            }
+        }
+    }
+
+    fn decode_fig_1(&mut self, buf: &[u8]) {
+        let Some(label) = parse_fig_1(buf) else {
+            return;
+        };
+        if label.is_other_ensemble {
+            return;
+        }
+        match label.identifier {
+            Fig1Identifier::Ensemble(_) => { self.ensemble_label = Some(label.label); },
+            Fig1Identifier::ProgrammeService(id) => { self.service_labels.insert(id as u32, label.label); },
+            Fig1Identifier::DataService(id) => { self.service_labels.insert(id, label.label); },
+        }
+    }
+
+    /// Returns the most recently received ensemble information from FIG 0/0.
+    /// The CIF counter in it is from the frame that carried the FIG.
+    ///
+    /// # Examples
+    /// ```
+    /// use dab_core::dab_transmission_modes::DabTransmissionMode;
+    /// use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+    /// use dab_radio::fic::fic_decoder::{FicDecoder, FIB_DATA_LENGTH};
+    /// use dab_radio::crc::calculate_crc16_ccitt;
+    ///
+    /// // FIG 0/0 for ensemble 0xCE15 at CIF count 7*250+156 with no changes signalled and alarms disabled
+    /// let mut fib = vec![0x05, 0x00, 0xCE, 0x15, 0x07, 0x9C];
+    /// fib.resize(FIB_DATA_LENGTH, 0xFF);
+    /// let crc = calculate_crc16_ccitt(&fib);
+    /// fib.extend_from_slice(&crc.to_be_bytes());
+    ///
+    /// let mut decoder = FicDecoder::new(get_dab_radio_parameters(DabTransmissionMode::I));
+    /// assert!(decoder.ensemble_info().is_none());
+    /// assert!(decoder.decode_fib(&fib));
+    /// let info = decoder.ensemble_info().unwrap();
+    /// assert_eq!(info.ensemble_id, 0xCE15);
+    /// assert_eq!((info.cif_count_hi, info.cif_count_lo), (7, 156));
+    /// assert_eq!(info.cif_count(), 1906);
+    /// assert_eq!((info.change_flag, info.alarm_flag, info.occurrence_change), (0, false, None));
+    ///
+    /// // A sub-channel organisation change at CIF count 160 with alarms enabled carries an extra byte
+    /// let mut fib = vec![0x06, 0x00, 0xCE, 0x15, 0xA7, 0x9D, 0xA0];
+    /// fib.resize(FIB_DATA_LENGTH, 0xFF);
+    /// let crc = calculate_crc16_ccitt(&fib);
+    /// fib.extend_from_slice(&crc.to_be_bytes());
+    /// assert!(decoder.decode_fib(&fib));
+    /// let info = decoder.ensemble_info().unwrap();
+    /// assert_eq!(info.cif_count(), 1907);
+    /// assert_eq!((info.change_flag, info.alarm_flag, info.occurrence_change), (0b10, true, Some(160)));
+    /// ```
+    pub fn ensemble_info(&self) -> Option<EnsembleInfo> {
+        self.ensemble_info
+    }
+
+    /// Returns the label of the ensemble if it has been received.
+    pub fn ensemble_label(&self) -> Option<String> {
+        self.ensemble_label.clone()
+    }
+
+    /// Returns the labels of each service in the ensemble keyed by the service identifier (SId).
+    pub fn service_labels(&self) -> Vec<(u32, String)> {
+        self.service_labels
+            .iter()
+            .map(|(id, label)| (*id, label.clone()))
+            .collect()
+    }
+}
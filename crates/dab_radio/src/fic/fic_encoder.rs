@@ -0,0 +1,96 @@
+use crate::dab_radio_parameters::DabRadioParameters;
+use crate::crc::calculate_crc16_ccitt;
+use crate::puncturing::{puncture, get_fic_puncture_segments};
+use crate::convolutional_code::{encode, get_encoded_length};
+use crate::energy_dispersal::apply_energy_dispersal;
+use crate::fic::fic_decoder::{FIB_LENGTH, FIB_DATA_LENGTH};
+
+/// Creates a fast information block (FIB) from the bytes of one or more FIGs.
+/// The unused data is filled with end markers and the CRC is appended.
+/// Panics if the FIGs don't fit inside the FIB.
+pub fn create_fib(figs: &[u8]) -> [u8; FIB_LENGTH] {
+    assert!(figs.len() <= FIB_DATA_LENGTH, "FIGs with {} bytes don't fit inside the {} bytes of a FIB", figs.len(), FIB_DATA_LENGTH);
+    let mut fib = [0xFFu8; FIB_LENGTH];
+    fib[..figs.len()].copy_from_slice(figs);
+    let crc = calculate_crc16_ccitt(&fib[..FIB_DATA_LENGTH]);
+    fib[FIB_DATA_LENGTH..].copy_from_slice(&crc.to_be_bytes());
+    fib
+}
+
+/// A reference encoder for the fast information channel (FIC) that produces the bits sent by a transmitter.
+/// This reverses FicDecoder so test signals can be created for it.
+///
+/// # Examples
+/// ```
+/// use dab_core::dab_transmission_modes::DabTransmissionMode;
+/// use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+/// use dab_radio::fic::fic_decoder::{FicDecoder, FIB_LENGTH};
+/// use dab_radio::fic::fic_encoder::{FicEncoder, create_fib};
+///
+/// for mode in [DabTransmissionMode::I, DabTransmissionMode::III] {
+///     let params = get_dab_radio_parameters(mode);
+///     let nb_fibs = params.nb_fibs_in_fic;
+///     // FIG 0/0 for ensemble 0xCE15 followed by empty FIBs
+///     let mut fibs = vec![create_fib(&[0x05, 0x00, 0xCE, 0x15, 0x00, 0x00])];
+///     fibs.resize(nb_fibs, create_fib(&[]));
+///     let fibs = fibs.concat();
+///     assert_eq!(fibs.len(), nb_fibs*FIB_LENGTH);
+///
+///     let mut encoder = FicEncoder::new(get_dab_radio_parameters(mode));
+///     let mut fic_bits = vec![false; params.nb_bits_in_fic];
+///     encoder.encode_fic(&fibs, &mut fic_bits);
+///
+///     let soft_bits: Vec<i8> = fic_bits.iter().map(|x| if *x { 127 } else { -127 }).collect();
+///     let mut decoder = FicDecoder::new(params);
+///     decoder.decode_fic(&soft_bits);
+///     assert_eq!(decoder.total_fibs_valid as usize, nb_fibs);
+///     assert_eq!(decoder.total_fibs_invalid, 0);
+///     assert_eq!(decoder.ensemble_info().unwrap().ensemble_id, 0xCE15);
+/// }
+/// ```
+pub struct FicEncoder {
+    params: DabRadioParameters,
+    fib_bytes_buffer: Vec<u8>,
+    input_bits_buffer: Vec<bool>,
+    encoded_bits_buffer: Vec<bool>,
+}
+
+impl FicEncoder {
+    pub fn new(params: DabRadioParameters) -> Self {
+        let nb_fib_bytes = params.nb_fibs_per_fig()*FIB_LENGTH;
+        Self {
+            fib_bytes_buffer: vec![0u8; nb_fib_bytes],
+            input_bits_buffer: vec![false; nb_fib_bytes*8],
+            encoded_bits_buffer: vec![false; get_encoded_length(nb_fib_bytes*8)],
+            params,
+        }
+    }
+
+    /// Encodes the FIBs of a whole frame into the bits of the FIC.
+    pub fn encode_fic(&mut self, fibs: &[u8], output: &mut [bool]) {
+        assert!(fibs.len() == self.params.nb_fibs_in_fic*FIB_LENGTH, "FIC must have {} FIBs but got {} bytes", self.params.nb_fibs_in_fic, fibs.len());
+        assert!(output.len() == self.params.nb_bits_in_fic, "FIC must have {} bits but got {} bits", self.params.nb_bits_in_fic, output.len());
+        let nb_fib_bytes = self.fib_bytes_buffer.len();
+        for (fibs, output) in fibs.chunks_exact(nb_fib_bytes).zip(output.chunks_exact_mut(self.params.nb_bits_per_fig)) {
+            self.encode_fig(fibs, output);
+        }
+    }
+
+    /// Encodes the FIBs of a single FIG into its punctured codeword.
+    pub fn encode_fig(&mut self, fibs: &[u8], output: &mut [bool]) {
+        assert!(fibs.len() == self.fib_bytes_buffer.len(), "FIG must have {} bytes of FIBs but got {} bytes", self.fib_bytes_buffer.len(), fibs.len());
+        assert!(output.len() == self.params.nb_bits_per_fig, "FIG must have {} bits but got {} bits", self.params.nb_bits_per_fig, output.len());
+
+        // Referring to clause 10.2 - Energy dispersal of the FIC
+        self.fib_bytes_buffer.copy_from_slice(fibs);
+        apply_energy_dispersal(&mut self.fib_bytes_buffer);
+        for (byte, bits) in self.fib_bytes_buffer.iter().zip(self.input_bits_buffer.chunks_exact_mut(8)) {
+            for (i, bit) in bits.iter_mut().enumerate() {
+                *bit = (byte >> (7-i)) & 0b1 == 0b1;
+            }
+        }
+        // Referring to clause 11.2 - Coding in the fast information channel
+        encode(&self.input_bits_buffer, &mut self.encoded_bits_buffer);
+        puncture(&self.encoded_bits_buffer, output, &get_fic_puncture_segments(self.params.nb_bits_per_fig));
+    }
+}
@@ -0,0 +1,90 @@
+// DOC: ETSI EN 300 401
+// Referring to clause 5.2.2.1 - FIG type 0 data field
+// | C/N (1) | OE (1) | P/D (1) | Extension (5) | Type 0 field |
+
+/// The header of a FIG type 0 data field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fig0Header {
+    /// Whether the information applies to the next configuration instead of the current one.
+    pub is_next_configuration: bool,
+    /// Whether the information is for another ensemble instead of the current one.
+    pub is_other_ensemble: bool,
+    /// Whether service identifiers in the field are 32bit data service identifiers instead of 16bit programme ones.
+    pub is_data_service: bool,
+    pub extension: u8,
+}
+
+/// Parses the header of a FIG type 0 data field.
+/// Returns the header and the rest of the data field, or None if the data field is empty.
+pub fn parse_fig_0_header(buf: &[u8]) -> Option<(Fig0Header, &[u8])> {
+    let header = *buf.first()?;
+    let header = Fig0Header {
+        is_next_configuration: (header & 0b1000_0000) != 0,
+        is_other_ensemble: (header & 0b0100_0000) != 0,
+        is_data_service: (header & 0b0010_0000) != 0,
+        extension: header & 0b0001_1111,
+    };
+    Some((header, &buf[1..]))
+}
+
+// Referring to clause 6.4 - Ensemble information
+// | EId (16) | Change flags (2) | AI flag (1) | CIF count high (5) | CIF count low (8) | Occurrence change (8) |
+// The occurrence change field is only present when the change flags are non-zero.
+
+/// Number of values of the low part of the CIF counter which counts modulo 250.
+pub const CIF_COUNT_LO_MODULO: u16 = 250;
+/// Number of values of the high part of the CIF counter which counts modulo 20.
+pub const CIF_COUNT_HI_MODULO: u16 = 20;
+
+/// The ensemble information carried by FIG 0/0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnsembleInfo {
+    /// The ensemble identifier (EId).
+    pub ensemble_id: u16,
+    /// The high part of the CIF counter from 0 to 19.
+    pub cif_count_hi: u8,
+    /// The low part of the CIF counter from 0 to 249.
+    pub cif_count_lo: u8,
+    /// Signals an upcoming change to the sub-channel organisation (bit 1) or service organisation (bit 0).
+    pub change_flag: u8,
+    /// Whether alarm announcements are enabled for the ensemble.
+    pub alarm_flag: bool,
+    /// The lower 8 bits of the CIF count when the signalled change happens.
+    /// This is only present when the change flag is non-zero.
+    pub occurrence_change: Option<u8>,
+}
+
+impl EnsembleInfo {
+    /// Returns the CIF counter as a single value from 0 to 4999 that increases by one for each CIF.
+    /// This is the clock of the transmitter that is used to align the CIFs of a frame in time.
+    pub fn cif_count(&self) -> u16 {
+        (self.cif_count_hi as u16)*CIF_COUNT_LO_MODULO + (self.cif_count_lo as u16)
+    }
+}
+
+/// Parses the type 0 field of a FIG 0/0 which follows the FIG type 0 header.
+/// Returns None if the field is too short or the CIF counter is out of range.
+pub fn parse_fig_0_0(buf: &[u8]) -> Option<EnsembleInfo> {
+    let bytes = buf.get(..4)?;
+    let ensemble_id = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let change_flag = (bytes[2] & 0b1100_0000) >> 6;
+    let alarm_flag = (bytes[2] & 0b0010_0000) != 0;
+    let cif_count_hi = bytes[2] & 0b0001_1111;
+    let cif_count_lo = bytes[3];
+    if (cif_count_hi as u16) >= CIF_COUNT_HI_MODULO || (cif_count_lo as u16) >= CIF_COUNT_LO_MODULO {
+        return None;
+    }
+    let occurrence_change = match change_flag {
+        0 => None,
+        _ => Some(*buf.get(4)?),
+    };
+
+    Some(EnsembleInfo {
+        ensemble_id,
+        cif_count_hi,
+        cif_count_lo,
+        change_flag,
+        alarm_flag,
+        occurrence_change,
+    })
+}
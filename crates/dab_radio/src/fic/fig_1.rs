@@ -0,0 +1,86 @@
+use crate::charset::decode_label;
+
+// DOC: ETSI EN 300 401
+// Referring to clause 5.2.2.2 - FIG type 1 data field
+// | Charset (4) | OE (1) | Extension (3) | Identifier (16 or 32) | Label (16*8) | Character flag field (16) |
+
+/// Number of bytes in a label.
+pub const LABEL_LENGTH: usize = 16;
+
+/// The entity that a FIG type 1 label belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fig1Identifier {
+    /// Extension 0 - The ensemble identifier (EId).
+    Ensemble(u16),
+    /// Extension 1 - The programme service identifier (SId).
+    ProgrammeService(u16),
+    /// Extension 5 - The data service identifier (SId).
+    DataService(u32),
+}
+
+/// A decoded FIG type 1 label.
+#[derive(Debug, Clone)]
+pub struct Fig1Label {
+    pub identifier: Fig1Identifier,
+    /// Whether the label is for the current ensemble or another ensemble.
+    pub is_other_ensemble: bool,
+    /// The label with the character set converted and trailing padding removed.
+    pub label: String,
+    /// Each set bit selects a character from the label to use in the abbreviated label.
+    /// The MSB corresponds to the first character.
+    pub character_flags: u16,
+}
+
+impl Fig1Label {
+    /// Creates the abbreviated label from the character flag field.
+    pub fn short_label(&self) -> String {
+        self.label
+            .chars()
+            .take(LABEL_LENGTH)
+            .enumerate()
+            .filter(|(i, _)| (self.character_flags >> (LABEL_LENGTH-1-i)) & 0b1 == 0b1)
+            .map(|(_, c)| c)
+            .collect()
+    }
+}
+
+/// Parses the data field of a FIG type 1.
+/// Returns None if the extension isn't supported or the data field is too short.
+pub fn parse_fig_1(buf: &[u8]) -> Option<Fig1Label> {
+    let header = *buf.first()?;
+    let charset = (header & 0b1111_0000) >> 4;
+    let is_other_ensemble = (header & 0b0000_1000) != 0;
+    let extension = header & 0b0000_0111;
+    let buf = &buf[1..];
+
+    let (identifier, total_identifier_bytes) = match extension {
+        0 => (Fig1Identifier::Ensemble(read_u16(buf)?), 2),
+        1 => (Fig1Identifier::ProgrammeService(read_u16(buf)?), 2),
+        5 => (Fig1Identifier::DataService(read_u32(buf)?), 4),
+        _ => return None,
+    };
+    let buf = &buf[total_identifier_bytes..];
+    if buf.len() < LABEL_LENGTH+2 {
+        return None;
+    }
+
+    let label = decode_label(charset, &buf[..LABEL_LENGTH]);
+    let character_flags = read_u16(&buf[LABEL_LENGTH..])?;
+
+    Some(Fig1Label {
+        identifier,
+        is_other_ensemble,
+        label,
+        character_flags,
+    })
+}
+
+fn read_u16(buf: &[u8]) -> Option<u16> {
+    let bytes = buf.get(..2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(buf: &[u8]) -> Option<u32> {
+    let bytes = buf.get(..4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -1 +1,4 @@
-pub mod fic_decoder;
\ No newline at end of file
+pub mod fic_decoder;
+pub mod fic_encoder;
+pub mod fig_0;
+pub mod fig_1;
\ No newline at end of file
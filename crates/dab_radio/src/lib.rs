@@ -1,2 +1,8 @@
 pub mod dab_radio_parameters;
-pub mod fic;
\ No newline at end of file
+pub mod fic;
+pub mod msc;
+pub mod crc;
+pub mod charset;
+pub mod puncturing;
+pub mod convolutional_code;
+pub mod energy_dispersal;
\ No newline at end of file
@@ -0,0 +1,75 @@
+pub mod time_deinterleaver;
+
+// DOC: ETSI EN 300 401
+// Referring to clause 6.2.1 - Basic sub-channel organisation
+// The CIF is divided into capacity units (CU) of 64 bits which are addressed from 0 to 863.
+// Each sub-channel occupies a contiguous range of capacity units given by its start address and size.
+
+/// Number of bits in a capacity unit (CU).
+pub const CAPACITY_UNIT_BITS: usize = 64;
+/// Number of bytes in a capacity unit (CU).
+pub const CAPACITY_UNIT_BYTES: usize = CAPACITY_UNIT_BITS/8;
+/// Number of capacity units (CU) in a common interleaved frame (CIF).
+pub const TOTAL_CAPACITY_UNITS_PER_CIF: usize = 864;
+
+/// The error protection applied to a sub-channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubChannelProtection {
+    /// Short form - Unequal error protection (UEP) given by an index into the table of audio bit rates and protection levels.
+    Unequal { table_index: u8 },
+    /// Long form - Equal error protection (EEP) with option A and protection levels 1 to 4.
+    EqualA { level: u8 },
+    /// Long form - Equal error protection (EEP) with option B and protection levels 1 to 4.
+    EqualB { level: u8 },
+}
+
+/// Describes where a sub-channel is located inside a CIF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubChannel {
+    /// Address of the first capacity unit (CU) of the sub-channel.
+    pub start_cu: usize,
+    /// Number of capacity units (CU) occupied by the sub-channel.
+    pub size_cu: usize,
+    pub protection: SubChannelProtection,
+}
+
+impl SubChannel {
+    /// Extracts the bytes of this sub-channel from a CIF.
+    pub fn extract<'a>(&self, cif: &'a [u8]) -> &'a [u8] {
+        extract_subchannel(cif, self.start_cu, self.size_cu)
+    }
+}
+
+/// Extracts the contiguous range of capacity units (CU) that make up a sub-channel from a CIF.
+/// Panics if the sub-channel extends past the end of the CIF.
+///
+/// # Examples
+/// ```
+/// use dab_radio::msc::{extract_subchannel, SubChannel, SubChannelProtection, CAPACITY_UNIT_BYTES, TOTAL_CAPACITY_UNITS_PER_CIF};
+///
+/// // Label each byte of the CIF with the capacity unit it belongs to
+/// let cif: Vec<u8> = (0..TOTAL_CAPACITY_UNITS_PER_CIF*CAPACITY_UNIT_BYTES).map(|i| (i / CAPACITY_UNIT_BYTES) as u8).collect();
+/// let data = extract_subchannel(&cif, 10, 3);
+/// assert_eq!(data.len(), 3*CAPACITY_UNIT_BYTES);
+/// assert!(data[..8].iter().all(|x| *x == 10));
+/// assert!(data[16..].iter().all(|x| *x == 12));
+///
+/// // The last sub-channel can end exactly at the end of the CIF
+/// let sub_channel = SubChannel { start_cu: 800, size_cu: 64, protection: SubChannelProtection::EqualA { level: 3 } };
+/// assert_eq!(sub_channel.extract(&cif).len(), 64*CAPACITY_UNIT_BYTES);
+///
+/// // Sub-channels that don't fit inside the CIF are rejected
+/// assert!(std::panic::catch_unwind(|| extract_subchannel(&cif, 800, 65)).is_err());
+/// ```
+pub fn extract_subchannel(cif: &[u8], start_cu: usize, size_cu: usize) -> &[u8] {
+    let total_cus = cif.len() / CAPACITY_UNIT_BYTES;
+    assert!(cif.len().is_multiple_of(CAPACITY_UNIT_BYTES), "CIF must be a whole number of capacity units but got {} bytes", cif.len());
+    assert!(
+        start_cu.checked_add(size_cu).is_some_and(|end_cu| end_cu <= total_cus),
+        "Sub-channel with start {} and size {} exceeds the {} capacity units in the CIF", start_cu, size_cu, total_cus,
+    );
+
+    let start = start_cu*CAPACITY_UNIT_BYTES;
+    let length = size_cu*CAPACITY_UNIT_BYTES;
+    &cif[start..start+length]
+}
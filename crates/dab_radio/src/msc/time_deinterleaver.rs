@@ -0,0 +1,93 @@
+use crate::dab_radio_parameters::DabRadioParameters;
+
+// DOC: ETSI EN 300 401
+// Referring to clause 12 - Time interleaving
+// Each bit in a CIF is delayed by a multiple of the CIF duration depending on its index.
+// The delay for bit i is given by DELAYS[i mod 16], which spreads burst errors across 16 CIFs.
+
+/// Number of CIFs that the time interleaver spreads each CIF across.
+pub const TIME_INTERLEAVING_DEPTH: usize = 16;
+/// The number of CIFs that bit i is delayed by in the interleaver is given by TIME_INTERLEAVING_DELAYS[i mod 16].
+pub const TIME_INTERLEAVING_DELAYS: [usize; TIME_INTERLEAVING_DEPTH] = [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15];
+
+/// Undoes the convolutional time interleaving applied to the main service channel (MSC).
+/// Each bit is delayed by the complement of its interleaving delay so every output CIF has a fixed delay of 15 CIFs.
+///
+/// # Examples
+/// ```
+/// use dab_core::dab_transmission_modes::DabTransmissionMode;
+/// use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+/// use dab_radio::msc::time_deinterleaver::{TimeDeinterleaver, TIME_INTERLEAVING_DELAYS, TIME_INTERLEAVING_DEPTH};
+///
+/// let params = get_dab_radio_parameters(DabTransmissionMode::II);
+/// let nb_bits = params.nb_bits_per_cif;
+/// let total_cifs = 40;
+/// let cif = |r: isize, i: usize| if r < 0 { 0 } else { ((r as usize*7 + i*13) % 255) as i8 };
+///
+/// let mut deinterleaver = TimeDeinterleaver::new(&params);
+/// let mut cif_in = vec![0i8; nb_bits];
+/// let mut cif_out = vec![0i8; nb_bits];
+/// for r in 0..total_cifs {
+///     // Interleave the known sequence
+///     for (i, bit) in cif_in.iter_mut().enumerate() {
+///         let delay = TIME_INTERLEAVING_DELAYS[i % TIME_INTERLEAVING_DEPTH] as isize;
+///         *bit = cif(r - delay, i);
+///     }
+///     deinterleaver.process(&cif_in, &mut cif_out);
+///     if deinterleaver.is_warmed_up() {
+///         let expected: Vec<i8> = (0..nb_bits).map(|i| cif(r - 15, i)).collect();
+///         assert_eq!(cif_out, expected);
+///     }
+/// }
+/// assert!(deinterleaver.is_warmed_up());
+/// ```
+pub struct TimeDeinterleaver {
+    nb_bits_per_cif: usize,
+    cifs_buffer: Vec<i8>,
+    cif_index: usize,
+    total_cifs_read: usize,
+}
+
+impl TimeDeinterleaver {
+    pub fn new(params: &DabRadioParameters) -> Self {
+        Self {
+            nb_bits_per_cif: params.nb_bits_per_cif,
+            cifs_buffer: vec![0i8; TIME_INTERLEAVING_DEPTH*params.nb_bits_per_cif],
+            cif_index: 0,
+            total_cifs_read: 0,
+        }
+    }
+
+    /// Returns true if the last output CIF was valid.
+    /// The first 15 CIFs only fill the interleaving delay so the output is valid from the 16th CIF onwards.
+    pub fn is_warmed_up(&self) -> bool {
+        self.total_cifs_read >= TIME_INTERLEAVING_DEPTH
+    }
+
+    /// Clears the history of CIFs so the deinterleaver needs to warm up again.
+    pub fn reset(&mut self) {
+        self.cifs_buffer.fill(0);
+        self.cif_index = 0;
+        self.total_cifs_read = 0;
+    }
+
+    /// Reads in the latest interleaved CIF and writes out the deinterleaved CIF from 15 CIFs ago.
+    /// The output is only valid once the deinterleaver has warmed up.
+    pub fn process(&mut self, cif_in: &[i8], cif_out: &mut [i8]) {
+        let nb_bits = self.nb_bits_per_cif;
+        assert!(cif_in.len() == nb_bits, "Input CIF must have {} bits but got {} bits", nb_bits, cif_in.len());
+        assert!(cif_out.len() == nb_bits, "Output CIF must have {} bits but got {} bits", nb_bits, cif_out.len());
+
+        let curr_offset = self.cif_index*nb_bits;
+        self.cifs_buffer[curr_offset..curr_offset+nb_bits].copy_from_slice(cif_in);
+
+        for (i, bit) in cif_out.iter_mut().enumerate() {
+            let delay = (TIME_INTERLEAVING_DEPTH-1) - TIME_INTERLEAVING_DELAYS[i % TIME_INTERLEAVING_DEPTH];
+            let cif_index = (self.cif_index + TIME_INTERLEAVING_DEPTH - delay) % TIME_INTERLEAVING_DEPTH;
+            *bit = self.cifs_buffer[cif_index*nb_bits + i];
+        }
+
+        self.cif_index = (self.cif_index + 1) % TIME_INTERLEAVING_DEPTH;
+        self.total_cifs_read = usize::min(self.total_cifs_read + 1, TIME_INTERLEAVING_DEPTH);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::dab_radio_parameters::DabRadioParameters;
+
+// DOC: ETSI EN 300 401
+// Referring to clause 11.1.2 - Puncturing procedure
+// The 1/4 rate mother code is punctured in blocks of 128 bits using a puncturing vector PI_k of 32 bits repeated 4 times.
+// A 1 in the puncturing vector means that the bit is transmitted and a 0 means that it is removed.
+// The 24 tail bits of the mother code are punctured using the vector PI_X.
+
+/// Number of bits in a puncturing vector.
+pub const PI_LENGTH: usize = 32;
+/// Number of mother code bits in a block that is punctured with the same puncturing vector.
+pub const PI_BLOCK_LENGTH: usize = 128;
+/// Number of bits in the tail of the mother code that is punctured with PI_X.
+pub const PI_X_LENGTH: usize = 24;
+
+/// The puncturing vectors PI_1 to PI_24 where PI_TABLE[k-1] is PI_k.
+/// PI_k transmits 8+k out of every 32 bits.
+pub const PI_TABLE: [[u8; PI_LENGTH]; 24] = [
+    [1,1,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0],
+    [1,1,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0],
+    [1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0, 1,0,0,0, 1,0,0,0],
+    [1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0],
+    [1,1,0,0, 1,1,0,0, 1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0, 1,1,0,0, 1,0,0,0],
+    [1,1,0,0, 1,1,0,0, 1,1,0,0, 1,0,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,0,0,0],
+    [1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,0,0,0],
+    [1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0],
+    [1,1,1,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0],
+    [1,1,1,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,1,0, 1,1,0,0, 1,1,0,0, 1,1,0,0],
+    [1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0, 1,1,0,0, 1,1,0,0],
+    [1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0],
+    [1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,0,0],
+    [1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,0,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,0,0],
+    [1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,0,0],
+    [1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0],
+    [1,1,1,1, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,0],
+    [1,1,1,1, 1,1,1,0, 1,1,1,0, 1,1,1,0, 1,1,1,1, 1,1,1,0, 1,1,1,0, 1,1,1,0],
+    [1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0, 1,1,1,0, 1,1,1,0],
+    [1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0],
+    [1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,0],
+    [1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,0, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,0],
+    [1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,0],
+    [1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1, 1,1,1,1],
+];
+
+/// The puncturing vector used for the 24 tail bits of the mother code.
+pub const PI_X: [u8; PI_X_LENGTH] = [1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0, 1,1,0,0];
+
+/// A run of consecutive mother code bits that are punctured with the same repeating puncturing vector.
+#[derive(Debug, Clone, Copy)]
+pub struct PunctureSegment {
+    /// Number of mother code bits in the segment.
+    pub nb_bits: usize,
+    /// The puncturing vector that is repeated over the segment.
+    pub code: &'static [u8],
+}
+
+impl PunctureSegment {
+    /// Number of bits that are transmitted for this segment.
+    pub fn nb_punctured_bits(&self) -> usize {
+        self.code.iter().cycle().take(self.nb_bits).filter(|x| **x == 1).count()
+    }
+}
+
+/// Number of 128 bit blocks in the mother code of a FIC codeword, excluding the tail.
+fn get_fic_total_blocks(nb_punctured_bits: usize) -> usize {
+    // Referring to clause 11.2 - Coding in the fast information channel
+    // All but the last 3 blocks use PI_16 which transmits 96 bits, the last 3 use PI_15 which transmits 92 bits.
+    // The tail uses PI_X which transmits 12 bits.
+    // nb_punctured_bits = 96*(B-3) + 92*3 + 12 = 96*B
+    assert!(nb_punctured_bits.is_multiple_of(96), "{} bits isn't a valid length for a punctured FIC codeword", nb_punctured_bits);
+    nb_punctured_bits / 96
+}
+
+/// Returns the puncturing segments for a FIC codeword with the given number of transmitted bits.
+/// This is 2304 bits for transmission modes I, II and IV and 3072 bits for transmission mode III.
+pub fn get_fic_puncture_segments(nb_punctured_bits: usize) -> [PunctureSegment; 3] {
+    let total_blocks = get_fic_total_blocks(nb_punctured_bits);
+    [
+        PunctureSegment { nb_bits: (total_blocks-3)*PI_BLOCK_LENGTH, code: &PI_TABLE[16-1] },
+        PunctureSegment { nb_bits: 3*PI_BLOCK_LENGTH, code: &PI_TABLE[15-1] },
+        PunctureSegment { nb_bits: PI_X_LENGTH, code: &PI_X },
+    ]
+}
+
+/// Returns the number of mother code bits in a FIC codeword after depuncturing.
+pub fn get_fic_depunctured_length(params: &DabRadioParameters) -> usize {
+    get_fic_total_blocks(params.nb_bits_per_fig)*PI_BLOCK_LENGTH + PI_X_LENGTH
+}
+
+/// Removes the bits of a mother code that aren't transmitted according to the puncturing segments.
+/// This works on hard or soft bits.
+pub fn puncture<T: Copy>(input: &[T], output: &mut [T], segments: &[PunctureSegment]) {
+    let nb_input: usize = segments.iter().map(|x| x.nb_bits).sum();
+    let nb_output: usize = segments.iter().map(|x| x.nb_punctured_bits()).sum();
+    assert!(input.len() == nb_input, "Mother code must have {} bits but got {} bits", nb_input, input.len());
+    assert!(output.len() == nb_output, "Punctured code must have {} bits but got {} bits", nb_output, output.len());
+
+    let mut input = input.iter();
+    let mut output = output.iter_mut();
+    for segment in segments {
+        for is_kept in segment.code.iter().cycle().take(segment.nb_bits) {
+            let bit = input.next().unwrap();
+            if *is_kept == 1 {
+                *output.next().unwrap() = *bit;
+            }
+        }
+    }
+}
+
+/// Reinserts the bits of a mother code that weren't transmitted according to the puncturing segments.
+/// The removed bits are filled with zero so the viterbi decoder treats them as erasures.
+///
+/// # Examples
+/// ```
+/// use dab_core::dab_transmission_modes::DabTransmissionMode;
+/// use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+/// use dab_radio::puncturing::{puncture, depuncture, depuncture_fic, get_fic_puncture_segments, get_fic_depunctured_length};
+///
+/// for mode in [DabTransmissionMode::I, DabTransmissionMode::III] {
+///     let params = get_dab_radio_parameters(mode);
+///     let segments = get_fic_puncture_segments(params.nb_bits_per_fig);
+///     let nb_mother_bits = get_fic_depunctured_length(&params);
+///
+///     // A full rate sequence without any zeros so erasures are easy to find
+///     let mother: Vec<i8> = (0..nb_mother_bits).map(|i| (i % 127) as i8 + 1).collect();
+///     let mut punctured = vec![0i8; params.nb_bits_per_fig];
+///     puncture(&mother, &mut punctured, &segments);
+///
+///     let mut depunctured = vec![0i8; nb_mother_bits];
+///     depuncture_fic(&punctured, &mut depunctured);
+///     for (i, (x, y)) in mother.iter().zip(depunctured.iter()).enumerate() {
+///         // PI_16 and PI_15 never transmit the last bit of each group of 4 bits
+///         if i % 4 == 3 {
+///             assert_eq!(*y, 0);
+///         }
+///         assert!(*y == 0 || *y == *x);
+///     }
+///
+///     let mut depunctured_generic = vec![0i8; nb_mother_bits];
+///     depuncture(&punctured, &mut depunctured_generic, &segments);
+///     assert_eq!(depunctured, depunctured_generic);
+///     assert_eq!(depunctured.iter().filter(|x| **x != 0).count(), params.nb_bits_per_fig);
+/// }
+/// ```
+pub fn depuncture(input: &[i8], output: &mut [i8], segments: &[PunctureSegment]) {
+    let nb_input: usize = segments.iter().map(|x| x.nb_punctured_bits()).sum();
+    let nb_output: usize = segments.iter().map(|x| x.nb_bits).sum();
+    assert!(input.len() == nb_input, "Punctured code must have {} bits but got {} bits", nb_input, input.len());
+    assert!(output.len() == nb_output, "Mother code must have {} bits but got {} bits", nb_output, output.len());
+
+    let mut input = input.iter();
+    let mut output = output.iter_mut();
+    for segment in segments {
+        for is_kept in segment.code.iter().cycle().take(segment.nb_bits) {
+            let bit = output.next().unwrap();
+            *bit = match is_kept {
+                1 => *input.next().unwrap(),
+                _ => 0,
+            };
+        }
+    }
+}
+
+/// Depunctures a FIC codeword into the 1/4 rate mother code.
+/// The output must have the length given by get_fic_depunctured_length().
+pub fn depuncture_fic(input: &[i8], output: &mut [i8]) {
+    let segments = get_fic_puncture_segments(input.len());
+    depuncture(input, output, &segments);
+}
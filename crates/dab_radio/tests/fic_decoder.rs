@@ -0,0 +1,49 @@
+use dab_core::dab_transmission_modes::DabTransmissionMode;
+use dab_radio::dab_radio_parameters::get_dab_radio_parameters;
+use dab_radio::fic::fic_decoder::FicDecoder;
+
+/// Soft bits of the FIC of one demodulated mode I frame from examples/generate_fic_fixture.rs.
+/// These have about 1% of their bits flipped by noise before decoding.
+fn fic_fixture() -> Vec<i8> {
+    let bytes = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/fic_mode_i.bin"));
+    bytes.iter().map(|x| *x as i8).collect()
+}
+
+#[test]
+fn decodes_ensemble_from_demodulated_fic() {
+    let params = get_dab_radio_parameters(DabTransmissionMode::I);
+    let nb_fibs = params.nb_fibs_in_fic;
+    let mut decoder = FicDecoder::new(params);
+    decoder.decode_fic(&fic_fixture());
+
+    assert_eq!(decoder.total_fibs_valid as usize, nb_fibs);
+    assert_eq!(decoder.total_fibs_invalid, 0);
+    let info = decoder.ensemble_info().unwrap();
+    assert_eq!(info.ensemble_id, 0xCE15);
+    // FIG 0/0 is sent in every FIG so the last one has the CIF count of the last CIF in the third frame
+    assert_eq!((info.cif_count_hi, info.cif_count_lo), (0, 2*4 + 3));
+    assert_eq!(decoder.ensemble_label().as_deref(), Some("BBC National DAB"));
+    assert_eq!(decoder.service_labels(), vec![
+        (0xC221, "BBC Radio 1".to_string()),
+        (0xC222, "BBC Radio 2".to_string()),
+        (0xC2A1, "Radio Éireann".to_string()),
+    ]);
+}
+
+#[test]
+fn rejects_only_the_fibs_of_a_corrupted_fig() {
+    let params = get_dab_radio_parameters(DabTransmissionMode::I);
+    let nb_fibs = params.nb_fibs_in_fic;
+    let nb_fibs_per_fig = params.nb_fibs_per_fig();
+    let nb_bits_per_fig = params.nb_bits_per_fig;
+    let mut decoder = FicDecoder::new(params);
+
+    // Inverting the soft bits of the second FIG leaves a codeword that can't be decoded
+    let mut fic = fic_fixture();
+    for x in fic[nb_bits_per_fig..2*nb_bits_per_fig].iter_mut() {
+        *x = x.saturating_neg();
+    }
+    decoder.decode_fic(&fic);
+    assert_eq!(decoder.total_fibs_valid as usize, nb_fibs - nb_fibs_per_fig);
+    assert_eq!(decoder.total_fibs_invalid as usize, nb_fibs_per_fig);
+}
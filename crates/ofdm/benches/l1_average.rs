@@ -0,0 +1,27 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ofdm::util::{calculate_l1_average, calculate_l1_average_scalar};
+use num::complex::Complex32;
+use std::hint::black_box;
+
+/// Block sizes of the default power analysis block and NULL symbol of mode I, and a large input chunk.
+const BLOCK_SIZES: [usize; 3] = [100, 2656, 32768];
+
+fn bench_l1_average(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calculate_l1_average");
+    for block_size in BLOCK_SIZES {
+        let block: Vec<Complex32> = (0..block_size)
+            .map(|i| Complex32::new((0.37*(i as f32)).sin()*100.0, (0.11*(i as f32)).cos()*-50.0))
+            .collect();
+        group.throughput(Throughput::Elements(block_size as u64));
+        group.bench_with_input(BenchmarkId::new("lanes", block_size), &block, |b, block| {
+            b.iter(|| calculate_l1_average(black_box(block)))
+        });
+        group.bench_with_input(BenchmarkId::new("scalar", block_size), &block, |b, block| {
+            b.iter(|| calculate_l1_average_scalar(black_box(block)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_l1_average);
+criterion_main!(benches);
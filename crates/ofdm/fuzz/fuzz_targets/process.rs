@@ -0,0 +1,35 @@
+#![no_main]
+
+// Run with: cargo +nightly fuzz run process
+use libfuzzer_sys::fuzz_target;
+use num::complex::Complex32;
+use ofdm::ofdm_demodulator::OfdmDemodulator;
+use ofdm::ofdm_parameters::OfdmParameters;
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte picks the chunk size so blocks straddle calls in different ways
+    let Some((&chunk_size, data)) = data.split_first() else {
+        return;
+    };
+    let chunk_size = (chunk_size as usize).max(1);
+
+    // Reinterpret the rest as raw little endian f32 pairs so NaN and infinity reach the demodulator
+    let buf: Vec<Complex32> = data
+        .chunks_exact(8)
+        .map(|b| Complex32::new(
+            f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            f32::from_le_bytes([b[4], b[5], b[6], b[7]]),
+        ))
+        .collect();
+
+    let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    demod.settings.dc_offset_correction_enabled = true;
+    demod.settings.iq_imbalance_correction_enabled = true;
+    for chunk in buf.chunks(chunk_size) {
+        demod.process(chunk);
+    }
+    demod.flush();
+});
@@ -29,26 +29,61 @@ impl<T> CircularBucket<T> {
 
     /// Returns immutable iterator to valid data and wraps around as a circular buffer.
     pub fn iter(&self) -> Iter<'_,T> {
+        self.iter_from(0)
+    }
+
+    /// Returns immutable iterator to valid data that starts at a logical offset where 0 is the oldest value.
+    /// This can be used to read the most recent values without copying them out of the buffer.
+    /// Panics if the offset is past the number of valid values.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::circular_bucket::CircularBucket;
+    ///
+    /// let mut bucket = CircularBucket::<i32>::new(4);
+    /// bucket.push_slice_overwrite(&[0, 1, 2, 3, 4, 5]);
+    ///
+    /// // Read the last 3 values which wrap around the end of the internal buffer
+    /// let last: Vec<i32> = bucket.iter_from(bucket.length()-3).copied().collect();
+    /// assert_eq!(last, [3, 4, 5]);
+    /// assert_eq!(bucket.iter_from(0).len(), 4);
+    /// assert_eq!(bucket.iter_from(4).next(), None);
+    /// assert!(std::panic::catch_unwind(|| bucket.iter_from(5).count()).is_err());
+    /// ```
+    pub fn iter_from(&self, logical_start: usize) -> Iter<'_,T> {
+        assert!(logical_start <= self.length, "Start offset {} is past the {} valid values", logical_start, self.length);
         let capacity = self.capacity();
+        let index = match capacity {
+            0 => 0,
+            _ => (self.start_index() + logical_start) % capacity,
+        };
         Iter {
             data: &self.data,
-            index: self.index,
+            index,
             capacity,
-            remain_length: self.length,
+            remain_length: self.length - logical_start,
         }
     }
 
     /// Returns immutable iterator to valid data and wraps around as a circular buffer.
     pub fn iter_mut(&mut self) -> IterMut<'_,T> {
         let capacity = self.capacity();
+        let index = self.start_index();
         IterMut {
             data: &mut self.data,
-            index: self.index,
+            index,
             capacity,
             remain_length: self.length,
         }
     }
 
+    /// Returns the valid data as two contiguous slices in logical order where the second is empty if it doesn't wrap around.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let start = self.start_index();
+        let first_length = self.length.min(self.capacity() - start);
+        (&self.data[start..start+first_length], &self.data[..self.length-first_length])
+    }
+
     /// Returns immutable slice of entire internal buffer.
     pub fn raw_slice(&self) -> &[T] {
         &self.data
@@ -58,6 +93,15 @@ impl<T> CircularBucket<T> {
     pub fn raw_slice_mut(&mut self) -> &mut[T] {
         &mut self.data
     }
+
+    /// Returns the index into the internal buffer of the oldest valid value.
+    fn start_index(&self) -> usize {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return 0;
+        }
+        (self.index + capacity - self.length) % capacity
+    }
 }
 
 #[allow(unused)]
@@ -70,27 +114,106 @@ impl<T:Default+Copy+Clone> CircularBucket<T> {
         }
     }
 
+    /// Resets to an empty buffer with a new capacity.
+    /// The internal buffer is only reallocated if the capacity changes.
+    pub fn clear_and_resize(&mut self, new_capacity: usize) {
+        self.reset();
+        if self.capacity() != new_capacity {
+            self.data.resize(new_capacity, T::default());
+            self.data.shrink_to_fit();
+        }
+    }
+
+    /// Copies an array into the circular buffer until the capacity has been reached.
+    /// Returns the number of samples that were dropped since there was no space for them.
+    pub fn push_slice(&mut self, buf: &[T]) -> usize {
+        let remain = self.capacity() - self.length;
+        let total_read = buf.len().min(remain);
+        self.write_slice(&buf[..total_read]);
+        buf.len() - total_read
+    }
+
+    /// Copies an entire array into the circular buffer, overwriting the oldest data once the capacity has been reached.
+    pub fn push_slice_overwrite(&mut self, buf: &[T]) {
+        self.write_slice(buf);
+    }
+
     /// Copies an array into the circular buffer until the capacity has been reached.
     /// An additional argument is used to specify if it should continue if the current capacity is reached anyway.
     /// Returns the number of samples read from the array.
     pub fn consume(&mut self, buf: &[T], consume_all: bool) -> usize {
-        let capacity = self.capacity();
-        let remain = capacity - self.length;
+        if consume_all {
+            self.push_slice_overwrite(buf);
+            buf.len()
+        } else {
+            buf.len() - self.push_slice(buf)
+        }
+    }
 
-        let total_read: usize = match consume_all {
-            true => buf.len(),
-            false => buf.len().min(remain),
-        };
+    /// Copies the valid data in logical order into a buffer until either runs out.
+    /// Returns the number of values copied.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::circular_bucket::CircularBucket;
+    ///
+    /// let mut bucket = CircularBucket::<i32>::new(4);
+    /// let mut out = [0; 6];
+    /// assert_eq!(bucket.copy_to(&mut out), 0);
+    ///
+    /// // Partially filled
+    /// bucket.push_slice(&[1, 2, 3]);
+    /// assert_eq!(bucket.as_slices(), (&[1, 2, 3][..], &[][..]));
+    /// assert_eq!(bucket.copy_to(&mut out), 3);
+    /// assert_eq!(out[..3], [1, 2, 3]);
+    ///
+    /// // Wrapped around the end of the internal buffer
+    /// bucket.push_slice_overwrite(&[4, 5, 6]);
+    /// assert_eq!(bucket.as_slices(), (&[3, 4][..], &[5, 6][..]));
+    /// assert_eq!(bucket.copy_to(&mut out), 4);
+    /// assert_eq!(out[..4], [3, 4, 5, 6]);
+    ///
+    /// // Smaller output buffer only gets the oldest values
+    /// let mut small = [0; 3];
+    /// assert_eq!(bucket.copy_to(&mut small), 3);
+    /// assert_eq!(small, [3, 4, 5]);
+    /// ```
+    pub fn copy_to(&self, out: &mut [T]) -> usize {
+        let (head, tail) = self.as_slices();
+        let total_head = head.len().min(out.len());
+        out[..total_head].copy_from_slice(&head[..total_head]);
+        let total_tail = tail.len().min(out.len() - total_head);
+        out[total_head..total_head+total_tail].copy_from_slice(&tail[..total_tail]);
+        total_head + total_tail
+    }
 
-        for i in 0..total_read {
-            self.data[self.index] = buf[i];
+    fn write_slice(&mut self, buf: &[T]) {
+        let capacity = self.capacity();
+        for value in buf {
+            self.data[self.index] = *value;
             self.index = (self.index + 1) % capacity;
         }
-        self.length = usize::min(capacity, self.length+total_read);
-        total_read
+        self.length = usize::min(capacity, self.length+buf.len());
     }
 }
 
+/// Immutable iterator over the valid data of a circular buffer.
+/// Iterates from the oldest to the newest value, or in reverse with `rev()`.
+/// 
+/// # Examples
+/// ```
+/// use ofdm::circular_bucket::CircularBucket;
+/// 
+/// let mut bucket = CircularBucket::<i32>::new(4);
+/// bucket.push_slice_overwrite(&[0, 1, 2, 3, 4, 5]);
+/// 
+/// let forward: Vec<i32> = bucket.iter().copied().collect();
+/// let mut reverse: Vec<i32> = bucket.iter().rev().copied().collect();
+/// reverse.reverse();
+/// assert_eq!(forward, [2, 3, 4, 5]);
+/// assert_eq!(forward, reverse);
+/// assert_eq!(bucket.iter().len(), 4);
+/// ```
 pub struct Iter<'a, T> {
     data: &'a[T],
     index: usize,
@@ -98,6 +221,8 @@ pub struct Iter<'a, T> {
     remain_length: usize,
 } 
 
+/// Mutable iterator over the valid data of a circular buffer.
+/// Iterates from the oldest to the newest value, or in reverse with `rev()`.
 pub struct IterMut<'a, T> {
     data: &'a mut[T],
     index: usize,
@@ -117,6 +242,10 @@ impl<'a,T> Iterator for Iter<'a,T> {
         self.remain_length -= 1;
         Some(value)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remain_length, Some(self.remain_length))
+    }
 }
 
 impl<'a,T> Iterator for IterMut<'a,T> {
@@ -135,13 +264,48 @@ impl<'a,T> Iterator for IterMut<'a,T> {
         self.remain_length -= 1;
         Some(value)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remain_length, Some(self.remain_length))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_,T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remain_length == 0 {
+            return None;
+        }
+
+        self.remain_length -= 1;
+        let index = (self.index + self.remain_length) % self.capacity;
+        Some(&self.data[index])
+    }
 }
 
+impl<T> DoubleEndedIterator for IterMut<'_,T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remain_length == 0 {
+            return None;
+        }
+
+        // The front and back of the iterator never overlap since both shrink the remaining length
+        self.remain_length -= 1;
+        let index = (self.index + self.remain_length) % self.capacity;
+        let value = unsafe {
+            &mut *(&mut self.data[index] as *mut T)
+        };
+        Some(value)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_,T> {}
+impl<T> ExactSizeIterator for IterMut<'_,T> {}
+
 impl<T> Index<usize> for CircularBucket<T> {
     type Output = T;
     fn index(&self, index: usize) -> &Self::Output {
         assert!(index < self.length);
-        let wrapped_index = (index + self.index) % self.capacity();
+        let wrapped_index = (index + self.start_index()) % self.capacity();
         &self.data[wrapped_index]
     }
 }
@@ -149,7 +313,7 @@ impl<T> Index<usize> for CircularBucket<T> {
 impl<T> IndexMut<usize> for CircularBucket<T> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         assert!(index < self.length);
-        let wrapped_index = (index + self.index) % self.capacity();
+        let wrapped_index = (index + self.start_index()) % self.capacity();
         &mut self.data[wrapped_index]
     }
 }
\ No newline at end of file
@@ -0,0 +1,150 @@
+use num::complex::Complex32;
+
+/// The magnitude that a full scale I or Q value of every sample format is converted to.
+/// This is the range of the unsigned 8bit format from RTL-SDR dongles that the demodulator settings were tuned on.
+/// Each format is scaled by a power of 2 so the conversion is exact and the results only differ by quantisation.
+pub const IQ_FULL_SCALE: f32 = 128.0;
+
+/// A single IQ sample from a receiver that can be converted into a complex number for the demodulator.
+/// Every format is an array of its I and Q values so a buffer of samples has the same layout as the interleaved stream.
+/// The signed formats can also be a tuple of their I and Q values which is converted the same way as the array.
+/// All formats are normalised so full scale has a magnitude of IQ_FULL_SCALE in both I and Q.
+/// This keeps the power of the same signal consistent between formats so the NULL power detection behaves the same.
+///
+/// # Examples
+/// The same signal read as unsigned 8bit and signed 16bit samples is demodulated identically.
+/// ```
+/// use ofdm::iq_sample::IqSample;
+/// use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+/// use ofdm::ofdm_parameters::OfdmParameters;
+/// use num::complex::Complex32;
+///
+/// // Signal with a NULL symbol in the middle that can be represented exactly in both formats
+/// let signal: Vec<[u8; 2]> = (0..3320)
+///     .map(|i| if (3000..3300).contains(&i) { [128, 128] } else { [128 + (i % 64) as u8, 100] })
+///     .collect();
+/// let signal_i16: Vec<[i16; 2]> = signal
+///     .iter()
+///     .map(|x| [(x[0] as i16 - 128)*256, (x[1] as i16 - 128)*256])
+///     .collect();
+/// assert_eq!(signal[5].to_complex32(), signal_i16[5].to_complex32());
+/// assert_eq!(signal_i16[5].to_complex32(), (signal_i16[5][0], signal_i16[5][1]).to_complex32());
+///
+/// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+/// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+/// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+/// let mut demod_u8 = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+/// let mut demod_i16 = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+/// demod_u8.process_raw(&signal);
+/// demod_i16.process_raw(&signal_i16);
+///
+/// assert!(demod_u8.signal_l1_average > 0.0);
+/// assert_eq!(demod_u8.signal_l1_average, demod_i16.signal_l1_average);
+/// assert!(matches!(demod_u8.state, OfdmDemodulatorState::ReadingNullAndPrs));
+/// assert_eq!(demod_u8.state, demod_i16.state);
+/// assert_eq!(demod_u8.null_prs_buffer.iter(), demod_i16.null_prs_buffer.iter());
+/// ```
+pub trait IqSample {
+    fn to_complex32(&self) -> Complex32;
+}
+
+/// Unsigned 8bit IQ pair as produced by an RTL-SDR dongle.
+/// The DC offset of 128 is removed so the output is centered around zero.
+impl IqSample for [u8; 2] {
+    #[inline(always)]
+    fn to_complex32(&self) -> Complex32 {
+        let dc_offset = 128.0;
+        Complex32::new(self[0] as f32 - dc_offset, self[1] as f32 - dc_offset)
+    }
+}
+
+/// Signed 16bit IQ pair.
+/// This is scaled down by 256 so the 16bit range of -32768 to 32767 maps onto the 8bit range of -128 to 127.996.
+impl IqSample for [i16; 2] {
+    #[inline(always)]
+    fn to_complex32(&self) -> Complex32 {
+        let scale = IQ_FULL_SCALE / 32768.0;
+        Complex32::new(self[0] as f32 * scale, self[1] as f32 * scale)
+    }
+}
+
+/// Floating point IQ pair where full scale is -1 to 1.
+/// Values outside of full scale are clamped to it like an ADC would saturate, and NaN is treated as 0.
+///
+/// # Examples
+/// ```
+/// use ofdm::iq_sample::{IqSample, IQ_FULL_SCALE};
+/// use num::complex::Complex32;
+///
+/// assert_eq!([0.5, -1.0].to_complex32(), Complex32::new(0.5*IQ_FULL_SCALE, -IQ_FULL_SCALE));
+/// assert_eq!([4.0, f32::NEG_INFINITY].to_complex32(), Complex32::new(IQ_FULL_SCALE, -IQ_FULL_SCALE));
+/// assert_eq!([f32::NAN, 0.25].to_complex32(), Complex32::new(0.0, 0.25*IQ_FULL_SCALE));
+/// assert_eq!((4.0, f32::NAN).to_complex32(), [4.0, f32::NAN].to_complex32());
+/// ```
+impl IqSample for [f32; 2] {
+    #[inline(always)]
+    fn to_complex32(&self) -> Complex32 {
+        Complex32::new(saturate_full_scale(self[0]), saturate_full_scale(self[1]))
+    }
+}
+
+impl IqSample for (i16, i16) {
+    #[inline(always)]
+    fn to_complex32(&self) -> Complex32 {
+        [self.0, self.1].to_complex32()
+    }
+}
+
+impl IqSample for (f32, f32) {
+    #[inline(always)]
+    fn to_complex32(&self) -> Complex32 {
+        [self.0, self.1].to_complex32()
+    }
+}
+
+#[inline(always)]
+fn saturate_full_scale(x: f32) -> f32 {
+    match x.is_nan() {
+        true => 0.0,
+        false => x.clamp(-1.0, 1.0) * IQ_FULL_SCALE,
+    }
+}
+
+/// The binary layout of interleaved IQ samples in a byte stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// Unsigned 8bit IQ pairs as produced by an RTL-SDR dongle.
+    U8,
+    /// Signed 16bit little endian IQ pairs.
+    I16,
+    /// Floating point 32bit little endian IQ pairs.
+    F32,
+}
+
+impl SampleFormat {
+    /// Number of bytes used by a single IQ sample.
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            SampleFormat::U8 => 2,
+            SampleFormat::I16 => 4,
+            SampleFormat::F32 => 8,
+        }
+    }
+
+    /// Converts a byte stream of whole IQ samples into complex samples and appends them to the output.
+    /// Any trailing bytes that don't make up a whole sample are ignored.
+    pub fn convert_bytes(&self, buf: &[u8], out: &mut Vec<Complex32>) {
+        let samples = buf.chunks_exact(self.bytes_per_sample());
+        match self {
+            SampleFormat::U8 => out.extend(samples.map(|x| [x[0], x[1]].to_complex32())),
+            SampleFormat::I16 => out.extend(samples.map(|x| [
+                i16::from_le_bytes([x[0], x[1]]),
+                i16::from_le_bytes([x[2], x[3]]),
+            ].to_complex32())),
+            SampleFormat::F32 => out.extend(samples.map(|x| [
+                f32::from_le_bytes([x[0], x[1], x[2], x[3]]),
+                f32::from_le_bytes([x[4], x[5], x[6], x[7]]),
+            ].to_complex32())),
+        }
+    }
+}
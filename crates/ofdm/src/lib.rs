@@ -1,5 +1,9 @@
 pub mod ofdm_parameters;
 pub mod ofdm_demodulator;
-
-mod circular_bucket;
-mod linear_bucket;
\ No newline at end of file
+pub mod ofdm_fft;
+pub mod ofdm_modulator;
+pub mod iq_sample;
+pub mod circular_bucket;
+pub mod linear_bucket;
+pub mod util;
+pub mod resample;
@@ -26,6 +26,10 @@ impl<T> LinearBucket<T> {
         self.length == self.capacity()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
     /// Returns immutable slice to valid data.
     pub fn iter(&self) -> &[T] {
         &self.data[..self.length]
@@ -56,6 +60,21 @@ impl<T:Default+Copy+Clone> LinearBucket<T> {
         }
     }
 
+    /// Alias for new().
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::new(capacity)
+    }
+
+    /// Resets it to being empty with a new capacity.
+    /// The internal buffer is only reallocated if the capacity changes.
+    pub fn clear_and_resize(&mut self, new_capacity: usize) {
+        self.length = 0;
+        if self.capacity() != new_capacity {
+            self.data.resize(new_capacity, T::default());
+            self.data.shrink_to_fit();
+        }
+    }
+
     /// Copies a array until the capacity has been reached.
     /// Returns the number of samples read from the array.
     pub fn consume(&mut self, buf: &[T]) -> usize {
@@ -68,6 +87,29 @@ impl<T:Default+Copy+Clone> LinearBucket<T> {
         total_read
     }
 
+    /// Copies the valid data into a buffer until either runs out.
+    /// Returns the number of values copied.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::linear_bucket::LinearBucket;
+    ///
+    /// let mut bucket = LinearBucket::<i32>::new(4);
+    /// bucket.consume(&[1, 2, 3]);
+    /// let mut out = [0; 6];
+    /// assert_eq!(bucket.copy_to(&mut out), 3);
+    /// assert_eq!(out, [1, 2, 3, 0, 0, 0]);
+    ///
+    /// let mut small = [0; 2];
+    /// assert_eq!(bucket.copy_to(&mut small), 2);
+    /// assert_eq!(small, [1, 2]);
+    /// ```
+    pub fn copy_to(&self, out: &mut [T]) -> usize {
+        let total_copy = self.length.min(out.len());
+        out[..total_copy].copy_from_slice(&self.data[..total_copy]);
+        total_copy
+    }
+
     /// Copies data from a generic iterator.
     /// Returns the number of samples read from iterator.
     pub fn consume_from_iterator<I>(&mut self, mut iter: I) -> usize 
@@ -90,6 +132,16 @@ impl<T:Default+Copy+Clone> LinearBucket<T> {
     }
 }
 
+impl<T> Default for LinearBucket<T> {
+    /// Creates an empty bucket with zero capacity.
+    fn default() -> Self {
+        Self {
+            data: vec![],
+            length: 0,
+        }
+    }
+}
+
 impl<T, U> Index<U> for LinearBucket<T> 
 where U: SliceIndex<[T]> 
 {
@@ -1,11 +1,145 @@
 use crate::ofdm_parameters::OfdmParameters;
 use crate::circular_bucket::CircularBucket;
 use crate::linear_bucket::LinearBucket;
+use crate::iq_sample::{IqSample, SampleFormat};
+use crate::util::{span_slice, chunk_slice, calculate_l1_average};
+use crate::ofdm_fft::{OfdmFft, RustFft};
+use std::io::Read;
 use std::sync::Arc;
 use std::cmp::Ordering;
-use num::complex::Complex32;
-use rustfft::{FftPlanner, Fft};
+use num::complex::{Complex, Complex32};
 use itertools::izip;
+use log::debug;
+
+/// Number of samples read at a time by process_reader().
+const READER_CHUNK_SAMPLES: usize = 8192;
+/// The largest gain the AGC can apply so a silent input doesn't amplify to infinity.
+const AGC_MAX_GAIN: f32 = 1e4;
+/// Number of consecutive coarse frequency estimates on the edge of the search range before it is considered saturated.
+const COARSE_FREQUENCY_SATURATION_FRAMES: u32 = 3;
+/// Number of most recent OFDM frames whose net frequency offset is kept for estimating the frequency offset jitter.
+const FREQUENCY_OFFSET_HISTORY_FRAMES: usize = 32;
+/// The lowest decibel value of the fine time impulse response so a zero correlation stays finite.
+const FINE_TIME_IMPULSE_FLOOR_DB: f32 = -200.0;
+
+type BitsOutCallback = Box<dyn FnMut(&[i8], &FrameInfo) + Send + Sync + 'static>;
+type DesyncCaptureCallback = Box<dyn FnMut(&[Complex32], &DesyncInfo) + Send + Sync + 'static>;
+type PrsOutCallback = Box<dyn FnMut(&[Complex32]) + Send + Sync + 'static>;
+type SymbolsOutCallback = Box<dyn FnMut(&[i8], &SymbolInfo) + Send + Sync + 'static>;
+type ProcessTraceCallback = Box<dyn FnMut(&OfdmDemodulatorState, usize) + Send + Sync + 'static>;
+
+/// Identifies a registered callback so it can be removed with the matching unsubscribe method.
+/// Each subscription to a demodulator gets a new id which is never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Unrecoverable conditions reported by OfdmDemodulator::try_process().
+/// The demodulator keeps running after these so the caller decides whether to retune, reset or stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemodError {
+    /// Fine time synchronisation failed this many times in a row, including across full resets.
+    /// This usually means there is no DAB signal at the tuned frequency or the signal was lost.
+    TooManyDesyncs { total_desyncs: u32 },
+}
+
+impl std::fmt::Display for DemodError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DemodError::TooManyDesyncs { total_desyncs } => write!(f, "Failed to synchronise to the PRS {} times in a row", total_desyncs),
+        }
+    }
+}
+
+impl std::error::Error for DemodError {}
+
+/// Reasons that a carrier map isn't a permutation of the data carriers returned by validate_carrier_map().
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarrierMapError {
+    /// The carrier map doesn't have exactly one entry for each data carrier.
+    WrongLength { expected: usize, actual: usize },
+    /// The entry at the index maps to a carrier outside of the data carriers.
+    OutOfRange { index: usize, carrier: usize },
+    /// The entry at the index maps to a carrier that an earlier entry already maps to.
+    Duplicate { index: usize, carrier: usize },
+}
+
+impl std::fmt::Display for CarrierMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CarrierMapError::WrongLength { expected, actual } => write!(f, "Carrier map has {} entries but there are {} data carriers", actual, expected),
+            CarrierMapError::OutOfRange { index, carrier } => write!(f, "Carrier map entry {} maps to carrier {} which is out of range", index, carrier),
+            CarrierMapError::Duplicate { index, carrier } => write!(f, "Carrier map entry {} maps to carrier {} which is already mapped to", index, carrier),
+        }
+    }
+}
+
+impl std::error::Error for CarrierMapError {}
+
+/// Information about an OFDM frame that is passed alongside its output bits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameInfo {
+    /// Increases by one for each OFDM frame outputted by the demodulator so consumers can detect dropped frames.
+    /// This is not reset when the demodulator is reset.
+    pub frame_counter: u64,
+    /// The net frequency offset normalised to the sampling frequency that the frame was demodulated with.
+    pub net_frequency_offset: f32,
+    /// The index of the sample where the PRS of the frame started, counting from the first sample passed to the demodulator.
+    /// This includes the fine time offset so it points to the start of the PRS cyclic prefix.
+    pub prs_start_sample: u64,
+    /// Whether the frame was cut short by flush() so its output bits cover fewer DQPSK symbols than a full frame.
+    pub is_partial: bool,
+}
+
+/// Information about a DQPSK symbol that is passed alongside its output bits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SymbolInfo {
+    /// The frame counter of the OFDM frame that the symbol belongs to which matches FrameInfo::frame_counter.
+    pub frame_counter: u64,
+    /// The index of the DQPSK symbol in the OFDM frame where 0 is formed from the PRS and the first data symbol.
+    pub symbol_index: usize,
+}
+
+/// Information about a failed fine time synchronisation that is passed alongside the NULL and PRS samples that were searched.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DesyncInfo {
+    /// The offset of the PRS from where it was expected at the highest impulse peak, even if that peak was rejected.
+    pub prs_start_offset: isize,
+    /// The height of the impulse peak above the average of the impulse response in dB.
+    pub peak_height_db: f32,
+}
+
+/// A block of samples used for power analysis that was completed by the current input buffer.
+#[derive(Debug, Clone, Copy)]
+struct PowerBlock {
+    /// The index in the input buffer one past the last sample of the block.
+    end: usize,
+    /// This is only calculated in advance for the blocks used in the signal average and blocks that straddle buffers.
+    l1_average: Option<f32>,
+}
+
+/// The method used to correlate the received PRS against the reference PRS in fine time synchronisation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FineTimeCorrelationMethod {
+    /// Multiplies the spectrum of the received PRS with the conjugate of the reference spectrum and takes the inverse FFT.
+    /// This calculates every lag of the circular cross correlation at once.
+    #[default]
+    FftMultiply,
+    /// Sums the products of the received PRS and the conjugate of the reference PRS in the time domain for each lag.
+    /// Each lag costs a full pass over the symbol so only the lags inside the fine time search window are calculated.
+    DirectTime,
+}
+
+/// The scale of the fine time impulse response that its peak is searched on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FineTimeImpulseScale {
+    /// The decibel value of the correlation whose peak is weighed by its distance from the expected location.
+    #[default]
+    Decibel,
+    /// The linear magnitude of the correlation whose peak is weighed by its distance from the expected location.
+    Linear,
+    /// The squared magnitude of the correlation which skips a logarithm for every sample but doesn't weigh the peak.
+    Power,
+}
 
 #[derive(Debug)]
 pub struct OfdmDemodulatorSettings {
@@ -18,12 +152,49 @@ pub struct OfdmDemodulatorSettings {
     pub null_power_decimation_factor: usize,
     /// The amount of the L1 power average that the signal needs to fall below to detect the start of the NULL symbol.
     pub null_power_threshold_start: f32,
+    /// The number of consecutive blocks that need to fall below the start threshold to detect the start of the NULL symbol.
+    /// This rejects brief fades in the middle of a frame and should be less than the number of blocks inside the NULL symbol.
+    pub null_power_min_dip_blocks: usize,
     /// The amount of the L1 power average that the signal needs to rise above to detect the end of the NULL symbol.
     pub null_power_threshold_end: f32,
+    /// The start threshold that replaces null_power_threshold_start after max_consecutive_desyncs until the next lock.
+    /// This widens the NULL search to the shallower dips of a noisy signal and should be below null_power_threshold_end.
+    pub null_power_widened_threshold_start: f32,
     /// The rate to update the fine frequency offset during each OFDM frame. 
     /// Fine frequency offsets are smaller than the frequency spacing of one FFT bin.
     /// This is a number from 0 to 1 where 1 is the fastest update rate.
     pub fine_frequency_update_beta: f32,
+    /// Whether fine frequency is tracked with a second order proportional-integral (PI) loop instead of a first order loop.
+    /// The integral term lets the loop track frequency drift without a steady state error.
+    pub fine_frequency_pi_is_enabled: bool,
+    /// The proportional gain of the fine frequency PI loop.
+    pub fine_frequency_pi_proportional_gain: f32,
+    /// The integral gain of the fine frequency PI loop.
+    pub fine_frequency_pi_integral_gain: f32,
+    /// The fraction of the cyclic prefix that is correlated against the end of the symbol to estimate the fine frequency offset.
+    /// Only the central portion of the cyclic prefix is used so that the edges contaminated by multipath are skipped.
+    /// This is a number from 0 to 1 where 1 uses the full cyclic prefix.
+    pub fine_frequency_cyclic_prefix_fraction: f32,
+    /// The fine frequency offset is wrapped to within this many half FFT bins of zero after each update so coarse frequency correction tracks the whole bins.
+    /// None lets the offset accumulate past one bin to follow a drifting carrier, which should only be used while coarse frequency correction is disabled.
+    pub fine_frequency_wrap_margin: Option<f32>,
+    /// The length of the raised cosine window that crossfades the end of each symbol into its cyclic prefix as a fraction of the cyclic prefix, where 0 disables it.
+    /// This reduces inter-carrier interference from Doppler shifted echoes as long as the delay spread is shorter than the rest of the cyclic prefix.
+    pub prefix_window_rolloff: f32,
+    /// Whether each symbol is demodulated as soon as it has been read instead of after the whole OFDM frame is read.
+    /// This lowers the latency of the soft bits passed to subscribe_symbols_out() to one symbol period.
+    /// The whole frame is still passed to subscribe_bits_out() once it is complete and its soft bits are unchanged.
+    pub symbol_streaming_is_enabled: bool,
+    /// The maximum number of DQPSK symbols at the start of each OFDM frame that are demodulated and outputted, or None for every symbol.
+    /// The remaining symbols are still used for synchronisation and changes take effect at the start of the next frame.
+    pub max_output_symbols: Option<usize>,
+    /// Whether the data demapper calculates the soft bits with fixed point arithmetic instead of floating point.
+    /// Each DQPSK value is converted into 16bit integers with its own power of two scale using integer operations on its
+    /// floating point representation, and then demapped with calculate_soft_bits_fixed_point().
+    pub soft_bits_fixed_point_is_enabled: bool,
+    /// Whether the data demapper undoes the frequency interleaving so carrier i of the soft bits was read from the physical carrier carrier_map\[i\].
+    /// When disabled the carriers are in the physical order of increasing frequency for decoders that undo the frequency interleaving themselves.
+    pub frequency_deinterleaving_is_enabled: bool,
     /// Whether we perform coarse frequency correction. 
     /// Coarse frequency offsets are larger than the frequency spacing of one FFT bin.
     pub coarse_frequency_is_enabled: bool,
@@ -34,6 +205,13 @@ pub struct OfdmDemodulatorSettings {
     /// This is only used when the coarse frequency offset changes in small amounts for after a stable period.
     /// This is a number from 0 to 1 where 1 is the fastest update rate.
     pub coarse_frequency_slow_update_beta: f32,
+    /// This is the amount to weigh down the height of the coarse frequency peak based on its distance from zero offset.
+    /// Strong out of band energy or an image of the signal can produce spurious peaks far away from the true offset.
+    /// Therefore nearer peaks are preferred unless a distant peak is much stronger.
+    /// The peak height is in dB so the weight is applied as a penalty of 10*log10(1 - penalty*distance) dB.
+    /// This is a number from 0 to 1 where 0 disables the weighting and values closer to 1 penalise peaks at the edge of the search range more.
+    /// Values outside of this range are clamped to just below 1 so the penalty at the edge of the search range stays finite.
+    pub coarse_frequency_peak_distance_penalty: f32,
     /// During fine time correction we generate an impulse response, where the highest peak is considered the start of our phase reference symbol (PRS).
     /// This is the required height for the impulse peak to be considered valid as the start of the PRS.
     pub fine_time_impulse_peak_threshold_db: f32,
@@ -41,6 +219,72 @@ pub struct OfdmDemodulatorSettings {
     /// We assume that after the NULL symbol detection step that the PRS will be situated roughly in the correct position.
     /// Therefore to prevent spurious locks onto peaks that are far away from the expected position due to noise, we lower the perceived height of the peak the further away it is.
     pub fine_time_impulse_peak_distance_probability: f32,
+    /// The scale of the values in the impulse response buffer that the fine time impulse peak is searched on.
+    /// The peak threshold is given in dB for every scale.
+    pub fine_time_impulse_scale: FineTimeImpulseScale,
+    /// If set then only peaks within this many samples of the expected location are considered in fine time synchronisation.
+    /// Peaks inside this window are still weighed by their distance from the expected location.
+    pub fine_time_search_radius: Option<usize>,
+    /// Whether the FFT of each symbol is corrected for the fractional part of the fine time offset.
+    /// The DQPSK values are unaffected but the FFT of the PRS becomes a better channel estimate for external equalisation.
+    pub fine_time_fractional_delay_is_enabled: bool,
+    /// How the impulse response of the PRS is calculated in fine time synchronisation.
+    /// Lags outside of the search window aren't calculated by the direct method and are set to the average of the ones that are.
+    pub fine_time_correlation_method: FineTimeCorrelationMethod,
+    /// The rate to update a coherent average of the received PRS that is used for fine time synchronisation.
+    /// Averaging the PRS across frames reduces the noise in the impulse response at low SNR.
+    /// This is a number from 0 to 1 where 1 is the fastest update rate, and 0 disables averaging.
+    pub prs_averaging_beta: f32,
+    /// The maximum fine time offset in samples that fine time synchronisation accepts before forcing a full resync, or None to accept any offset.
+    /// A large jump of the frame boundary usually means that a spurious peak was locked onto.
+    pub max_fine_time_offset: Option<usize>,
+    /// The number of consecutive failed fine time synchronisations before the demodulator fully resets and widens the NULL search.
+    /// Before this is reached the demodulator retries finding the NULL symbol while keeping its frequency offsets and signal average.
+    pub max_consecutive_desyncs: u32,
+    /// If set then try_process() returns an error once fine time synchronisation fails this many times in a row.
+    /// This counts across the full resets caused by max_consecutive_desyncs so it can detect a signal that never locks.
+    /// The count starts again after the error is returned.
+    pub max_desyncs_before_error: Option<u32>,
+    /// Whether NaN and infinite samples are replaced with zero before any other processing and counted in total_non_finite_samples.
+    /// A single one would otherwise spread through the FFTs and running averages and corrupt the synchronisation.
+    pub non_finite_sanitisation_enabled: bool,
+    /// Whether we estimate and remove the DC offset of the incoming samples.
+    /// Receivers can have a residual DC bias that adds energy to the DC bin and biases the NULL symbol detection.
+    pub dc_offset_correction_enabled: bool,
+    /// The rate to update the DC offset estimate for each incoming sample.
+    /// This should be slow enough that the estimate is averaged over multiple OFDM frames and not the NULL symbol alone.
+    /// This is a number from 0 to 1 where 1 is the fastest update rate.
+    pub dc_offset_update_beta: f32,
+    /// Whether we apply the estimated I/Q gain and phase imbalance correction to the incoming samples.
+    /// The imbalance is always estimated from the PRS so it can be monitored even when this is disabled.
+    pub iq_imbalance_correction_enabled: bool,
+    /// The rate to update the I/Q imbalance estimate for each PRS.
+    /// This is a number from 0 to 1 where 1 is the fastest update rate.
+    pub iq_imbalance_update_beta: f32,
+    /// Whether we scale the incoming samples so their L1 average tracks agc_target_level.
+    /// This is applied after the DC offset and I/Q imbalance correction so the signal level is consistent across receiver gains.
+    pub agc_enabled: bool,
+    /// The L1 average of the samples that the AGC scales the incoming samples to.
+    /// This is on the same scale as signal_l1_average where IQ_FULL_SCALE is the full scale of each of I and Q.
+    pub agc_target_level: f32,
+    /// The rate to update the AGC estimate of the input level for each incoming sample.
+    /// This should be slow enough that the gain doesn't rise noticeably during the NULL symbol.
+    /// This is a number from 0 to 1 where 1 is the fastest update rate.
+    pub agc_update_beta: f32,
+    /// Whether the coarse frequency offset is held at the manual value instead of being estimated.
+    /// Freezing the tracking loops is useful when diagnosing which of them is misbehaving.
+    pub freeze_coarse_frequency: bool,
+    /// The coarse frequency offset normalised to the sampling frequency that is used while it is frozen.
+    pub manual_coarse_frequency_offset: f32,
+    /// Whether the fine frequency offset is held at the manual value instead of being estimated.
+    pub freeze_fine_frequency: bool,
+    /// The fine frequency offset normalised to the sampling frequency that is used while it is frozen.
+    pub manual_fine_frequency_offset: f32,
+    /// Whether the fine time offset is held at the manual value instead of being found from the PRS impulse response.
+    /// No desyncs are detected while this is frozen since the impulse peak isn't used.
+    pub freeze_fine_time: bool,
+    /// The number of samples the OFDM frame is offset by from the detected NULL symbol that is used while it is frozen.
+    pub manual_fine_time_offset: isize,
 }
 
 impl Default for OfdmDemodulatorSettings {
@@ -50,34 +294,264 @@ impl Default for OfdmDemodulatorSettings {
             null_power_total_samples: 100,
             null_power_decimation_factor: 5,
             null_power_threshold_start: 0.35,
+            null_power_min_dip_blocks: 1,
             null_power_threshold_end: 0.75,
+            null_power_widened_threshold_start: 0.55,
             fine_frequency_update_beta: 0.95,
+            fine_frequency_pi_is_enabled: false,
+            fine_frequency_pi_proportional_gain: 0.5,
+            fine_frequency_pi_integral_gain: 0.05,
+            fine_frequency_cyclic_prefix_fraction: 1.0,
+            fine_frequency_wrap_margin: Some(1.01),
+            prefix_window_rolloff: 0.0,
+            symbol_streaming_is_enabled: false,
+            max_output_symbols: None,
+            soft_bits_fixed_point_is_enabled: false,
+            frequency_deinterleaving_is_enabled: true,
             coarse_frequency_is_enabled: true,
             coarse_frequency_max_range: 0.1, 
             coarse_frequency_slow_update_beta: 0.1,
+            coarse_frequency_peak_distance_penalty: 0.05,
             fine_time_impulse_peak_threshold_db: 20.0,
             fine_time_impulse_peak_distance_probability: 0.15,
+            fine_time_impulse_scale: FineTimeImpulseScale::Decibel,
+            fine_time_search_radius: None,
+            fine_time_fractional_delay_is_enabled: false,
+            fine_time_correlation_method: FineTimeCorrelationMethod::FftMultiply,
+            prs_averaging_beta: 0.0,
+            max_fine_time_offset: None,
+            max_consecutive_desyncs: 3,
+            max_desyncs_before_error: None,
+            non_finite_sanitisation_enabled: true,
+            dc_offset_correction_enabled: false,
+            dc_offset_update_beta: 1e-6,
+            iq_imbalance_correction_enabled: false,
+            iq_imbalance_update_beta: 0.1,
+            agc_enabled: false,
+            agc_target_level: 32.0,
+            agc_update_beta: 1e-5,
+            freeze_coarse_frequency: false,
+            manual_coarse_frequency_offset: 0.0,
+            freeze_fine_frequency: false,
+            manual_fine_frequency_offset: 0.0,
+            freeze_fine_time: false,
+            manual_fine_time_offset: 0,
         }
     }
 }
 
-#[derive(Debug)]
+impl OfdmDemodulatorSettings {
+    /// Creates the default settings scaled to the structure of the OFDM frame.
+    /// The NULL symbol length varies widely between transmission modes so a fixed block size for power detection
+    /// would only fit a few blocks inside short NULL symbols and limit how precisely its start and end are found.
+    /// Instead the block size is scaled so there are about 27 blocks inside the NULL symbol.
+    /// The decimation factor is kept since it only controls how many blocks are used for the signal average.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulatorSettings;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    ///
+    /// // DAB transmission modes I and III
+    /// let settings_mode_i = OfdmDemodulatorSettings::new(&OfdmParameters::new(76, 2656, 2552, 2048, 1536));
+    /// let settings_mode_iii = OfdmDemodulatorSettings::new(&OfdmParameters::new(153, 345, 319, 256, 192));
+    /// assert_eq!(settings_mode_i.null_power_total_samples, 100);
+    /// assert!(settings_mode_iii.null_power_total_samples < settings_mode_i.null_power_total_samples);
+    /// ```
+    pub fn new(params: &OfdmParameters) -> Self {
+        let null_power_block_fraction: f32 = 0.0375;
+        let null_power_total_samples = (null_power_block_fraction * params.nb_null_period as f32).round().max(1.0) as usize;
+        Self {
+            null_power_total_samples,
+            ..Self::default()
+        }
+    }
+
+    /// Lists the name and value of every setting so the exact configuration can be attached to bug reports.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulatorSettings;
+    ///
+    /// let mut settings = OfdmDemodulatorSettings::default();
+    /// settings.fine_time_search_radius = Some(12);
+    /// let description = settings.describe();
+    /// assert!(description.contains(&("coarse_frequency_is_enabled", "true".to_string())));
+    /// assert!(description.contains(&("fine_time_search_radius", "Some(12)".to_string())));
+    /// assert!(description.contains(&("manual_fine_time_offset", "0".to_string())));
+    /// ```
+    pub fn describe(&self) -> Vec<(&'static str, String)> {
+        // The settings are destructured without a rest pattern so a new setting can't be left out
+        macro_rules! describe_settings {
+            ($($field:ident),* $(,)?) => {{
+                let Self { $($field),* } = self;
+                vec![$((stringify!($field), format!("{:?}", $field))),*]
+            }};
+        }
+        describe_settings!(
+            null_power_update_beta,
+            null_power_total_samples,
+            null_power_decimation_factor,
+            null_power_threshold_start,
+            null_power_min_dip_blocks,
+            null_power_threshold_end,
+            null_power_widened_threshold_start,
+            fine_frequency_update_beta,
+            fine_frequency_pi_is_enabled,
+            fine_frequency_pi_proportional_gain,
+            fine_frequency_pi_integral_gain,
+            fine_frequency_cyclic_prefix_fraction,
+            fine_frequency_wrap_margin,
+            prefix_window_rolloff,
+            symbol_streaming_is_enabled,
+            max_output_symbols,
+            soft_bits_fixed_point_is_enabled,
+            frequency_deinterleaving_is_enabled,
+            coarse_frequency_is_enabled,
+            coarse_frequency_max_range,
+            coarse_frequency_slow_update_beta,
+            coarse_frequency_peak_distance_penalty,
+            fine_time_impulse_peak_threshold_db,
+            fine_time_impulse_peak_distance_probability,
+            fine_time_impulse_scale,
+            fine_time_search_radius,
+            fine_time_fractional_delay_is_enabled,
+            fine_time_correlation_method,
+            prs_averaging_beta,
+            max_fine_time_offset,
+            max_consecutive_desyncs,
+            max_desyncs_before_error,
+            non_finite_sanitisation_enabled,
+            dc_offset_correction_enabled,
+            dc_offset_update_beta,
+            iq_imbalance_correction_enabled,
+            iq_imbalance_update_beta,
+            agc_enabled,
+            agc_target_level,
+            agc_update_beta,
+            freeze_coarse_frequency,
+            manual_coarse_frequency_offset,
+            freeze_fine_frequency,
+            manual_fine_frequency_offset,
+            freeze_fine_time,
+            manual_fine_time_offset,
+        )
+    }
+}
+
+/// The discriminants are stable so hosts can store and compare states without parsing their names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
 pub enum OfdmDemodulatorState {
     /// Finding the NULL symbol by analysing the average L1 power of blocks in the signal
-    FindingNullPowerDip,
+    FindingNullPowerDip = 0,
     /// Once the NULL symbol has been detected we read the NULL and PRS symbol
-    ReadingNullAndPrs,
+    ReadingNullAndPrs = 1,
     /// Compensating for large frequency offsets that are greater than one FFT bin
-    RunningCoarseFrequencySynchronisation,
+    RunningCoarseFrequencySynchronisation = 2,
     /// Compensating for sample offsets where we detected our NULL and PRS symbols. 
     /// This step can fail if the impulse peak is too weak or too far away from our expected location.
     /// When this occurs the demodulator will go back to finding the NULL symbol through L1 power analysis.
-    RunningFineTimeSync,
+    RunningFineTimeSync = 3,
     /// Once the NULL and PRS symbol have been read we read in the rest of the OFDM frame.
-    ReadingSymbols,
+    ReadingSymbols = 4,
     /// Once the OFDM frame has been read we process the symbols.
     /// This includes performing DQPSK demodulation, fine frequency compensation and data carrier remapping.
-    ProcessingSymbols,
+    ProcessingSymbols = 5,
+}
+
+impl OfdmDemodulatorState {
+    /// Returns a human readable name of the state for display.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OfdmDemodulatorState::FindingNullPowerDip => "Finding NULL power dip",
+            OfdmDemodulatorState::ReadingNullAndPrs => "Reading NULL and PRS",
+            OfdmDemodulatorState::RunningCoarseFrequencySynchronisation => "Coarse frequency synchronisation",
+            OfdmDemodulatorState::RunningFineTimeSync => "Fine time synchronisation",
+            OfdmDemodulatorState::ReadingSymbols => "Reading symbols",
+            OfdmDemodulatorState::ProcessingSymbols => "Processing symbols",
+        }
+    }
+
+    /// Returns the stable integer value of the state.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+    ///
+    /// let state = OfdmDemodulatorState::ReadingSymbols;
+    /// assert_eq!(state.as_u8(), 4);
+    /// assert_eq!(OfdmDemodulatorState::from_u8(state.as_u8()), Some(state));
+    /// assert_eq!(OfdmDemodulatorState::from_u8(6), None);
+    /// // The name used for debugging is unchanged
+    /// assert_eq!(format!("{:?}", state), "ReadingSymbols");
+    /// ```
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Returns the state with a value from as_u8() or None if the value isn't a valid state.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(OfdmDemodulatorState::FindingNullPowerDip),
+            1 => Some(OfdmDemodulatorState::ReadingNullAndPrs),
+            2 => Some(OfdmDemodulatorState::RunningCoarseFrequencySynchronisation),
+            3 => Some(OfdmDemodulatorState::RunningFineTimeSync),
+            4 => Some(OfdmDemodulatorState::ReadingSymbols),
+            5 => Some(OfdmDemodulatorState::ProcessingSymbols),
+            _ => None,
+        }
+    }
+}
+
+/// The frequency and time corrections that the demodulator is currently applying.
+/// Frequency offsets are normalised to the sampling frequency unless they are in Hz.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncState {
+    /// The sum of the coarse and fine frequency offsets which is the total correction applied to the samples.
+    pub net_frequency_offset: f32,
+    /// The frequency offset in multiples of the FFT bin spacing.
+    pub coarse_frequency_offset: f32,
+    /// The frequency offset smaller than the FFT bin spacing.
+    pub fine_frequency_offset: f32,
+    /// The offset in samples of the PRS from where it was expected in the last fine time synchronisation.
+    pub fine_time_offset: isize,
+    /// The fine time offset with the fractional part interpolated from the impulse peak.
+    pub fine_time_offset_subsample: f32,
+    /// The net frequency offset in Hz.
+    pub net_frequency_offset_hz: f32,
+    /// The coarse frequency offset in Hz.
+    pub coarse_frequency_offset_hz: f32,
+    /// The fine frequency offset in Hz.
+    pub fine_frequency_offset_hz: f32,
+}
+
+/// A copy of the scalar statistics of the demodulator.
+/// This can be taken while briefly holding a lock on the demodulator and then displayed without holding it.
+#[derive(Debug, Clone, Copy)]
+pub struct DemodStats {
+    pub state: OfdmDemodulatorState,
+    pub is_locked: bool,
+    pub total_frames_read: u32,
+    pub total_frames_desync: u32,
+    pub total_consecutive_desyncs: u32,
+    pub is_null_search_widened: bool,
+    pub samples_to_first_lock: Option<u64>,
+    pub total_non_finite_samples: u64,
+    pub sync_state: SyncState,
+    pub coarse_frequency_saturated: bool,
+    pub fine_frequency_integral_term: f32,
+    pub fine_frequency_drift_hz: f32,
+    pub frequency_offset_jitter_hz: f32,
+    pub fine_time_peak_height_db: f32,
+    pub signal_l1_average: f32,
+    pub null_symbol_noise_power: f32,
+    pub dc_offset: Complex32,
+    pub iq_imbalance_gain_ratio: f32,
+    pub iq_imbalance_phase_error: f32,
+    pub agc_gain: f32,
+    pub raw_ber_estimate: f32,
+    pub frame_progress: f32,
 }
 
 pub struct OfdmDemodulator {
@@ -88,27 +562,74 @@ pub struct OfdmDemodulator {
     pub total_frames_read: u32,
     /// The number of OFDM frames that desynced if the detected NULL and PRS symbols are too offset in time. 
     pub total_frames_desync: u32,
+    /// The number of OFDM frames that desynced in a row without a successful fine time synchronisation.
+    pub total_consecutive_desyncs: u32,
+    /// Whether the NULL search uses null_power_widened_threshold_start after too many consecutive desyncs.
+    pub is_null_search_widened: bool,
+    total_desyncs_before_error: u32,
+    /// The number of OFDM frames read successfully since the last desync.
+    pub frames_since_last_desync: u32,
+    /// The number of samples consumed from a cold start until the first OFDM frame was read successfully.
+    pub samples_to_first_lock: Option<u64>,
+    /// The number of NaN and infinite input samples that were replaced with zero.
+    /// This is only counted when non-finite sanitisation is enabled.
+    pub total_non_finite_samples: u64,
+    total_samples_read: u64,
+    /// The total number of samples passed through the demodulator.
+    /// This is not reset when the demodulator is reset so it can be used as a timestamp for each sample.
+    pub total_samples_processed: u64,
+    prs_start_sample: u64,
     is_found_coarse_frequency_offset: bool,
     /// The current coarse frequency offset normalised to the sampling frequency.
     pub coarse_frequency_offset: f32,
+    /// Whether the coarse frequency estimate has been stuck on the edge of the search range for several frames.
+    /// This usually means the true offset is outside of the coarse frequency max range and the search range should be widened.
+    pub coarse_frequency_saturated: bool,
+    total_coarse_frequency_edge_frames: u32,
     /// The current fine frequency offset normalised to the sampling frequency.
     pub fine_frequency_offset: f32,
+    /// The accumulated integral term of the fine frequency PI loop normalised to the sampling frequency.
+    /// This is the frequency drift per OFDM frame that the loop is tracking.
+    pub fine_frequency_integral_term: f32,
     /// The number of samples the incoming OFDM frame is offset by in time.
+    /// The NULL symbol at the end of each frame is carried over relative to the PRS that was found so only the first frame after synchronising is offset.
     pub fine_time_offset: isize,
+    /// The fine time offset with a fractional part from parabolic interpolation of the impulse peak and its neighbours.
+    /// This is the same as fine_time_offset when the fine time offset is held.
+    pub fine_time_offset_subsample: f32,
+    /// The height of the fine time impulse peak above the average of the impulse response in dB.
+    /// This is updated on every attempt at fine time synchronisation, including ones that desync.
+    pub fine_time_peak_height_db: f32,
     is_null_start_found: bool,
+    nb_null_dip_blocks: usize,
     is_null_end_found: bool,
     /// The current L1 signal average of the receiving signal.
     pub signal_l1_average: f32,
+    null_symbol_noise_power: f32,
+    dc_offset: Complex32,
+    iq_imbalance_gain_ratio: f32,
+    iq_imbalance_phase_error: f32,
+    agc_input_level: f32,
+    agc_gain: f32,
+    raw_ber_estimate: f32,
     // fft
-    fft: Arc<dyn Fft<f32>>,
-    ifft: Arc<dyn Fft<f32>>,
+    fft: Arc<dyn OfdmFft>,
+    is_custom_fft: bool,
     temp_fft_buffer: Vec<Complex32>,
+    fixed_point_dqpsk_buffer: Vec<Complex<i16>>,
     // reference data
     carrier_mapper_data: Vec<usize>,
     correlation_prs_fft_data: Vec<Complex32>,
     correlation_prs_time_data: Vec<Complex32>,
+    correlation_prs_reference_time_data: Vec<Complex32>,
     // buffers
+    prs_average_buffer: Vec<Complex32>,
+    is_prs_average_valid: bool,
     null_power_dip_buffer: CircularBucket<Complex32>,
+    signal_power_block_buffer: LinearBucket<Complex32>,
+    signal_power_block_index: usize,
+    power_blocks: Vec<PowerBlock>,
+    power_block_cursor: usize,
     /// The buffer that holds the current predicted NULL and PRS symbols.
     pub null_prs_buffer: LinearBucket<Complex32>,
     /// The buffer that holds the fine time impulse response buffer. 
@@ -118,61 +639,227 @@ pub struct OfdmDemodulator {
     /// There should be multiple peaks with the largest peak indicating the coarse frequency offset.
     /// The spacing between each sample indicates a frequency different of one FFT bin.
     pub coarse_frequency_impulse_response_buffer: Vec<f32>,
+    /// The buffer that holds the magnitude of the channel response for each FFT bin estimated from the last PRS.
+    /// FFT bins that aren't data carriers have no reference and are zero.
+    pub channel_response_buffer: Vec<f32>,
     data_time_buffer: LinearBucket<Complex32>,
     data_fft_buffer: Vec<Complex32>,
+    prefix_window: Vec<f32>,
+    fractional_delay_buffer: Vec<Complex32>,
     /// The buffer that holds the constellations of DQPSK complex symbols for each data symbol.
     pub data_dqpsk_buffer: Vec<Complex32>,
     /// The buffer that holds the soft decision bits outputted for each data symbol after carrier remapping.
     pub data_out_bits_buffer: Vec<i8>,
-    bits_out_callbacks: Vec<Box<dyn FnMut(&[i8]) + Send + Sync + 'static>>,
+    raw_samples_buffer: Vec<Complex32>,
+    corrected_samples_buffer: Vec<Complex32>,
+    replay_buffer: CircularBucket<Complex32>,
+    frequency_offset_history: CircularBucket<f32>,
+    frame_counter: u64,
+    frame_info: FrameInfo,
+    nb_symbols_demodulated: usize,
+    nb_frame_dqpsk_symbols: usize,
+    next_subscription_id: u64,
+    bits_out_callbacks: Vec<(SubscriptionId, BitsOutCallback)>,
+    desync_capture_callbacks: Vec<(SubscriptionId, DesyncCaptureCallback)>,
+    prs_out_callbacks: Vec<(SubscriptionId, PrsOutCallback)>,
+    symbols_out_callbacks: Vec<(SubscriptionId, SymbolsOutCallback)>,
+    process_trace_callbacks: Vec<(SubscriptionId, ProcessTraceCallback)>,
 }
 
 impl OfdmDemodulator {
+    /// Creates a demodulator like try_new() but panics if the carrier map is invalid.
     pub fn new(params: &OfdmParameters, carrier_mapper: &[usize], prs_fft: &[Complex32]) -> Self {
-        assert!(params.nb_fft_data_carriers == carrier_mapper.len(), "Mismatching number of data carriers between params {} and lookup table {}", params.nb_fft_data_carriers, carrier_mapper.len());
-        assert!(params.nb_fft == prs_fft.len(), "Mismatching FFT size between params {} and FFT buffer {}", params.nb_fft, prs_fft.len());
+        Self::try_new(params, carrier_mapper, prs_fft).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a demodulator for the frame structure with the carrier map and the FFT of the PRS.
+    /// Returns an error if the carrier map isn't a permutation of the data carriers.
+    /// Panics if the PRS FFT doesn't have nb_fft samples.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::{OfdmDemodulator, CarrierMapError};
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    /// let mut carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// assert!(OfdmDemodulator::try_new(&params, &carrier_map, &prs_fft).is_ok());
+    ///
+    /// carrier_map[10] = carrier_map[3];
+    /// let err = OfdmDemodulator::try_new(&params, &carrier_map, &prs_fft).err();
+    /// assert_eq!(err, Some(CarrierMapError::Duplicate { index: 10, carrier: 3 }));
+    /// ```
+    pub fn try_new(params: &OfdmParameters, carrier_mapper: &[usize], prs_fft: &[Complex32]) -> Result<Self, CarrierMapError> {
+        let mut demodulator = Self::try_with_fft(params, carrier_mapper, prs_fft, Arc::new(RustFft::new(params.nb_fft)))?;
+        demodulator.is_custom_fft = false;
+        Ok(demodulator)
+    }
+
+    /// Creates a demodulator like try_new() that uses a different FFT implementation instead of rustfft.
+    /// The FFT must have nb_fft samples.
+    pub fn try_with_fft(params: &OfdmParameters, carrier_mapper: &[usize], prs_fft: &[Complex32], fft: Arc<dyn OfdmFft>) -> Result<Self, CarrierMapError> {
+        validate_carrier_map(carrier_mapper, params.nb_fft_data_carriers)?;
+        check_reference_data(params, prs_fft);
 
-        let mut planner = FftPlanner::new();
-        let fft = planner.plan_fft_forward(params.nb_fft);
-        let ifft = planner.plan_fft_inverse(params.nb_fft);
+        let settings = OfdmDemodulatorSettings::new(params);
+        let signal_power_block_buffer = LinearBucket::<Complex32>::new(settings.null_power_total_samples);
 
         let mut demodulator = Self {
             state: OfdmDemodulatorState::FindingNullPowerDip,
-            settings: OfdmDemodulatorSettings::default(),
+            settings,
             params: *params,
             // initial state
             total_frames_read: 0,
             total_frames_desync: 0,
+            total_consecutive_desyncs: 0,
+            is_null_search_widened: false,
+            total_desyncs_before_error: 0,
+            frames_since_last_desync: 0,
+            samples_to_first_lock: None,
+            total_non_finite_samples: 0,
+            total_samples_read: 0,
+            total_samples_processed: 0,
+            prs_start_sample: 0,
             is_found_coarse_frequency_offset: false,
             coarse_frequency_offset: 0.0,
+            coarse_frequency_saturated: false,
+            total_coarse_frequency_edge_frames: 0,
             fine_frequency_offset: 0.0,
+            fine_frequency_integral_term: 0.0,
             fine_time_offset: 0,
+            fine_time_offset_subsample: 0.0,
+            fine_time_peak_height_db: 0.0,
             is_null_start_found: false,
+            nb_null_dip_blocks: 0,
             is_null_end_found: false,
             signal_l1_average: 0.0,
+            null_symbol_noise_power: 0.0,
+            dc_offset: Complex32::default(),
+            iq_imbalance_gain_ratio: 1.0,
+            iq_imbalance_phase_error: 0.0,
+            agc_input_level: 0.0,
+            agc_gain: 1.0,
+            raw_ber_estimate: 0.0,
             // fft
             fft,
-            ifft,
+            is_custom_fft: true,
             // data
             carrier_mapper_data: carrier_mapper.to_vec(),
-            correlation_prs_fft_data: vec![Complex32::default(); params.nb_fft],
-            correlation_prs_time_data: vec![Complex32::default(); params.nb_fft],
+            correlation_prs_fft_data: vec![],
+            correlation_prs_time_data: vec![],
+            correlation_prs_reference_time_data: vec![],
             // buffer
-            null_power_dip_buffer: CircularBucket::<Complex32>::new(params.nb_null_period),
-            null_prs_buffer: LinearBucket::<Complex32>::new(params.nb_null_period + params.nb_symbol_period),
-            fine_time_impulse_response_buffer: vec![0.0; params.nb_fft],
-            coarse_frequency_impulse_response_buffer: vec![0.0; params.nb_fft],
-            temp_fft_buffer: vec![Complex32::default(); params.nb_fft],
-            data_time_buffer: LinearBucket::<Complex32>::new(params.nb_input_samples),
-            data_fft_buffer: vec![Complex32::default(); params.nb_symbols*params.nb_fft],
-            data_dqpsk_buffer: vec![Complex32::default(); params.nb_output_samples],
-            data_out_bits_buffer: vec![0i8; params.nb_output_bits],
+            prs_average_buffer: vec![],
+            is_prs_average_valid: false,
+            null_power_dip_buffer: CircularBucket::<Complex32>::new(0),
+            signal_power_block_buffer,
+            signal_power_block_index: 0,
+            power_blocks: vec![],
+            power_block_cursor: 0,
+            null_prs_buffer: LinearBucket::<Complex32>::default(),
+            fine_time_impulse_response_buffer: vec![],
+            coarse_frequency_impulse_response_buffer: vec![],
+            temp_fft_buffer: vec![],
+            fixed_point_dqpsk_buffer: vec![],
+            channel_response_buffer: vec![],
+            data_time_buffer: LinearBucket::<Complex32>::default(),
+            data_fft_buffer: vec![],
+            prefix_window: vec![],
+            fractional_delay_buffer: vec![],
+            data_dqpsk_buffer: vec![],
+            data_out_bits_buffer: vec![],
+            raw_samples_buffer: vec![],
+            corrected_samples_buffer: vec![],
+            replay_buffer: CircularBucket::<Complex32>::new(0),
+            frequency_offset_history: CircularBucket::<f32>::new(FREQUENCY_OFFSET_HISTORY_FRAMES),
             // callbacks
+            frame_counter: 0,
+            frame_info: FrameInfo::default(),
+            nb_symbols_demodulated: 0,
+            nb_frame_dqpsk_symbols: 0,
+            next_subscription_id: 0,
             bits_out_callbacks: vec![],
+            desync_capture_callbacks: vec![],
+            prs_out_callbacks: vec![],
+            symbols_out_callbacks: vec![],
+            process_trace_callbacks: vec![],
         };
 
+        demodulator.resize_buffers();
         demodulator.init(prs_fft);
-        demodulator
+        Ok(demodulator)
+    }
+
+    /// Changes the structure of the OFDM frame that is demodulated, for example when switching transmission modes.
+    /// The FFT is only replanned if its size changes and buffers are only reallocated if their sizes change.
+    /// The demodulator is reset while settings and callbacks are kept.
+    /// Settings that scale with the frame structure are rescaled if they weren't changed from their defaults.
+    /// Panics if the carrier map is invalid like new().
+    /// Also panics if the FFT size changes while a different FFT implementation from try_with_fft() is used,
+    /// since it can't be replanned here and reconfigure_with_fft() has to be given one of the new size instead.
+    pub fn reconfigure(&mut self, params: &OfdmParameters, carrier_mapper: &[usize], prs_fft: &[Complex32]) {
+        let mut fft = None;
+        if params.nb_fft != self.params.nb_fft {
+            assert!(
+                !self.is_custom_fft,
+                "FFT size changes from {} to {} but the FFT implementation from try_with_fft() can't be replanned, use reconfigure_with_fft()",
+                self.params.nb_fft, params.nb_fft,
+            );
+            fft = Some(Arc::new(RustFft::new(params.nb_fft)) as Arc<dyn OfdmFft>);
+        }
+        self.reconfigure_frame(params, carrier_mapper, prs_fft, fft);
+    }
+
+    /// Changes the structure of the OFDM frame like reconfigure() and replaces the FFT implementation.
+    /// The FFT must have nb_fft samples of the new frame structure.
+    pub fn reconfigure_with_fft(&mut self, params: &OfdmParameters, carrier_mapper: &[usize], prs_fft: &[Complex32], fft: Arc<dyn OfdmFft>) {
+        self.reconfigure_frame(params, carrier_mapper, prs_fft, Some(fft));
+        self.is_custom_fft = true;
+    }
+
+    fn reconfigure_frame(&mut self, params: &OfdmParameters, carrier_mapper: &[usize], prs_fft: &[Complex32], fft: Option<Arc<dyn OfdmFft>>) {
+        if let Err(err) = validate_carrier_map(carrier_mapper, params.nb_fft_data_carriers) {
+            panic!("{}", err);
+        }
+        check_reference_data(params, prs_fft);
+        if let Some(fft) = fft {
+            self.fft = fft;
+        }
+
+        let old_default_settings = OfdmDemodulatorSettings::new(&self.params);
+        if self.settings.null_power_total_samples == old_default_settings.null_power_total_samples {
+            self.settings.null_power_total_samples = OfdmDemodulatorSettings::new(params).null_power_total_samples;
+        }
+
+        self.params = *params;
+        self.carrier_mapper_data.clear();
+        self.carrier_mapper_data.extend_from_slice(carrier_mapper);
+
+        self.resize_buffers();
+        self.init(prs_fft);
+        self.reset();
+    }
+
+    fn resize_buffers(&mut self) {
+        let params = &self.params;
+        self.correlation_prs_fft_data.resize(params.nb_fft, Complex32::default());
+        self.correlation_prs_time_data.resize(params.nb_fft, Complex32::default());
+        self.correlation_prs_reference_time_data.resize(params.nb_fft, Complex32::default());
+        self.null_power_dip_buffer.clear_and_resize(params.nb_null_period);
+        self.null_prs_buffer.clear_and_resize(params.nb_null_period + params.nb_symbol_period);
+        self.fine_time_impulse_response_buffer.resize(params.nb_fft, 0.0);
+        self.coarse_frequency_impulse_response_buffer.resize(params.nb_fft, 0.0);
+        self.temp_fft_buffer.resize(params.nb_fft, Complex32::default());
+        self.fixed_point_dqpsk_buffer.resize(params.nb_fft_data_carriers, Complex::default());
+        self.prs_average_buffer.resize(params.nb_fft, Complex32::default());
+        self.channel_response_buffer.resize(params.nb_fft, 0.0);
+        self.data_time_buffer.clear_and_resize(params.nb_input_samples);
+        self.data_fft_buffer.resize(params.nb_symbols*params.nb_fft, Complex32::default());
+        self.data_dqpsk_buffer.resize(params.nb_output_samples, Complex32::default());
+        self.data_out_bits_buffer.resize(params.nb_output_bits, 0);
+        self.nb_frame_dqpsk_symbols = params.nb_dqpsk_symbols;
     }
 
     fn init(&mut self, prs_fft: &[Complex32]) {
@@ -180,7 +867,7 @@ impl OfdmDemodulator {
 
         self.correlation_prs_time_data.copy_from_slice(prs_fft);
         calculate_relative_phase(&mut self.correlation_prs_time_data);
-        self.ifft.process(&mut self.correlation_prs_time_data);
+        self.fft.inverse(&mut self.correlation_prs_time_data);
 
         // Correlation in either time or frequency domain requires the conjugate product in the opposite domain
         // Used in coarse frequency correction
@@ -188,87 +875,883 @@ impl OfdmDemodulator {
             *value = value.conj();
         }
         // Used in fine time correction
-        for i in 0..self.params.nb_fft {
-            self.correlation_prs_fft_data[i] = prs_fft[i].conj();
+        for (x,y) in izip!(prs_fft.iter(), self.correlation_prs_fft_data.iter_mut()) {
+            *y = x.conj();
+        }
+        // Used in fine time correction with direct time domain correlation
+        // NOTE: The inverse FFT isn't normalised so the correlation has the same scale as the frequency domain method
+        self.correlation_prs_reference_time_data.copy_from_slice(prs_fft);
+        self.fft.inverse(&mut self.correlation_prs_reference_time_data);
+        for value in &mut self.correlation_prs_reference_time_data {
+            *value = value.conj();
+        }
+        // The settings can change afterwards so this is also checked before each frame
+        self.prefix_window.clear();
+        self.update_prefix_window();
+    }
+
+    fn update_prefix_window(&mut self) {
+        let nb_cyclic_prefix = self.params.nb_cyclic_prefix;
+        let rolloff = self.settings.prefix_window_rolloff.clamp(0.0, 1.0);
+        let window_length = ((rolloff * nb_cyclic_prefix as f32).round() as usize).min(nb_cyclic_prefix);
+        if self.prefix_window.len() == window_length {
+            return;
+        }
+        // Rises from 0 to 1 so the end of the symbol fades into the cyclic prefix
+        use std::f32::consts::PI;
+        self.prefix_window.clear();
+        self.prefix_window.extend((0..window_length).map(|i| {
+            let x = (i as f32 + 0.5) / (window_length as f32);
+            0.5 - 0.5*(PI*x).cos()
+        }));
+    }
+
+    fn update_fractional_delay(&mut self) {
+        self.fractional_delay_buffer.clear();
+        if !self.settings.fine_time_fractional_delay_is_enabled {
+            return;
         }
+        // The symbols were read from the nearest sample so the FFT window starts early by the fractional part
+        // This delays the spectrum by exp(-j*2*pi*k*delay/N) which is undone for each FFT bin k
+        use std::f32::consts::PI;
+        let nb_fft = self.params.nb_fft;
+        let delay = self.fine_time_offset_subsample - self.fine_time_offset as f32;
+        self.fractional_delay_buffer.extend((0..nb_fft).map(|i| {
+            let k = if i < nb_fft/2 { i as f32 } else { i as f32 - nb_fft as f32 };
+            Complex32::from_polar(1.0, 2.0*PI*k*delay/(nb_fft as f32))
+        }));
     }
 
     /// Registers a callback when the OFDM demodulator has successfully produced the output bits for a signal OFDM frame.
     /// Returns the soft decision bits as an array of signed 8bit value between -127 and +127.
-    pub fn subscribe_bits_out(&mut self, callback: impl FnMut(&[i8]) + Send + Sync + 'static) {
-        self.bits_out_callbacks.push(Box::new(callback));
+    pub fn subscribe_bits_out(&mut self, mut callback: impl FnMut(&[i8]) + Send + Sync + 'static) -> SubscriptionId {
+        self.subscribe_frames_out(move |bits, _| callback(bits))
+    }
+
+    /// Registers a callback like subscribe_bits_out() that also receives information about the OFDM frame.
+    pub fn subscribe_frames_out(&mut self, callback: impl FnMut(&[i8], &FrameInfo) + Send + Sync + 'static) -> SubscriptionId {
+        let id = self.create_subscription_id();
+        self.bits_out_callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Removes a callback registered with subscribe_bits_out() or subscribe_frames_out().
+    /// Ids of callbacks that were already removed are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// let id = demod.subscribe_bits_out(|bits| println!("Frame has {} soft bits", bits.len()));
+    /// demod.unsubscribe_bits_out(id);
+    /// ```
+    pub fn unsubscribe_bits_out(&mut self, id: SubscriptionId) {
+        self.bits_out_callbacks.retain(|(callback_id, _)| *callback_id != id);
+    }
+
+    /// Registers a callback when fine time synchronisation fails to find the PRS.
+    /// Returns the NULL and PRS buffer that was searched so the failure can be analysed offline.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// demod.subscribe_desync_capture(|buf| println!("Captured {} samples around the desync", buf.len()));
+    /// ```
+    pub fn subscribe_desync_capture(&mut self, mut callback: impl FnMut(&[Complex32]) + Send + Sync + 'static) -> SubscriptionId {
+        self.subscribe_desync_events(move |buf, _| callback(buf))
+    }
+
+    /// Registers a callback like subscribe_desync_capture() that also receives the offset and height of the rejected impulse peak.
+    pub fn subscribe_desync_events(&mut self, callback: impl FnMut(&[Complex32], &DesyncInfo) + Send + Sync + 'static) -> SubscriptionId {
+        let id = self.create_subscription_id();
+        self.desync_capture_callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Removes a callback registered with subscribe_desync_capture() or subscribe_desync_events().
+    pub fn unsubscribe_desync_capture(&mut self, id: SubscriptionId) {
+        self.desync_capture_callbacks.retain(|(callback_id, _)| *callback_id != id);
+    }
+
+    /// Registers a callback that receives the time aligned and frequency corrected PRS of each OFDM frame.
+    /// Returns the nb_symbol_period samples of the PRS starting from its cyclic prefix which can be used for transmitter identification.
+    /// This is invoked when the symbols of the frame are processed since the frequency correction is applied to the whole frame at once.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// demod.subscribe_prs_out(|prs| println!("PRS has {} samples", prs.len()));
+    /// ```
+    pub fn subscribe_prs_out(&mut self, callback: impl FnMut(&[Complex32]) + Send + Sync + 'static) -> SubscriptionId {
+        let id = self.create_subscription_id();
+        self.prs_out_callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Removes a callback registered with subscribe_prs_out().
+    pub fn unsubscribe_prs_out(&mut self, id: SubscriptionId) {
+        self.prs_out_callbacks.retain(|(callback_id, _)| *callback_id != id);
+    }
+
+    /// Registers a callback that receives the soft decision bits of each DQPSK symbol.
+    /// The bits are in the same format as subscribe_bits_out() and each call covers 2*nb_fft_data_carriers of them.
+    /// The symbols are passed as soon as they are read if symbol streaming is enabled, otherwise when the frame is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// demod.settings.symbol_streaming_is_enabled = true;
+    /// demod.subscribe_symbols_out(|bits, info| println!("Symbol {} has {} soft bits", info.symbol_index, bits.len()));
+    /// ```
+    pub fn subscribe_symbols_out(&mut self, callback: impl FnMut(&[i8], &SymbolInfo) + Send + Sync + 'static) -> SubscriptionId {
+        let id = self.create_subscription_id();
+        self.symbols_out_callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Removes a callback registered with subscribe_symbols_out().
+    pub fn unsubscribe_symbols_out(&mut self, id: SubscriptionId) {
+        self.symbols_out_callbacks.retain(|(callback_id, _)| *callback_id != id);
+    }
+
+    /// Registers a callback that traces how each input buffer is split between the states of the demodulator.
+    /// It is called once per step of the state machine with the state that ran and the number of samples it consumed.
+    /// States that only process samples that were already read, such as ProcessingSymbols, consume zero samples.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// demod.subscribe_process_trace(|state, total_read| println!("{:?} consumed {} samples", state, total_read));
+    /// ```
+    pub fn subscribe_process_trace(&mut self, callback: impl FnMut(&OfdmDemodulatorState, usize) + Send + Sync + 'static) -> SubscriptionId {
+        let id = self.create_subscription_id();
+        self.process_trace_callbacks.push((id, Box::new(callback)));
+        id
+    }
+
+    /// Removes a callback registered with subscribe_process_trace().
+    pub fn unsubscribe_process_trace(&mut self, id: SubscriptionId) {
+        self.process_trace_callbacks.retain(|(callback_id, _)| *callback_id != id);
+    }
+
+    fn create_subscription_id(&mut self) -> SubscriptionId {
+        let id = SubscriptionId(self.next_subscription_id);
+        self.next_subscription_id += 1;
+        id
     }
 
     /// Consumes an array of complex samples from the receiver and passes it through the demodulator.
+    /// The samples can be split into arbitrarily sized chunks across multiple calls, down to a single sample per call.
+    /// Partial power analysis blocks are carried over between calls so no samples are dropped from the NULL detection.
+    /// Samples of any value including NaN and infinity are accepted without panicking.
+    /// Any estimate they corrupt is reset so the demodulator can lock again once the samples are valid.
+    /// 
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    /// 
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    /// let signal = vec![Complex32::new(1.0, 0.0); 1000];
+    /// for chunk in signal.chunks(7) {
+    ///     demod.process(chunk);
+    /// }
+    /// assert_eq!(demod.state, OfdmDemodulatorState::FindingNullPowerDip);
+    /// ```
     pub fn process(&mut self, buf: &[Complex32]) {
+        // Callers that don't care about errors can rely on the demodulator resetting itself
+        let _ = self.try_process(buf);
+    }
+
+    /// Consumes an array of complex samples like process() but returns an error for unrecoverable conditions.
+    /// The whole array is still processed when an error occurs so no samples are lost if the caller carries on.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// demod.settings.max_desyncs_before_error = Some(10);
+    /// if let Err(err) = demod.try_process(&vec![Complex32::default(); 1000]) {
+    ///     println!("Demodulator failed with {:?}", err);
+    /// }
+    /// ```
+    pub fn try_process(&mut self, buf: &[Complex32]) -> Result<(), DemodError> {
+        self.record_replay(buf);
+        let mut callbacks = std::mem::take(&mut self.bits_out_callbacks);
+        let result = self.process_samples(buf, &mut |bits, info| {
+            for (_, callback) in &mut callbacks {
+                callback(bits, info);
+            }
+        });
+        self.bits_out_callbacks = callbacks;
+        result
+    }
+
+    /// Consumes an array of complex samples like try_process() but passes the output bits of each OFDM frame to on_frame.
+    /// The callbacks registered with subscribe_bits_out() and subscribe_frames_out() are not invoked, which avoids their
+    /// dynamic dispatch for each frame in latency critical paths. The symbols, PRS, process trace and desync capture
+    /// callbacks are still invoked so they should be unsubscribed if that dispatch also needs to be avoided.
+    /// This is allocation free after construction, except for growing the scratch buffers used by DC offset and I/Q imbalance
+    /// correction and power analysis to the largest chunk size seen.
+    /// A frame that the input ends partway through is only passed to on_frame by flush_into().
+    pub fn process_into(&mut self, buf: &[Complex32], on_frame: &mut dyn FnMut(&[i8])) -> Result<(), DemodError> {
+        self.record_replay(buf);
+        self.process_samples(buf, &mut |bits, _| on_frame(bits))
+    }
+
+    fn record_replay(&mut self, buf: &[Complex32]) {
+        if self.replay_buffer.capacity() > 0 {
+            self.replay_buffer.push_slice_overwrite(buf);
+        }
+    }
+
+    /// Sets the number of most recent input samples that are stored for replaying with reprocess_last().
+    /// A length of zero disables storing the input samples.
+    pub fn set_replay_length(&mut self, length: usize) {
+        self.replay_buffer = CircularBucket::<Complex32>::new(length);
+    }
+
+    /// Returns the number of most recent input samples that are stored for replaying.
+    pub fn replay_length(&self) -> usize {
+        self.replay_buffer.capacity()
+    }
+
+    /// Resets the demodulator and runs the most recently stored input samples through it again.
+    /// This gives deterministic results for the same samples so the effect of changing settings can be compared.
+    /// Registered callbacks are not invoked for replayed frames to avoid outputting duplicate bits.
+    pub fn reprocess_last(&mut self) {
+        let mut samples = vec![Complex32::default(); self.replay_buffer.length()];
+        self.replay_buffer.copy_to(&mut samples);
+        // Replayed frames aren't outputted so they shouldn't advance the frame counter
+        // The replayed samples are the most recent ones so the sample counter ends up where it started
+        let frame_counter = self.frame_counter;
+        let total_samples_processed = self.total_samples_processed;
+        let bits_out_callbacks = std::mem::take(&mut self.bits_out_callbacks);
+        let desync_capture_callbacks = std::mem::take(&mut self.desync_capture_callbacks);
+        let prs_out_callbacks = std::mem::take(&mut self.prs_out_callbacks);
+        let symbols_out_callbacks = std::mem::take(&mut self.symbols_out_callbacks);
+        let process_trace_callbacks = std::mem::take(&mut self.process_trace_callbacks);
+        self.reset();
+        self.total_samples_processed = self.total_samples_processed.saturating_sub(samples.len() as u64);
+        let _ = self.process_samples(&samples, &mut |_, _| {});
+        self.bits_out_callbacks = bits_out_callbacks;
+        self.desync_capture_callbacks = desync_capture_callbacks;
+        self.prs_out_callbacks = prs_out_callbacks;
+        self.symbols_out_callbacks = symbols_out_callbacks;
+        self.process_trace_callbacks = process_trace_callbacks;
+        self.frame_counter = frame_counter;
+        self.total_samples_processed = total_samples_processed;
+    }
+
+    /// Processes an OFDM frame that the input ended partway through so its complete symbols aren't lost.
+    /// This should be called once the input has ended, such as at the end of a capture file.
+    /// The output bits passed to the registered callbacks only cover the DQPSK symbols that could be formed and the
+    /// frame information is marked as partial. Nothing is outputted unless the PRS and one data symbol have been read.
+    /// A partial frame can't be continued so afterwards the demodulator searches for the next NULL symbol while keeping
+    /// its frequency offsets. Returns true if a frame was outputted.
+    /// Use flush_into() instead when the frames are read with process_into().
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// // Nothing was read so there is no partial frame to flush
+    /// assert!(!demod.flush());
+    /// ```
+    pub fn flush(&mut self) -> bool {
+        let mut callbacks = std::mem::take(&mut self.bits_out_callbacks);
+        let is_flushed = self.flush_samples(&mut |bits, info| {
+            for (_, callback) in &mut callbacks {
+                callback(bits, info);
+            }
+        });
+        self.bits_out_callbacks = callbacks;
+        is_flushed
+    }
+
+    /// Processes an OFDM frame that the input ended partway through like flush() but passes its output bits to on_frame.
+    /// The bits out and frames out callbacks are skipped like process_into() while the symbols and PRS callbacks are still invoked.
+    pub fn flush_into(&mut self, on_frame: &mut dyn FnMut(&[i8])) -> bool {
+        self.flush_samples(&mut |bits, _| on_frame(bits))
+    }
+
+    fn flush_samples(&mut self, on_frame: &mut dyn FnMut(&[i8], &FrameInfo)) -> bool {
+        // The last sample of a frame can fill the buffer without the frame being processed
+        let total_bits = match self.state {
+            OfdmDemodulatorState::ProcessingSymbols => {
+                self.process_symbols();
+                Some(self.frame_out_bits().len())
+            },
+            OfdmDemodulatorState::ReadingSymbols => {
+                let total_bits = self.process_partial_symbols();
+                self.state = OfdmDemodulatorState::FindingNullPowerDip;
+                self.data_time_buffer.reset();
+                self.null_prs_buffer.reset();
+                total_bits
+            },
+            _ => None,
+        };
+        let Some(total_bits) = total_bits else {
+            return false;
+        };
+        on_frame(&self.data_out_bits_buffer[..total_bits], &self.frame_info);
+        true
+    }
+
+    /// Resets the demodulator to its initial state while keeping its settings and callbacks.
+    pub fn reset(&mut self) {
+        self.reset_from_desync();
+        self.total_frames_read = 0;
+        self.total_frames_desync = 0;
+        self.total_consecutive_desyncs = 0;
+        self.is_null_search_widened = false;
+        self.total_desyncs_before_error = 0;
+        self.frames_since_last_desync = 0;
+        self.samples_to_first_lock = None;
+        self.total_non_finite_samples = 0;
+        self.total_samples_read = 0;
+        self.frequency_offset_history.reset();
+        self.coarse_frequency_saturated = false;
+        self.total_coarse_frequency_edge_frames = 0;
+        self.fine_time_peak_height_db = 0.0;
+        self.null_symbol_noise_power = 0.0;
+        self.dc_offset = Complex32::default();
+        self.iq_imbalance_gain_ratio = 1.0;
+        self.iq_imbalance_phase_error = 0.0;
+        self.agc_input_level = 0.0;
+        self.agc_gain = 1.0;
+        self.is_null_start_found = false;
+        self.is_null_end_found = false;
+        self.nb_null_dip_blocks = 0;
+        self.null_power_dip_buffer.reset();
+        self.signal_power_block_buffer.reset();
+        self.signal_power_block_index = 0;
+        self.data_time_buffer.reset();
+        self.nb_symbols_demodulated = 0;
+    }
+
+    fn process_samples(&mut self, buf: &[Complex32], on_frame: &mut dyn FnMut(&[i8], &FrameInfo)) -> Result<(), DemodError> {
+        let is_dc_correction = self.settings.dc_offset_correction_enabled;
+        let is_iq_correction = self.settings.iq_imbalance_correction_enabled;
+        let is_agc = self.settings.agc_enabled;
+        // Valid inputs are scanned without copying them so sanitisation is cheap when it isn't needed
+        let is_sanitise = self.settings.non_finite_sanitisation_enabled && buf.iter().any(|x| !x.is_finite());
+        if !is_sanitise && !is_dc_correction && !is_iq_correction && !is_agc {
+            return self.run_state_machine(buf, on_frame);
+        }
+
+        let mut samples = std::mem::take(&mut self.corrected_samples_buffer);
+        samples.clear();
+        samples.extend_from_slice(buf);
+        if is_sanitise {
+            for x in samples.iter_mut().filter(|x| !x.is_finite()) {
+                *x = Complex32::default();
+                self.total_non_finite_samples += 1;
+            }
+        }
+        if is_dc_correction {
+            let beta = self.settings.dc_offset_update_beta;
+            for x in samples.iter_mut() {
+                self.dc_offset += beta*(*x - self.dc_offset);
+                *x -= self.dc_offset;
+            }
+        }
+        if is_iq_correction {
+            let [[a, b], [c, d]] = self.iq_imbalance_correction_matrix();
+            for x in samples.iter_mut() {
+                *x = Complex32::new(a*x.re + b*x.im, c*x.re + d*x.im);
+            }
+        }
+        if is_agc {
+            // The input level is measured before the gain so the gain doesn't feed back into its own estimate
+            // It starts from the level of the first samples so it doesn't have to rise slowly from zero
+            if self.agc_input_level <= 0.0 {
+                self.agc_input_level = calculate_l1_average(&samples);
+            }
+            let beta = self.settings.agc_update_beta;
+            let target_level = self.settings.agc_target_level;
+            for x in samples.iter_mut() {
+                self.agc_input_level += beta*(x.l1_norm() - self.agc_input_level);
+                self.agc_gain = (target_level / self.agc_input_level).min(AGC_MAX_GAIN);
+                *x *= self.agc_gain;
+            }
+        }
+        let result = self.run_state_machine(&samples, on_frame);
+        self.corrected_samples_buffer = samples;
+        result
+    }
+
+    /// Returns the current estimate of the DC offset of the incoming samples.
+    /// This is only updated when DC offset correction is enabled.
+    pub fn dc_offset(&self) -> Complex32 {
+        self.dc_offset
+    }
+
+    /// Returns the gain that the AGC is applying to the incoming samples.
+    /// This is only updated when the AGC is enabled.
+    pub fn agc_gain(&self) -> f32 {
+        self.agc_gain
+    }
+
+    /// Returns the estimated ratio of the Q channel gain to the I channel gain.
+    pub fn iq_imbalance_gain_ratio(&self) -> f32 {
+        self.iq_imbalance_gain_ratio
+    }
+
+    /// Returns the estimated phase error of the Q channel relative to quadrature in radians.
+    pub fn iq_imbalance_phase_error(&self) -> f32 {
+        self.iq_imbalance_phase_error
+    }
+
+    /// Returns the 2x2 matrix applied to [I, Q] to undo the estimated I/Q imbalance.
+    /// We model the received Q channel as Q' = g*(Q*cos(phi) + I*sin(phi)) and leave the I channel as the reference.
+    pub fn iq_imbalance_correction_matrix(&self) -> [[f32; 2]; 2] {
+        let gain = self.iq_imbalance_gain_ratio;
+        let (sin, cos) = self.iq_imbalance_phase_error.sin_cos();
+        [
+            [1.0, 0.0],
+            [-sin/cos, 1.0/(gain*cos)],
+        ]
+    }
+
+    fn run_state_machine(&mut self, buf: &[Complex32], on_frame: &mut dyn FnMut(&[i8], &FrameInfo)) -> Result<(), DemodError> {
         self.update_signal_power_average(buf);
 
+        // The rest of the buffer is still processed after an error so callers that ignore it don't lose samples
+        let mut result = Ok(());
         let mut curr_buf = buf;
         while !curr_buf.is_empty() {
-            let total_read = match self.state {
-                OfdmDemodulatorState::FindingNullPowerDip                   =>   self.find_null_power_dip(curr_buf),
+            let state = self.state;
+            let total_read = match state {
+                OfdmDemodulatorState::FindingNullPowerDip                   =>   self.find_null_power_dip(buf, buf.len()-curr_buf.len()),
                 OfdmDemodulatorState::ReadingNullAndPrs                     =>   self.read_null_prs(curr_buf),
                 OfdmDemodulatorState::RunningCoarseFrequencySynchronisation => { self.run_coarse_frequency_synchronisation(); 0 },
-                OfdmDemodulatorState::RunningFineTimeSync                   => { self.run_fine_time_sync(); 0 },
+                OfdmDemodulatorState::RunningFineTimeSync                   => { result = result.and(self.run_fine_time_sync()); 0 },
                 OfdmDemodulatorState::ReadingSymbols                        =>   self.read_symbols(curr_buf),
-                OfdmDemodulatorState::ProcessingSymbols                     => { self.process_symbols(); 0 },
+                OfdmDemodulatorState::ProcessingSymbols                     => { self.process_symbols(); on_frame(self.frame_out_bits(), &self.frame_info); 0 },
             };
+            for (_, callback) in &mut self.process_trace_callbacks {
+                callback(&state, total_read);
+            }
             curr_buf = &curr_buf[total_read..];
+            self.total_samples_read += total_read as u64;
+            self.total_samples_processed += total_read as u64;
+        }
+        self.reset_non_finite_estimates();
+        result
+    }
+
+    /// Non-finite samples poison the running estimates and they never recover by themselves since they are averages.
+    /// Any estimate that has become non-finite is reset so the demodulator can lock again once the samples are valid.
+    fn reset_non_finite_estimates(&mut self) {
+        let is_sync_finite =
+            self.signal_l1_average.is_finite() &&
+            self.coarse_frequency_offset.is_finite() &&
+            self.fine_frequency_offset.is_finite() &&
+            self.fine_frequency_integral_term.is_finite();
+        if !is_sync_finite {
+            self.reset_from_desync();
+            self.is_null_start_found = false;
+            self.is_null_end_found = false;
+            self.nb_null_dip_blocks = 0;
+            self.null_power_dip_buffer.reset();
+            self.signal_power_block_buffer.reset();
+            self.signal_power_block_index = 0;
+        }
+        if !self.dc_offset.is_finite() {
+            self.dc_offset = Complex32::default();
+        }
+        if !self.agc_input_level.is_finite() || !self.agc_gain.is_finite() {
+            self.agc_input_level = 0.0;
+            self.agc_gain = 1.0;
+        }
+        if !self.iq_imbalance_gain_ratio.is_finite() || !self.iq_imbalance_phase_error.is_finite() {
+            self.iq_imbalance_gain_ratio = 1.0;
+            self.iq_imbalance_phase_error = 0.0;
+        }
+    }
+
+    /// Returns true if OFDM frames have been read successfully since the last desync.
+    pub fn is_locked(&self) -> bool {
+        self.frames_since_last_desync > 0
+    }
+
+    /// Returns the decision based bit error estimate of the last OFDM frame from calculate_raw_ber_estimate().
+    pub fn estimate_raw_ber(&self) -> f32 {
+        self.raw_ber_estimate
+    }
+
+    /// Iterates over the DQPSK values of the last frame with one slice of data carriers per symbol.
+    /// This only includes the symbols that were outputted if the settings limit them with max_output_symbols.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    /// let demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    ///
+    /// assert_eq!(demod.dqpsk_symbols().count(), params.nb_dqpsk_symbols);
+    /// assert!(demod.dqpsk_symbols().all(|x| x.len() == params.nb_fft_data_carriers));
+    /// assert_eq!(demod.frame_soft_bits_by_symbol().count(), params.nb_dqpsk_symbols);
+    /// assert!(demod.frame_soft_bits_by_symbol().all(|x| x.len() == 2*params.nb_fft_data_carriers));
+    /// ```
+    pub fn dqpsk_symbols(&self) -> impl Iterator<Item = &[Complex32]> {
+        self.data_dqpsk_buffer.chunks_exact(self.params.nb_fft_data_carriers).take(self.nb_frame_dqpsk_symbols)
+    }
+
+    /// Iterates over the soft decision bits of the last frame with one slice per DQPSK symbol.
+    /// Each slice contains the real components of the data carriers followed by the imaginary components.
+    pub fn frame_soft_bits_by_symbol(&self) -> impl Iterator<Item = &[i8]> {
+        self.frame_out_bits().chunks_exact(self.params.nb_fft_data_carriers*2)
+    }
+
+    fn frame_out_bits(&self) -> &[i8] {
+        &self.data_out_bits_buffer[..self.nb_frame_dqpsk_symbols*self.params.nb_fft_data_carriers*2]
+    }
+
+    /// Returns the FFT of a symbol in the last frame before differential demodulation.
+    /// The first symbol is the PRS which can be used to estimate the channel for external equalisation.
+    /// This is only valid for the current frame after its symbols have been processed and is overwritten by the next frame.
+    ///
+    /// # Panics
+    /// Panics if symbol_index is not less than nb_symbols.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    /// let demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    ///
+    /// assert_eq!(demod.symbol_fft(0).len(), params.nb_fft);
+    /// assert_eq!(demod.symbol_fft(params.nb_symbols-1).len(), params.nb_fft);
+    /// ```
+    pub fn symbol_fft(&self, symbol_index: usize) -> &[Complex32] {
+        assert!(
+            symbol_index < self.params.nb_symbols,
+            "Symbol index {} is out of range for {} symbols", symbol_index, self.params.nb_symbols,
+        );
+        &self.data_fft_buffer[chunk_slice(symbol_index, self.params.nb_fft)]
+    }
+
+    /// Returns how far the demodulator is through reading the current OFDM frame from 0 to 1.
+    /// This is 0 until the PRS of the frame is found and 1 once all of its symbols have been read.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// println!("{} is {:.0}% through the frame", demod.state_name(), 100.0*demod.frame_progress());
+    /// ```
+    pub fn frame_progress(&self) -> f32 {
+        match self.state {
+            OfdmDemodulatorState::ReadingSymbols => (self.data_time_buffer.length() as f32) / (self.data_time_buffer.capacity() as f32),
+            OfdmDemodulatorState::ProcessingSymbols => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns a human readable name of the current state for display.
+    pub fn state_name(&self) -> &'static str {
+        self.state.name()
+    }
+
+    /// Returns the frequency and time corrections together with the net frequency offset and their values in Hz.
+    /// The Hz values use the sample rate of the OFDM parameters so they are normalised values if it wasn't set.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48).with_sample_rate(1e3);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    /// demod.coarse_frequency_offset = 2.0 / (params.nb_fft as f32);
+    /// demod.fine_frequency_offset = 0.25 / (params.nb_fft as f32);
+    /// demod.fine_time_offset = -3;
+    ///
+    /// let sync = demod.sync_state();
+    /// assert_eq!(sync.net_frequency_offset, demod.coarse_frequency_offset + demod.fine_frequency_offset);
+    /// assert_eq!(sync.fine_time_offset, -3);
+    /// // The FFT bins are 1000/64 Hz apart
+    /// assert!((sync.coarse_frequency_offset_hz - 31.25).abs() < 1e-3);
+    /// assert!((sync.net_frequency_offset_hz - 35.15625).abs() < 1e-3);
+    /// ```
+    pub fn sync_state(&self) -> SyncState {
+        let net_frequency_offset = self.coarse_frequency_offset + self.fine_frequency_offset;
+        let params = &self.params;
+        SyncState {
+            net_frequency_offset,
+            coarse_frequency_offset: self.coarse_frequency_offset,
+            fine_frequency_offset: self.fine_frequency_offset,
+            fine_time_offset: self.fine_time_offset,
+            fine_time_offset_subsample: self.fine_time_offset_subsample,
+            net_frequency_offset_hz: params.normalised_to_hz(net_frequency_offset),
+            coarse_frequency_offset_hz: params.normalised_to_hz(self.coarse_frequency_offset),
+            fine_frequency_offset_hz: params.normalised_to_hz(self.fine_frequency_offset),
+        }
+    }
+
+    /// Returns the standard deviation in Hz of the net frequency offset over the most recent OFDM frames.
+    /// A high jitter means the lock is marginal or the oscillator is drifting faster than it is tracked.
+    /// This is zero until at least two frames have been read.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let demod = OfdmDemodulator::new(&params, &carrier_map, &vec![Complex32::new(1.0, 0.0); params.nb_fft]);
+    /// assert_eq!(demod.frequency_offset_jitter_hz(), 0.0);
+    /// ```
+    pub fn frequency_offset_jitter_hz(&self) -> f32 {
+        let history = &self.frequency_offset_history;
+        if history.length() < 2 {
+            return 0.0;
+        }
+        let total_frames = history.length() as f32;
+        let mean = history.iter().sum::<f32>() / total_frames;
+        let variance = history.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / total_frames;
+        self.params.normalised_to_hz(variance.sqrt())
+    }
+
+    /// Copies the scalar statistics of the demodulator.
+    pub fn stats_snapshot(&self) -> DemodStats {
+        DemodStats {
+            state: self.state,
+            is_locked: self.is_locked(),
+            total_frames_read: self.total_frames_read,
+            total_frames_desync: self.total_frames_desync,
+            total_consecutive_desyncs: self.total_consecutive_desyncs,
+            is_null_search_widened: self.is_null_search_widened,
+            samples_to_first_lock: self.samples_to_first_lock,
+            total_non_finite_samples: self.total_non_finite_samples,
+            sync_state: self.sync_state(),
+            coarse_frequency_saturated: self.coarse_frequency_saturated,
+            fine_frequency_integral_term: self.fine_frequency_integral_term,
+            fine_frequency_drift_hz: self.params.normalised_to_hz(self.fine_frequency_integral_term),
+            frequency_offset_jitter_hz: self.frequency_offset_jitter_hz(),
+            fine_time_peak_height_db: self.fine_time_peak_height_db,
+            signal_l1_average: self.signal_l1_average,
+            null_symbol_noise_power: self.null_symbol_noise_power,
+            dc_offset: self.dc_offset,
+            iq_imbalance_gain_ratio: self.iq_imbalance_gain_ratio,
+            iq_imbalance_phase_error: self.iq_imbalance_phase_error,
+            agc_gain: self.agc_gain,
+            raw_ber_estimate: self.raw_ber_estimate,
+            frame_progress: self.frame_progress(),
+        }
+    }
+
+    /// Converts an array of raw IQ samples from the receiver into complex samples and passes it through the demodulator.
+    /// The conversion uses an internal scratch buffer which is reused between calls.
+    pub fn process_raw<S: IqSample>(&mut self, buf: &[S]) {
+        let mut samples = std::mem::take(&mut self.raw_samples_buffer);
+        samples.clear();
+        samples.extend(buf.iter().map(|x| x.to_complex32()));
+        self.process(&samples);
+        self.raw_samples_buffer = samples;
+    }
+
+    /// Reads raw IQ samples from the reader in chunks and passes them through the demodulator until the end of the stream.
+    /// Reads that end partway through a sample are carried over to the next read.
+    /// Returns the total number of complex samples consumed.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_demodulator::OfdmDemodulator;
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use ofdm::iq_sample::SampleFormat;
+    /// use num::complex::Complex32;
+    ///
+    /// // A reader that only returns 3 bytes at a time so reads never align to a sample
+    /// struct SlowReader(std::io::Cursor<Vec<u8>>);
+    /// impl std::io::Read for SlowReader {
+    ///     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    ///         let length = buf.len().min(3);
+    ///         self.0.read(&mut buf[..length])
+    ///     }
+    /// }
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    /// let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    /// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    ///
+    /// let bytes: Vec<u8> = (0..1000i16).flat_map(|i| [i.to_le_bytes(), (-i).to_le_bytes()]).flatten().collect();
+    /// let total_samples = demod.process_reader(&mut SlowReader(std::io::Cursor::new(bytes)), SampleFormat::I16).unwrap();
+    /// assert_eq!(total_samples, 1000);
+    /// ```
+    pub fn process_reader(&mut self, reader: &mut dyn Read, sample_format: SampleFormat) -> std::io::Result<usize> {
+        let bytes_per_sample = sample_format.bytes_per_sample();
+        let mut bytes = vec![0u8; READER_CHUNK_SAMPLES*bytes_per_sample];
+        let mut total_leftover_bytes = 0;
+        let mut total_samples = 0;
+        loop {
+            let total_read = match reader.read(&mut bytes[total_leftover_bytes..]) {
+                Ok(0) => break,
+                Ok(length) => length,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let total_bytes = total_leftover_bytes + total_read;
+            let total_aligned_bytes = total_bytes - total_bytes % bytes_per_sample;
+
+            let mut samples = std::mem::take(&mut self.raw_samples_buffer);
+            samples.clear();
+            sample_format.convert_bytes(&bytes[..total_aligned_bytes], &mut samples);
+            self.process(&samples);
+            total_samples += samples.len();
+            self.raw_samples_buffer = samples;
+
+            bytes.copy_within(total_aligned_bytes..total_bytes, 0);
+            total_leftover_bytes = total_bytes - total_aligned_bytes;
         }
+        Ok(total_samples)
+    }
+
+    /// Returns the average power of the samples in the last NULL symbol.
+    /// Since nothing is transmitted during the NULL symbol this is an estimate of the noise floor.
+    pub fn null_symbol_noise_power(&self) -> f32 {
+        self.null_symbol_noise_power
+    }
+
+    fn retry_from_desync(&mut self) {
+        // An isolated desync could be caused by a burst of noise so we keep our frequency offsets and signal average
+        self.state = OfdmDemodulatorState::FindingNullPowerDip;
+        self.null_prs_buffer.reset();
+        self.is_prs_average_valid = false;
     }
 
     fn reset_from_desync(&mut self) {
         self.state = OfdmDemodulatorState::FindingNullPowerDip;
         self.null_prs_buffer.reset();
+        self.is_prs_average_valid = false;
 
         // NOTE: We also reset fine frequency synchronisation since an incorrect value
         // can reduce performance of fine time synchronisation using the impulse response
         self.signal_l1_average = 0.0;
         self.is_found_coarse_frequency_offset = false;
         self.fine_frequency_offset = 0.0;
+        self.fine_frequency_integral_term = 0.0;
         self.coarse_frequency_offset = 0.0;
         self.fine_time_offset = 0;
+        self.fine_time_offset_subsample = 0.0;
     }
 
-    fn find_null_power_dip(&mut self, buf: &[Complex32]) -> usize {
+    fn find_null_power_dip(&mut self, buf: &[Complex32], start: usize) -> usize {
         // Clause 3.12.2 - Frame synchronisation using power detection
         // we run this if we dont have an initial estimate for the prs index
         // This can occur if:
         //      1. We just started the demodulator and need a quick estimate of OFDM start
         //      2. The PRS impulse response didn't have a sufficiently large peak
 
-        let null_start_threshold = self.signal_l1_average * self.settings.null_power_threshold_start;
+        let threshold_start = if self.is_null_search_widened {
+            self.settings.null_power_widened_threshold_start
+        } else {
+            self.settings.null_power_threshold_start
+        };
+        let null_start_threshold = self.signal_l1_average * threshold_start;
         let null_end_threshold   = self.signal_l1_average * self.settings.null_power_threshold_end;
 
         // We analyse the average power of the signal in blocks
-        let block_size = self.settings.null_power_total_samples;
-        let mut total_read = 0;
-        for block in buf.chunks_exact(block_size) {
-            let l1_average = calculate_l1_average(block);
-            total_read += block_size;
+        // These are the same blocks as the signal average so the L1 average of each block is only calculated once
+        // The partial block at the end of the buffer is analysed when the next buffer completes it
+        let block_size = self.signal_power_block_buffer.capacity();
+        let mut total_read = buf.len() - start;
+        while let Some(block) = self.power_blocks.get(self.power_block_cursor).copied() {
+            self.power_block_cursor += 1;
+            if block.end <= start {
+                continue;
+            }
+            let l1_average = block.l1_average.unwrap_or_else(|| calculate_l1_average(&buf[block.end-block_size..block.end]));
             if self.is_null_start_found {
                 if l1_average > null_end_threshold {
                     self.is_null_end_found = true;
+                    total_read = block.end - start;
                     break;
                 }
+            } else if l1_average < null_start_threshold {
+                self.nb_null_dip_blocks += 1;
+                self.is_null_start_found = self.nb_null_dip_blocks >= self.settings.null_power_min_dip_blocks;
             } else {
-                if l1_average < null_start_threshold {
-                    self.is_null_start_found = true;
-                }
+                self.nb_null_dip_blocks = 0;
             }
         }
 
         // We ignore the remaining buffer until there are enough samples for analysis
+        let consumed_blocks = &buf[start..start+total_read];
+        self.null_power_dip_buffer.push_slice_overwrite(consumed_blocks);
         if !self.is_null_end_found {
-            self.null_power_dip_buffer.consume(buf, true);
-            return buf.len();
+            return total_read;
         }
 
         // Copy null symbol into correlation buffer
         // This is done since our captured null symbol may actually contain parts of the PRS 
         // We do this so we can guarantee the full start of the PRS is attained after fine time sync
-        let consumed_blocks = &buf[..total_read];
-        self.null_power_dip_buffer.consume(consumed_blocks, true);
         self.null_prs_buffer.reset();
         self.null_prs_buffer.consume_from_iterator(
             self.null_power_dip_buffer.iter().copied()
@@ -277,6 +1760,7 @@ impl OfdmDemodulator {
 
         self.is_null_start_found = false;
         self.is_null_end_found = false;
+        self.nb_null_dip_blocks = 0;
         self.null_power_dip_buffer.reset();
         self.state = OfdmDemodulatorState::ReadingNullAndPrs;
 
@@ -286,15 +1770,33 @@ impl OfdmDemodulator {
     fn read_null_prs(&mut self, buf: &[Complex32]) -> usize {
         let total_read = self.null_prs_buffer.consume(buf);
         if self.null_prs_buffer.is_full() {
+            // The NULL symbol has no transmitted signal so it is a measure of the noise floor
+            let null_symbol = &self.null_prs_buffer[..self.params.nb_null_period];
+            self.null_symbol_noise_power = calculate_average_power(null_symbol);
+            self.update_iq_imbalance();
             self.state = OfdmDemodulatorState::RunningCoarseFrequencySynchronisation;
         }
         total_read
     }
 
     fn run_coarse_frequency_synchronisation(&mut self) {
+        // Frozen offsets are applied at the start of every frame so changes to the manual values take effect immediately
+        if self.settings.freeze_fine_frequency {
+            self.fine_frequency_offset = self.settings.manual_fine_frequency_offset;
+        }
+        if self.settings.freeze_coarse_frequency {
+            self.coarse_frequency_offset = self.settings.manual_coarse_frequency_offset;
+            self.coarse_frequency_saturated = false;
+            self.total_coarse_frequency_edge_frames = 0;
+            self.state = OfdmDemodulatorState::RunningFineTimeSync;
+            return;
+        }
+
         // Clause: 3.13.2 Integral frequency offset estimation
         if !self.settings.coarse_frequency_is_enabled {
             self.coarse_frequency_offset = 0.0;
+            self.coarse_frequency_saturated = false;
+            self.total_coarse_frequency_edge_frames = 0;
             self.state = OfdmDemodulatorState::RunningFineTimeSync;
             return;
         }
@@ -305,9 +1807,9 @@ impl OfdmDemodulator {
         // To mitigate effect of phase shifts we instead correlate the complex difference between consecutive FFT bins
         // arg(~z0*z1) = arg(z1)-arg(z0)
         self.temp_fft_buffer.copy_from_slice(prs_fft);
-        self.fft.process(&mut self.temp_fft_buffer);
+        self.fft.forward(&mut self.temp_fft_buffer);
         calculate_relative_phase(&mut self.temp_fft_buffer);
-        self.ifft.process(&mut self.temp_fft_buffer);
+        self.fft.inverse(&mut self.temp_fft_buffer);
 
         // Correlation in frequency domain is multiplication in time domain
         // NOTE: PRS time data is already conjugate in self.init()
@@ -317,17 +1819,25 @@ impl OfdmDemodulator {
         ) {
             *y *= *x;
         }
-        self.fft.process(&mut self.temp_fft_buffer);
+        self.fft.forward(&mut self.temp_fft_buffer);
         calculate_magnitude_spectrum(&self.temp_fft_buffer, &mut self.coarse_frequency_impulse_response_buffer);
 
-        assert!(self.settings.coarse_frequency_max_range < 1.0);
+        // The search range is limited to the FFT bins on either side of DC
         let dc_bin = (self.params.nb_fft/2) as i32;
         let max_carrier_offset_bins = (0.5 * self.settings.coarse_frequency_max_range * self.params.nb_fft as f32).floor() as i32;
+        let max_carrier_offset_bins = max_carrier_offset_bins.min(dc_bin-1);
+        // A penalty of 1 would give a weight of 0 at the edge of the search range which is -inf dB
+        let peak_distance_penalty = self.settings.coarse_frequency_peak_distance_penalty.clamp(0.0, 1.0 - f32::EPSILON);
         let carrier_offset_bin = (-max_carrier_offset_bins..=max_carrier_offset_bins)
             .map(|offset| {
                 let fft_bin = offset+dc_bin;
                 let value: f32 = self.coarse_frequency_impulse_response_buffer[fft_bin as usize];
-                (offset, value)
+                // We weigh the value of the peak with its distance from zero offset to avoid locking onto images
+                // The value is in dB so scaling its power by the weight is the same as adding the weight in dB
+                let norm_distance = (offset.abs() as f32) / (max_carrier_offset_bins.max(1) as f32);
+                let weight = 1.0 - peak_distance_penalty * norm_distance;
+                let weighted_value = value + 10.0*weight.log10();
+                (offset, weighted_value)
             })
             .max_by(|(_,x), (_,y)| {
                 if x > y {
@@ -339,6 +1849,14 @@ impl OfdmDemodulator {
             .map(|(offset,_)| offset)
             .unwrap_or(0);
 
+        // If the peak keeps landing on the edge of the search range then the true offset is probably outside of it
+        let is_edge_bin = max_carrier_offset_bins > 0 && carrier_offset_bin.abs() == max_carrier_offset_bins;
+        self.total_coarse_frequency_edge_frames = match is_edge_bin {
+            true => self.total_coarse_frequency_edge_frames.saturating_add(1),
+            false => 0,
+        };
+        self.coarse_frequency_saturated = self.total_coarse_frequency_edge_frames >= COARSE_FREQUENCY_SATURATION_FRAMES;
+
         let current_coarse_frequency_offset: f32 = (-carrier_offset_bin as f32) / (self.params.nb_fft as f32);
         let delta_coarse_frequency_offset = current_coarse_frequency_offset - self.coarse_frequency_offset;
         
@@ -359,40 +1877,94 @@ impl OfdmDemodulator {
         self.state = OfdmDemodulatorState::RunningFineTimeSync;
     }
 
-    fn run_fine_time_sync(&mut self) {
+    fn run_fine_time_sync(&mut self) -> Result<(), DemodError> {
+        let impulse_scale = self.settings.fine_time_impulse_scale;
         let prs_data = &self.null_prs_buffer[span_slice(self.params.nb_null_period, self.params.nb_fft)];
 
         let total_frequency_offset = self.coarse_frequency_offset + self.fine_frequency_offset;
         self.temp_fft_buffer.copy_from_slice(prs_data);
-        apply_pll(&mut self.temp_fft_buffer, total_frequency_offset);
+        apply_pll(&mut self.temp_fft_buffer, total_frequency_offset, 0);
+        self.update_prs_average();
+
+        // Restrict the search to a window around the expected peak if requested
+        let expected_peak_x = self.params.nb_cyclic_prefix;
+        let (search_start, search_end) = match self.settings.fine_time_search_radius {
+            Some(radius) => {
+                let start = expected_peak_x.saturating_sub(radius).min(self.params.nb_fft-1);
+                let end = (expected_peak_x + radius + 1).clamp(start+1, self.params.nb_fft);
+                (start, end)
+            },
+            None => (0, self.params.nb_fft),
+        };
 
-        // Perform impulse correlation in time domain using multiplication in frequency domain
-        // NOTE: Our PRS FFT reference was conjugated in self.init()
-        self.fft.process(&mut self.temp_fft_buffer);
-        for (x,y) in izip!(
-            self.correlation_prs_fft_data.iter().take(self.params.nb_fft), 
-            self.temp_fft_buffer.iter_mut().take(self.params.nb_fft),
-        ) {
-            *y *= *x;
+        // 10*log10(|x|^2) is equal to 20*log10(|x|) without the square root
+        // Values are clamped to a floor so a zero correlation doesn't produce -inf which turns the average into NaN
+        // The decibel floor is far below any real correlation but high enough that zero bins don't drag the average down
+        let get_impulse_value = |x: Complex32| match impulse_scale {
+            FineTimeImpulseScale::Decibel => (10.0 * x.norm_sqr().log10()).max(FINE_TIME_IMPULSE_FLOOR_DB),
+            FineTimeImpulseScale::Linear => x.norm().max(f32::MIN_POSITIVE),
+            FineTimeImpulseScale::Power => x.norm_sqr().max(f32::MIN_POSITIVE),
+        };
+        match self.settings.fine_time_correlation_method {
+            FineTimeCorrelationMethod::FftMultiply => {
+                // Perform impulse correlation in time domain using multiplication in frequency domain
+                // NOTE: Our PRS FFT reference was conjugated in self.init()
+                self.fft.forward(&mut self.temp_fft_buffer);
+                for (x,y) in izip!(
+                    self.correlation_prs_fft_data.iter().take(self.params.nb_fft), 
+                    self.temp_fft_buffer.iter_mut().take(self.params.nb_fft),
+                ) {
+                    *y *= *x;
+                }
+                self.fft.inverse(&mut self.temp_fft_buffer);
+                for (x,y) in izip!(
+                    self.temp_fft_buffer.iter().take(self.params.nb_fft),
+                    self.fine_time_impulse_response_buffer.iter_mut().take(self.params.nb_fft),
+                ) {
+                    *y = get_impulse_value(*x);
+                }
+            },
+            FineTimeCorrelationMethod::DirectTime => {
+                // NOTE: Our PRS time reference was conjugated in self.init()
+                for lag in search_start..search_end {
+                    let correlation = calculate_circular_correlation(&self.temp_fft_buffer, &self.correlation_prs_reference_time_data, lag);
+                    self.fine_time_impulse_response_buffer[lag] = get_impulse_value(correlation);
+                }
+                // Lags that weren't calculated are filled with the average so they don't change the peak height
+                let window = &self.fine_time_impulse_response_buffer[search_start..search_end];
+                let window_average = window.iter().sum::<f32>() / (window.len() as f32);
+                self.fine_time_impulse_response_buffer[..search_start].fill(window_average);
+                self.fine_time_impulse_response_buffer[search_end..].fill(window_average);
+            },
         }
-        self.ifft.process(&mut self.temp_fft_buffer);
-        for (x,y) in izip!(
-            self.temp_fft_buffer.iter().take(self.params.nb_fft),
-            self.fine_time_impulse_response_buffer.iter_mut().take(self.params.nb_fft),
-        ) {
-            let amplitude = x.norm().log10() * 20.0;
-            *y = amplitude;
+
+        // The impulse response is still calculated so it can be compared against the held offset
+        if self.settings.freeze_fine_time {
+            let min_offset = -(self.params.nb_null_period as isize);
+            let max_offset = self.params.nb_symbol_period as isize;
+            let prs_start_offset = self.settings.manual_fine_time_offset.clamp(min_offset, max_offset);
+            self.total_consecutive_desyncs = 0;
+            self.total_desyncs_before_error = 0;
+            self.start_reading_symbols(prs_start_offset, 0.0);
+            return Ok(());
         }
 
-        let (impulse_peak_index, impulse_peak_value) = self.fine_time_impulse_response_buffer
+        let (impulse_peak_index, weighted_peak_value) = self.fine_time_impulse_response_buffer
             .iter()
             .enumerate()
+            .take(search_end)
+            .skip(search_start)
+            // Bins on the decibel floor had no correlation so they can't be the peak
+            // Otherwise weighing their large negative value would favour them when they are far from the expected location
+            .filter(|(_, peak_value)| impulse_scale != FineTimeImpulseScale::Decibel || **peak_value > FINE_TIME_IMPULSE_FLOOR_DB)
             .map(|(i, peak_value)| {
+                if impulse_scale == FineTimeImpulseScale::Power {
+                    return (i, *peak_value);
+                }
                 // We expect that the correlation peak will at least be somewhere near where we expect it
                 // When we are still locking on, the impulse response may have many peaks due to frequency offsets
                 // This causes spurious desyncs when one of these other peaks are very far away
                 // Thus we weigh the value of the peak with its distance from the expected location
-                let expected_peak_x = self.params.nb_cyclic_prefix;
                 let distance_from_expectation = (expected_peak_x as i32 - i as i32).abs();
                 let norm_distance = (distance_from_expectation as f32) / (self.params.nb_symbol_period as f32);
                 let decay_weight = 1.0 - self.settings.fine_time_impulse_peak_distance_probability;
@@ -407,7 +1979,8 @@ impl OfdmDemodulator {
                     Ordering::Less
                 }
             })
-            .expect("The fine time impulse buffer cannot be empty");
+            .unwrap_or((expected_peak_x.clamp(search_start, search_end-1), FINE_TIME_IMPULSE_FLOOR_DB));
+        let impulse_peak_value = self.fine_time_impulse_response_buffer[impulse_peak_index];
     
         let impulse_sum: f32 = self.fine_time_impulse_response_buffer
             .iter()
@@ -416,31 +1989,113 @@ impl OfdmDemodulator {
 
         // If the main lobe is insufficiently powerful we do not have a valid impulse response
         // This probably means we had a severe desync and should restart 
-        let impulse_peak_height = impulse_peak_value - impulse_average;
-        if impulse_peak_height < self.settings.fine_time_impulse_peak_threshold_db {
-            self.reset_from_desync();
-            self.total_frames_desync += 1;
-            return;
-        }
+        let impulse_peak_height = match impulse_scale {
+            FineTimeImpulseScale::Decibel => weighted_peak_value - impulse_average,
+            FineTimeImpulseScale::Linear => 20.0 * (impulse_peak_value / impulse_average).log10(),
+            FineTimeImpulseScale::Power => 10.0 * (impulse_peak_value / impulse_average).log10(),
+        };
+        self.fine_time_peak_height_db = impulse_peak_height;
 
         // | [NULL] | [Cyclic prefix] | [PRS FFT]
         // The PRS correlation lobe occurs just after the cyclic prefix
         // We actually want the index at the start of the cyclic prefix, so we adjust offset for that
         let prs_start_offset = impulse_peak_index as isize - self.params.nb_cyclic_prefix as isize;
+        let desync_info = DesyncInfo {
+            prs_start_offset,
+            peak_height_db: impulse_peak_height,
+        };
+
+        // Non-finite samples can make the peak height NaN which should also count as a desync
+        let is_peak_found = impulse_peak_height >= self.settings.fine_time_impulse_peak_threshold_db;
+        if !is_peak_found {
+            debug!(
+                "Fine time impulse peak of {:.2}dB is below the threshold of {:.2}dB at sample {}",
+                impulse_peak_height, self.settings.fine_time_impulse_peak_threshold_db, self.total_samples_read,
+            );
+            return self.handle_fine_time_desync(&desync_info, false);
+        }
+        if let Some(max_offset) = self.settings.max_fine_time_offset {
+            if prs_start_offset.unsigned_abs() > max_offset {
+                debug!(
+                    "Fine time offset of {} is larger than the maximum of {} at sample {}",
+                    prs_start_offset, max_offset, self.total_samples_read,
+                );
+                return self.handle_fine_time_desync(&desync_info, true);
+            }
+        }
+        self.total_consecutive_desyncs = 0;
+        self.total_desyncs_before_error = 0;
+
+        // The impulse response is circular so the neighbours of the peak wrap around
+        let nb_fft = self.params.nb_fft;
+        let prs_start_fraction = calculate_parabolic_peak_offset(
+            self.fine_time_impulse_response_buffer[(impulse_peak_index + nb_fft - 1) % nb_fft],
+            self.fine_time_impulse_response_buffer[impulse_peak_index],
+            self.fine_time_impulse_response_buffer[(impulse_peak_index + 1) % nb_fft],
+        );
+        self.start_reading_symbols(prs_start_offset, prs_start_fraction);
+        Ok(())
+    }
+
+    /// Restarts synchronisation after fine time synchronisation failed.
+    /// The frequency offsets and signal average are kept for a retry unless a full resync is forced or there were too many desyncs in a row.
+    fn handle_fine_time_desync(&mut self, info: &DesyncInfo, is_full_resync: bool) -> Result<(), DemodError> {
+        for (_, callback) in &mut self.desync_capture_callbacks {
+            callback(self.null_prs_buffer.raw_slice(), info);
+        }
+        self.total_frames_desync += 1;
+        self.total_consecutive_desyncs += 1;
+        self.total_desyncs_before_error += 1;
+        self.frames_since_last_desync = 0;
+        if is_full_resync || self.total_consecutive_desyncs >= self.settings.max_consecutive_desyncs {
+            debug!("Restarting synchronisation after {} consecutive desyncs", self.total_consecutive_desyncs);
+            self.total_consecutive_desyncs = 0;
+            self.is_null_search_widened = true;
+            self.reset_from_desync();
+        } else {
+            self.retry_from_desync();
+        }
+        match self.settings.max_desyncs_before_error {
+            Some(max_desyncs) if self.total_desyncs_before_error >= max_desyncs => {
+                let total_desyncs = self.total_desyncs_before_error;
+                self.total_desyncs_before_error = 0;
+                Err(DemodError::TooManyDesyncs { total_desyncs })
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn start_reading_symbols(&mut self, prs_start_offset: isize, prs_start_fraction: f32) {
+        // The NULL search only needs to be widened until the demodulator locks again
+        self.is_null_search_widened = false;
         let prs_start_index = isize::max(self.params.nb_null_period as isize + prs_start_offset, 0) as usize;
         let prs_length = isize::max(self.params.nb_symbol_period as isize - prs_start_offset, 0) as usize;
         let prs_partial_buffer = &self.null_prs_buffer[span_slice(prs_start_index, prs_length)];
+        // The NULL and PRS buffer ends at the most recently read sample
+        let null_prs_start_sample = self.total_samples_processed.saturating_sub(self.null_prs_buffer.length() as u64);
+        self.prs_start_sample = null_prs_start_sample + prs_start_index as u64;
         
         self.data_time_buffer.reset();
         self.data_time_buffer.consume(prs_partial_buffer);
+        self.nb_symbols_demodulated = 0;
+        // The settings can change between frames but not during one
+        self.nb_frame_dqpsk_symbols = match self.settings.max_output_symbols {
+            Some(nb_symbols) => nb_symbols.min(self.params.nb_dqpsk_symbols),
+            None => self.params.nb_dqpsk_symbols,
+        };
 
         self.null_prs_buffer.reset();
         self.fine_time_offset = prs_start_offset;
+        self.fine_time_offset_subsample = prs_start_offset as f32 + prs_start_fraction;
         self.state = OfdmDemodulatorState::ReadingSymbols;
     }
 
     fn read_symbols(&mut self, buf: &[Complex32]) -> usize {
         let total_read = self.data_time_buffer.consume(buf);
+        if self.settings.symbol_streaming_is_enabled {
+            let nb_symbols = (self.data_time_buffer.length() / self.params.nb_symbol_period).min(self.params.nb_symbols);
+            self.demodulate_symbols(nb_symbols);
+        }
         if self.data_time_buffer.is_full() {
             self.state = OfdmDemodulatorState::ProcessingSymbols;
         }
@@ -449,106 +2104,345 @@ impl OfdmDemodulator {
 
     fn process_symbols(&mut self) {
         // Copy the null symbol so we can use it in find_null_prs
+        // The data time buffer starts from the PRS that fine time sync found instead of where it was expected
+        // Thus the NULL symbol of the next frame is always a whole frame after it regardless of the fine time offset
         let null_symbol_offset = self.params.nb_symbols*self.params.nb_symbol_period;
         let null_symbol = &self.data_time_buffer[span_slice(null_symbol_offset, self.params.nb_null_period)];
         self.null_prs_buffer.reset();
         self.null_prs_buffer.consume(null_symbol);
 
+        // The fine frequency offset is estimated from the symbols after they are corrected
         let net_frequency_offset = self.fine_frequency_offset + self.coarse_frequency_offset;
-        apply_pll(self.data_time_buffer.iter_mut(), net_frequency_offset);
+        self.demodulate_symbols(self.params.nb_symbols);
+        let dqpsk = &self.data_dqpsk_buffer[..self.nb_frame_dqpsk_symbols*self.params.nb_fft_data_carriers];
+        self.raw_ber_estimate = calculate_raw_ber_estimate(dqpsk, self.params.nb_fft_data_carriers);
 
         // Clause 3.13: Frequency offset estimation and correction
         // Clause 3.13.1 - Fraction frequency offset estimation
-        let total_phase_error: f32 = (0..self.params.nb_symbols)
-            .map(|i| &self.data_time_buffer[chunk_slice(i, self.params.nb_symbol_period)])
-            .map(|sym| calculate_cyclic_phase_error(sym, self.params.nb_cyclic_prefix))
-            .sum();
-        let average_phase_error = total_phase_error / (self.params.nb_symbols as f32);
-
-        // Clause 3.13.1 - Fraction frequency offset estimation
-        {
+        // The held fine frequency offset was already applied at the start of the frame
+        if !self.settings.freeze_fine_frequency {
             use std::f32::consts::PI;
+            let total_phase_error: f32 = (0..self.params.nb_symbols)
+                .map(|i| &self.data_time_buffer[chunk_slice(i, self.params.nb_symbol_period)])
+                .map(|sym| calculate_cyclic_phase_error(sym, self.params.nb_cyclic_prefix, self.settings.fine_frequency_cyclic_prefix_fraction))
+                .sum();
+            let average_phase_error = total_phase_error / (self.params.nb_symbols as f32);
             let fft_bin_spacing = 1.0 / (self.params.nb_fft as f32);
             let fine_frequency_error = fft_bin_spacing/2.0 * average_phase_error/PI;
-            let beta = self.settings.fine_frequency_update_beta;
-            let delta = -beta*fine_frequency_error;
+            let delta = if self.settings.fine_frequency_pi_is_enabled {
+                let kp = self.settings.fine_frequency_pi_proportional_gain;
+                let ki = self.settings.fine_frequency_pi_integral_gain;
+                self.fine_frequency_integral_term += ki*fine_frequency_error;
+                -(kp*fine_frequency_error + self.fine_frequency_integral_term)
+            } else {
+                let beta = self.settings.fine_frequency_update_beta;
+                -beta*fine_frequency_error
+            };
             self.update_fine_frequency_offset(delta);
         }
 
+        self.frame_info = FrameInfo {
+            frame_counter: self.frame_counter,
+            net_frequency_offset,
+            prs_start_sample: self.prs_start_sample,
+            is_partial: false,
+        };
+        self.frequency_offset_history.push_slice_overwrite(&[net_frequency_offset]);
+        debug!(
+            "Read frame {} with fine time offset {} and frequency offset {:.5}",
+            self.frame_counter, self.fine_time_offset, net_frequency_offset,
+        );
+        self.frame_counter += 1;
+        self.total_frames_read += 1;
+        self.frames_since_last_desync += 1;
+        if self.samples_to_first_lock.is_none() {
+            self.samples_to_first_lock = Some(self.total_samples_read);
+        }
+        self.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    }
+
+    /// Demodulates the first nb_symbols symbols in the data time buffer after correcting their frequency offset.
+    /// Symbols that were already demodulated while streaming are skipped so each symbol is only corrected once.
+    /// Symbols after the DQPSK symbols that are outputted are only corrected since fine frequency estimation still uses them.
+    fn demodulate_symbols(&mut self, nb_symbols: usize) {
+        let start_symbol = self.nb_symbols_demodulated;
+        if nb_symbols <= start_symbol {
+            return;
+        }
+        // The frequency offsets are only updated between frames so every symbol is corrected with the same offset
+        let net_frequency_offset = self.fine_frequency_offset + self.coarse_frequency_offset;
+        let start_sample = start_symbol*self.params.nb_symbol_period;
+        let end_sample = nb_symbols*self.params.nb_symbol_period;
+        apply_pll(&mut self.data_time_buffer[start_sample..end_sample], net_frequency_offset, start_sample);
+        // The PRS is the symbol before the first DQPSK symbol
+        for i in start_symbol..nb_symbols.min(self.nb_frame_dqpsk_symbols+1) {
+            self.demodulate_symbol(i);
+        }
+        self.nb_symbols_demodulated = nb_symbols;
+    }
+
+    /// Demodulates a frequency corrected symbol in the data time buffer against the symbol before it.
+    /// The first symbol is the PRS which is used to estimate the channel instead.
+    fn demodulate_symbol(&mut self, symbol_index: usize) {
+        if symbol_index == 0 {
+            let prs = &self.data_time_buffer[chunk_slice(0, self.params.nb_symbol_period)];
+            for (_, callback) in &mut self.prs_out_callbacks {
+                callback(prs);
+            }
+            // The settings can change between frames but not during one
+            self.update_prefix_window();
+            self.update_fractional_delay();
+        }
+
         // Clause 3.14.2 - FFT
-        (0..self.params.nb_symbols)
-            .for_each(|i| {
-                let symbol_in = &self.data_time_buffer[chunk_slice(i, self.params.nb_symbol_period)];
-                let fft_in = &symbol_in[self.params.nb_cyclic_prefix..];
-                let fft_out = &mut self.data_fft_buffer[chunk_slice(i, self.params.nb_fft)];
-                fft_out.copy_from_slice(fft_in);
-                self.fft.process(fft_out);
-            });
+        let window_length = self.prefix_window.len();
+        let symbol_in = &self.data_time_buffer[chunk_slice(symbol_index, self.params.nb_symbol_period)];
+        let fft_in = &symbol_in[self.params.nb_cyclic_prefix..];
+        let fft_out = &mut self.data_fft_buffer[chunk_slice(symbol_index, self.params.nb_fft)];
+        fft_out.copy_from_slice(fft_in);
+        // The samples just before the FFT window are a copy of its end which we crossfade into
+        let prefix = &symbol_in[self.params.nb_cyclic_prefix-window_length..self.params.nb_cyclic_prefix];
+        let tail = &mut fft_out[self.params.nb_fft-window_length..];
+        for (y, x, w) in izip!(tail.iter_mut(), prefix.iter(), self.prefix_window.iter()) {
+            *y += (x - *y) * w;
+        }
+        self.fft.forward(fft_out);
+        for (x, y) in fft_out.iter_mut().zip(self.fractional_delay_buffer.iter()) {
+            *x *= y;
+        }
+
+        if symbol_index == 0 {
+            // The first symbol is the PRS so we can estimate the channel by comparing it against the reference
+            // NOTE: Our PRS FFT reference was conjugated in self.init()
+            let prs_fft = &self.data_fft_buffer[chunk_slice(0, self.params.nb_fft)];
+            for (x, y, h) in izip!(
+                prs_fft.iter(),
+                self.correlation_prs_fft_data.iter(),
+                self.channel_response_buffer.iter_mut(),
+            ) {
+                *h = (x * y).norm();
+            }
+            return;
+        }
 
         // Clause 3.15 - Differential demodulator
-        (0..self.params.nb_dqpsk_symbols)
-            .for_each(|i| {
-                let x0 = &self.data_fft_buffer[chunk_slice(i  , self.params.nb_fft)];
-                let x1 = &self.data_fft_buffer[chunk_slice(i+1, self.params.nb_fft)];
-                let y = &mut self.data_dqpsk_buffer[chunk_slice(i, self.params.nb_fft_data_carriers)];
-                calculate_dqpsk(&self.params, x0, x1, y);
-            });
+        let dqpsk_index = symbol_index-1;
+        let x0 = &self.data_fft_buffer[chunk_slice(dqpsk_index, self.params.nb_fft)];
+        let x1 = &self.data_fft_buffer[chunk_slice(symbol_index, self.params.nb_fft)];
+        let x = &mut self.data_dqpsk_buffer[chunk_slice(dqpsk_index, self.params.nb_fft_data_carriers)];
+        calculate_dqpsk(&self.params, x0, x1, x);
 
         // Clause 3.16 - Data demapper
-        (0..self.params.nb_dqpsk_symbols)
-            .for_each(|i| {
-                let x = &self.data_dqpsk_buffer[chunk_slice(i, self.params.nb_fft_data_carriers)];
-                let y = &mut self.data_out_bits_buffer[chunk_slice(i, self.params.nb_fft_data_carriers*2)];
-                calculate_soft_bits(&self.carrier_mapper_data, x, y);
-            });
+        let x_deinterleaved = &mut self.temp_fft_buffer[..self.params.nb_fft_data_carriers];
+        let y = &mut self.data_out_bits_buffer[chunk_slice(dqpsk_index, self.params.nb_fft_data_carriers*2)];
+        if self.settings.frequency_deinterleaving_is_enabled {
+            frequency_deinterleave(x, x_deinterleaved, &self.carrier_mapper_data);
+        } else {
+            x_deinterleaved.copy_from_slice(x);
+        }
+        if self.settings.soft_bits_fixed_point_is_enabled {
+            for (y, x) in self.fixed_point_dqpsk_buffer.iter_mut().zip(x_deinterleaved.iter()) {
+                *y = convert_dqpsk_to_fixed_point(*x);
+            }
+            calculate_soft_bits_fixed_point(&self.fixed_point_dqpsk_buffer, y);
+        } else {
+            calculate_soft_bits(x_deinterleaved, y);
+        }
 
-        for callback in &mut self.bits_out_callbacks {
-            callback(&self.data_out_bits_buffer);
+        let info = SymbolInfo {
+            frame_counter: self.frame_counter,
+            symbol_index: dqpsk_index,
+        };
+        for (_, callback) in &mut self.symbols_out_callbacks {
+            callback(y, &info);
         }
+    }
 
-        self.total_frames_read += 1;
-        self.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    fn process_partial_symbols(&mut self) -> Option<usize> {
+        // We need the PRS and at least one data symbol to form a DQPSK symbol
+        let nb_symbols = (self.data_time_buffer.length() / self.params.nb_symbol_period).min(self.params.nb_symbols);
+        if nb_symbols < 2 {
+            return None;
+        }
+
+        // There is no following frame for the fine frequency estimate to be used on so we leave it unchanged
+        let net_frequency_offset = self.fine_frequency_offset + self.coarse_frequency_offset;
+        self.demodulate_symbols(nb_symbols);
+        let nb_dqpsk_symbols = (nb_symbols-1).min(self.nb_frame_dqpsk_symbols);
+        let dqpsk = &self.data_dqpsk_buffer[..nb_dqpsk_symbols*self.params.nb_fft_data_carriers];
+        self.raw_ber_estimate = calculate_raw_ber_estimate(dqpsk, self.params.nb_fft_data_carriers);
+
+        self.frame_info = FrameInfo {
+            frame_counter: self.frame_counter,
+            net_frequency_offset,
+            prs_start_sample: self.prs_start_sample,
+            is_partial: true,
+        };
+        self.frame_counter += 1;
+        Some(nb_dqpsk_symbols*self.params.nb_fft_data_carriers*2)
     }
 
     fn update_signal_power_average(&mut self, buf: &[Complex32]) {
-        let block_size = self.settings.null_power_total_samples;
-        let stride = self.settings.null_power_decimation_factor;
+        let block_size = self.settings.null_power_total_samples.max(1);
+        let stride = self.settings.null_power_decimation_factor.max(1);
+        if self.signal_power_block_buffer.capacity() != block_size {
+            self.signal_power_block_buffer.clear_and_resize(block_size);
+        }
 
-        let (total_blocks, power_sum) = buf
-            .chunks_exact(block_size)
-            .enumerate()
-            .filter(|(index,_)| index % stride == 0)
-            .map(|(_,x)| calculate_l1_average(x))
-            .fold((0usize, 0.0), |(total, sum),y| {
-                (total + 1, sum + y)
-            });
+        // The blocks that end in this buffer are kept for the NULL power dip detection
+        self.power_blocks.clear();
+        self.power_block_cursor = 0;
+
+        // A block can straddle multiple calls so we carry over the partial block until it is filled
+        let mut total_blocks = 0usize;
+        let mut power_sum = 0.0;
+        let mut offset = 0;
+        if self.signal_power_block_buffer.length() > 0 {
+            offset = self.signal_power_block_buffer.consume(buf);
+            if !self.signal_power_block_buffer.is_full() {
+                return;
+            }
+            // The samples from the previous buffer are discarded after this so its average is always calculated
+            let l1_average = calculate_l1_average(self.signal_power_block_buffer.iter());
+            if self.signal_power_block_index.is_multiple_of(stride) {
+                total_blocks += 1;
+                power_sum += l1_average;
+            }
+            self.power_blocks.push(PowerBlock { end: offset, l1_average: Some(l1_average) });
+            self.signal_power_block_index = (self.signal_power_block_index + 1) % stride;
+            self.signal_power_block_buffer.reset();
+        }
+
+        let mut blocks = buf[offset..].chunks_exact(block_size);
+        for block in &mut blocks {
+            offset += block_size;
+            let l1_average = self.signal_power_block_index.is_multiple_of(stride).then(|| calculate_l1_average(block));
+            if let Some(l1_average) = l1_average {
+                total_blocks += 1;
+                power_sum += l1_average;
+            }
+            self.power_blocks.push(PowerBlock { end: offset, l1_average });
+            self.signal_power_block_index = (self.signal_power_block_index + 1) % stride;
+        }
+        self.signal_power_block_buffer.consume(blocks.remainder());
 
         if total_blocks == 0 {
             return;
         }
 
         let l1_average = power_sum / (total_blocks as f32);
+        // Blocks with corrupted samples would poison the running average
+        if !l1_average.is_finite() {
+            return;
+        }
         let beta = self.settings.null_power_update_beta;
         self.signal_l1_average = beta*l1_average + (1.0-beta)*self.signal_l1_average;
     }
 
-    fn update_fine_frequency_offset(&mut self, delta: f32) {
-        let fft_bin_spacing = 1.0/(self.params.nb_fft as f32) * 0.5; 
-        let fft_bin_margin = 1.01;
-        let fft_bin_wrap = fft_bin_spacing * fft_bin_margin;
+    fn update_prs_average(&mut self) {
+        let beta = self.settings.prs_averaging_beta;
+        if beta <= 0.0 {
+            return;
+        }
 
+        let prs = &mut self.temp_fft_buffer;
+        if !self.is_prs_average_valid {
+            self.prs_average_buffer.copy_from_slice(prs);
+            self.is_prs_average_valid = true;
+            return;
+        }
+
+        // The carrier phase of the PRS changes between frames so we rotate it onto the average before accumulating
+        let correlation: Complex32 = izip!(self.prs_average_buffer.iter(), prs.iter())
+            .map(|(x, y)| x * y.conj())
+            .sum();
+        // A non-finite average would never recover so we start again from the new PRS
+        if !correlation.is_finite() {
+            self.prs_average_buffer.copy_from_slice(prs);
+            return;
+        }
+        let rotation = Complex32::from_polar(1.0, correlation.arg());
+        for (x, y) in izip!(self.prs_average_buffer.iter_mut(), prs.iter_mut()) {
+            *x = (1.0-beta)*(*x) + beta*(*y)*rotation;
+            *y = *x;
+        }
+    }
+
+    fn update_iq_imbalance(&mut self) {
+        // The PRS is a full power OFDM symbol whose ideal I and Q channels have equal power and are uncorrelated
+        // Any difference in power or correlation between the channels is caused by the receiver's I/Q imbalance
+        // NOTE: The PRS has already been corrected if enabled so we undo it to measure the raw imbalance
+        let prs = &self.null_prs_buffer[span_slice(self.params.nb_null_period, self.params.nb_symbol_period)];
+        let is_corrected = self.settings.iq_imbalance_correction_enabled;
+        let gain = self.iq_imbalance_gain_ratio;
+        let (sin, cos) = self.iq_imbalance_phase_error.sin_cos();
+        let (ii, qq, iq) = prs
+            .iter()
+            .map(|x| match is_corrected {
+                true => Complex32::new(x.re, gain*(x.im*cos + x.re*sin)),
+                false => *x,
+            })
+            .fold((0.0, 0.0, 0.0), |(ii, qq, iq), x| {
+                (ii + x.re*x.re, qq + x.im*x.im, iq + x.re*x.im)
+            });
+
+        if ii <= 0.0 || qq <= 0.0 {
+            return;
+        }
+
+        let gain_ratio = (qq/ii).sqrt();
+        let phase_error = (iq/(ii*qq).sqrt()).clamp(-1.0, 1.0).asin();
+        let beta = self.settings.iq_imbalance_update_beta;
+        self.iq_imbalance_gain_ratio += beta*(gain_ratio - self.iq_imbalance_gain_ratio);
+        self.iq_imbalance_phase_error += beta*(phase_error - self.iq_imbalance_phase_error);
+    }
+
+    fn update_fine_frequency_offset(&mut self, delta: f32) {
+        // The coarse frequency correction also moves the fine frequency offset so it must not disturb a held value
+        if self.settings.freeze_fine_frequency {
+            return;
+        }
         // TODO: If we are planning on multithreading this then we need to lock the fine frequency offset
         self.fine_frequency_offset += delta;
-        self.fine_frequency_offset %= fft_bin_wrap;
+        if let Some(fft_bin_margin) = self.settings.fine_frequency_wrap_margin {
+            let fft_bin_spacing = 1.0/(self.params.nb_fft as f32) * 0.5; 
+            let fft_bin_wrap = fft_bin_spacing * fft_bin_margin;
+            self.fine_frequency_offset %= fft_bin_wrap;
+        }
+    }
+}
+
+fn check_reference_data(params: &OfdmParameters, prs_fft: &[Complex32]) {
+    assert!(params.nb_fft == prs_fft.len(), "Mismatching FFT size between params {} and FFT buffer {}", params.nb_fft, prs_fft.len());
+}
+
+/// Calculates one lag of the circular cross correlation sum(x[(n+lag) % N] * y[n]).
+/// The second input should already be conjugated to correlate against it.
+fn calculate_circular_correlation(x: &[Complex32], y: &[Complex32], lag: usize) -> Complex32 {
+    let (x_head, x_tail) = x.split_at(lag);
+    let (y_head, y_tail) = y.split_at(x.len()-lag);
+    let head: Complex32 = izip!(x_tail.iter(), y_head.iter()).map(|(a, b)| a * b).sum();
+    let tail: Complex32 = izip!(x_head.iter(), y_tail.iter()).map(|(a, b)| a * b).sum();
+    head + tail
+}
+
+/// Finds the offset of the vertex of the parabola through a peak and its neighbours relative to the peak.
+/// Returns 0 if the values don't form a peak so that flat or non-finite impulse responses don't move the offset.
+fn calculate_parabolic_peak_offset(y_prev: f32, y_peak: f32, y_next: f32) -> f32 {
+    let curvature = y_prev - 2.0*y_peak + y_next;
+    let offset = 0.5 * (y_prev - y_next) / curvature;
+    if curvature >= 0.0 || !offset.is_finite() {
+        return 0.0;
     }
+    offset.clamp(-0.5, 0.5)
 }
 
-fn calculate_l1_average(block: &[Complex32]) -> f32 {
-    let l1_sum: f32 = block
+fn calculate_average_power(block: &[Complex32]) -> f32 {
+    let power_sum: f32 = block
         .iter()
-        .map(|x| x.l1_norm())
+        .map(|x| x.norm_sqr())
         .sum();
-    l1_sum / (block.len() as f32)
+    power_sum / (block.len() as f32)
 }
 
 fn calculate_relative_phase(x: &mut[Complex32]) {
@@ -564,15 +2458,16 @@ fn calculate_magnitude_spectrum(x: &[Complex32], y: &mut[f32]) {
     assert!(x.len() == y.len());
     let n = x.len();
     let m = n/2;
-    for i in 0..n {
+    for (i, y) in y.iter_mut().enumerate() {
         let j = (i+m) % n;
-        let mag: f32 = 20.0 * x[j].norm().log10();
-        y[i] = mag;
+        let mag: f32 = 10.0 * x[j].norm_sqr().log10();
+        *y = mag;
     }
 }
 
 // SOURCE: https://mooooo.ooo/chebyshev-sine-approximation 
 //         Chebyshev polynomial that approximates f(x) = sin(2*pi*x) accurately within [-0.75,+0.75]
+#[allow(clippy::excessive_precision)]
 fn fast_sine(x: f32) -> f32 {
     const A0: f32 = -25.1327419281005859375;
     const A1: f32 =  64.83582305908203125;
@@ -596,9 +2491,11 @@ fn fast_sine(x: f32) -> f32 {
     b0 * (z-0.25) * x
 }
 
-fn apply_pll(x: &mut [Complex32], freq_offset_normalised: f32) {
+/// Rotates the samples by the frequency offset where sample_offset is the index of the first sample.
+/// This keeps the phase continuous when consecutive parts of a buffer are corrected separately.
+fn apply_pll(x: &mut [Complex32], freq_offset_normalised: f32, sample_offset: usize) {
     x.iter_mut().enumerate().for_each(|(i, x)| {
-        let dt = (i as f32)*freq_offset_normalised;
+        let dt = ((sample_offset + i) as f32)*freq_offset_normalised;
         // get absolute integer offset from [-0.5,+0.5]
         // let dt = dt - dt.round();
         // NOTE: Faster version of f32::round()
@@ -613,21 +2510,72 @@ fn apply_pll(x: &mut [Complex32], freq_offset_normalised: f32) {
     });
 }
 
-fn calculate_cyclic_phase_error(x: &[Complex32], prefix_length: usize) -> f32 {
+/// Calculates the phase error between the cyclic prefix and the end of an OFDM symbol.
+/// Only the central fraction of the cyclic prefix is used which skips a guard region at both edges.
+/// 
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::calculate_cyclic_phase_error;
+/// use num::complex::Complex32;
+/// 
+/// let (nb_fft, nb_prefix) = (64, 16);
+/// let phase_error = 0.3;
+/// let symbol: Vec<Complex32> = (0..nb_fft).map(|i| Complex32::from_polar(1.0, (i*i*7 + i*3) as f32 * 0.1)).collect();
+/// let mut x: Vec<Complex32> = symbol[nb_fft-nb_prefix..].iter().chain(symbol.iter()).cloned().collect();
+/// for (i, v) in x.iter_mut().enumerate() {
+///     *v *= Complex32::from_polar(1.0, phase_error * (i as f32) / (nb_fft as f32));
+/// }
+/// assert!((calculate_cyclic_phase_error(&x, nb_prefix, 1.0) - phase_error).abs() < 1e-4);
+/// 
+/// // A trailing echo of the previous symbol contaminates the start of the cyclic prefix
+/// for (i, v) in x.iter_mut().take(4).enumerate() {
+///     *v += Complex32::from_polar(2.0, 1.3*(i as f32) + 0.5);
+/// }
+/// let full_error = (calculate_cyclic_phase_error(&x, nb_prefix, 1.0) - phase_error).abs();
+/// let central_error = (calculate_cyclic_phase_error(&x, nb_prefix, 0.5) - phase_error).abs();
+/// assert!(central_error < 1e-4);
+/// assert!(central_error < full_error);
+/// ```
+pub fn calculate_cyclic_phase_error(x: &[Complex32], prefix_length: usize, prefix_fraction: f32) -> f32 {
     let length = x.len();
     assert!(length >= prefix_length);
 
-    let prefix = &x[0..prefix_length];
-    let suffix = &x[span_slice(length-prefix_length, prefix_length)];
+    let prefix_fraction = prefix_fraction.clamp(0.0, 1.0);
+    let guard_length = ((prefix_length as f32) * (1.0-prefix_fraction) / 2.0).round() as usize;
+    let guard_length = guard_length.min(prefix_length.saturating_sub(1) / 2);
+    let total_correlated = prefix_length - 2*guard_length;
 
-    let conjugate_sum: Complex32 = (0..prefix_length)
+    let prefix = &x[span_slice(guard_length, total_correlated)];
+    let suffix = &x[span_slice(length-prefix_length+guard_length, total_correlated)];
+
+    let conjugate_sum: Complex32 = (0..total_correlated)
         .map(|i| suffix[i] * prefix[i].conj())
         .sum();
 
     conjugate_sum.im.atan2(conjugate_sum.re)
 }
 
-fn calculate_dqpsk(params: &OfdmParameters, x0: &[Complex32], x1: &[Complex32], y: &mut[Complex32]) {
+/// Calculates the phase difference between the data carriers of two consecutive OFDM symbols.
+/// The output is ordered from the lowest to highest frequency carrier with the DC bin excluded.
+/// This is the order that the carrier mapper used in the data demapper indexes into.
+/// 
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::calculate_dqpsk;
+/// use ofdm::ofdm_parameters::OfdmParameters;
+/// use num::complex::Complex32;
+/// 
+/// let params = OfdmParameters::new(2, 16, 20, 16, 8);
+/// let x0 = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+/// let x1: Vec<Complex32> = (0..params.nb_fft).map(|i| Complex32::new(i as f32, 0.0)).collect();
+/// let mut y = vec![Complex32::default(); params.nb_fft_data_carriers];
+/// calculate_dqpsk(&params, &x0, &x1, &mut y);
+/// 
+/// // Carriers [-4,-1] are at the end of the FFT and carriers [1,4] skip the DC bin
+/// let fft_bins: Vec<usize> = y.iter().map(|x| x.re as usize).collect();
+/// assert_eq!(fft_bins, [12, 13, 14, 15, 1, 2, 3, 4]);
+/// ```
+pub fn calculate_dqpsk(params: &OfdmParameters, x0: &[Complex32], x1: &[Complex32], y: &mut[Complex32]) {
     let nb_fft = params.nb_fft;
     let nb_data = params.nb_fft_data_carriers;
     let nb_data_half = nb_data/2;
@@ -636,7 +2584,7 @@ fn calculate_dqpsk(params: &OfdmParameters, x0: &[Complex32], x1: &[Complex32],
     assert!(x1.len() == nb_fft, "x1 ({}) has different length to the fft ({})", x1.len(), nb_fft);
     assert!(y.len() == nb_data, "y ({}) has different length to the number of data carriers ({})", y.len(), nb_data);
     assert!(nb_fft >= nb_data, "length of fft ({}) is less than number of required data carriers ({})", nb_fft, nb_data);
-    assert!(nb_data % 2 == 0, "number of data carriers must be even ({})", nb_data);
+    assert!(nb_data.is_multiple_of(2), "number of data carriers must be even ({})", nb_data);
 
     // x0,x1 are FFTs where [0,N] => [0,2Fs)
     // y is the DQPSK for the frequency range [-Fa,0)+(0,Fa] => [2Fs-Fa,2Fs), (0,Fa]
@@ -657,16 +2605,78 @@ fn calculate_dqpsk(params: &OfdmParameters, x0: &[Complex32], x1: &[Complex32],
     }
 }
 
-fn calculate_soft_bits(carrier_mapper: &[usize], x: &[Complex32], y: &mut[i8]) {
-    assert!(carrier_mapper.len() == x.len(), "Carrier map and input symbols have mismatching lengths {} != {}", carrier_mapper.len(), x.len());
+/// Scrambles the order of carriers using the carrier map, so the carrier at index i is moved to index carrier_map\[i\].
+/// This is the frequency interleaving applied by the transmitter before the carriers are placed into the FFT bins.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::{frequency_interleave, frequency_deinterleave};
+///
+/// let carrier_map: Vec<usize> = (0..8).map(|i| (5*i + 3) % 8).collect();
+/// let carriers: Vec<char> = "abcdefgh".chars().collect();
+/// let mut interleaved = vec![' '; 8];
+/// frequency_interleave(&carriers, &mut interleaved, &carrier_map);
+/// assert_eq!(interleaved.iter().collect::<String>(), "bgdafche");
+///
+/// let mut deinterleaved = vec![' '; 8];
+/// frequency_deinterleave(&interleaved, &mut deinterleaved, &carrier_map);
+/// assert_eq!(deinterleaved, carriers);
+/// ```
+pub fn frequency_interleave<T: Copy>(input: &[T], output: &mut [T], carrier_map: &[usize]) {
+    assert!(carrier_map.len() == input.len(), "Carrier map and input have mismatching lengths {} != {}", carrier_map.len(), input.len());
+    assert!(carrier_map.len() == output.len(), "Carrier map and output have mismatching lengths {} != {}", carrier_map.len(), output.len());
+    for (x, i_mapped) in input.iter().zip(carrier_map.iter()) {
+        output[*i_mapped] = *x;
+    }
+}
+
+/// Restores the order of carriers scrambled by frequency_interleave(), so index i is read from index carrier_map\[i\].
+pub fn frequency_deinterleave<T: Copy>(input: &[T], output: &mut [T], carrier_map: &[usize]) {
+    assert!(carrier_map.len() == input.len(), "Carrier map and input have mismatching lengths {} != {}", carrier_map.len(), input.len());
+    assert!(carrier_map.len() == output.len(), "Carrier map and output have mismatching lengths {} != {}", carrier_map.len(), output.len());
+    for (y, i_mapped) in output.iter_mut().zip(carrier_map.iter()) {
+        *y = input[*i_mapped];
+    }
+}
+
+/// Checks that the carrier map is a permutation of 0..nb_data_carriers so every data carrier is read exactly once.
+/// A malformed map would otherwise read the wrong carriers or panic while deinterleaving.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::{validate_carrier_map, CarrierMapError};
+///
+/// assert_eq!(validate_carrier_map(&[2, 0, 3, 1], 4), Ok(()));
+/// assert_eq!(validate_carrier_map(&[2, 0, 3], 4), Err(CarrierMapError::WrongLength { expected: 4, actual: 3 }));
+/// assert_eq!(validate_carrier_map(&[2, 0, 4, 1], 4), Err(CarrierMapError::OutOfRange { index: 2, carrier: 4 }));
+/// assert_eq!(validate_carrier_map(&[2, 0, 2, 1], 4), Err(CarrierMapError::Duplicate { index: 2, carrier: 2 }));
+/// ```
+pub fn validate_carrier_map(map: &[usize], nb_data_carriers: usize) -> Result<(), CarrierMapError> {
+    if map.len() != nb_data_carriers {
+        return Err(CarrierMapError::WrongLength { expected: nb_data_carriers, actual: map.len() });
+    }
+    // Every entry is in range and unique so the map covers every carrier since it has one entry for each
+    let mut is_mapped = vec![false; nb_data_carriers];
+    for (index, carrier) in map.iter().copied().enumerate() {
+        match is_mapped.get_mut(carrier) {
+            None => return Err(CarrierMapError::OutOfRange { index, carrier }),
+            Some(true) => return Err(CarrierMapError::Duplicate { index, carrier }),
+            Some(is_mapped) => *is_mapped = true,
+        }
+    }
+    Ok(())
+}
+
+/// Converts DQPSK values into soft decision bits with the real components followed by the imaginary components.
+/// Each value is normalised by its largest component so the soft bits of a clean symbol are at full scale.
+pub fn calculate_soft_bits(x: &[Complex32], y: &mut[i8]) {
     assert!(x.len()*2 == y.len(), "Requires 2 soft bits for each input symbol but arrays are of lengths {} and {}", x.len(), y.len());
 
-    let length = carrier_mapper.len();
+    let length = x.len();
 
     // Clause 3.16 - Data demapper
     for i in 0..length {
-        let i_mapped = carrier_mapper[i];
-        let mut vec = x[i_mapped];
+        let mut vec = x[i];
 
         // NOTE: Use the L1 norm since it doesn't truncate like L2 norm
         //       I.e. When real=imag, then we expect b0=A, b1=A
@@ -680,6 +2690,86 @@ fn calculate_soft_bits(carrier_mapper: &[usize], x: &[Complex32], y: &mut[i8]) {
     }
 }
 
+/// Converts fixed point DQPSK values into soft decision bits like calculate_soft_bits() without floating point arithmetic.
+/// The division by the largest component is replaced with a multiplication by its reciprocal.
+/// The reciprocal is approximated with Newton-Raphson iterations after shifting the component into a fixed range.
+/// The soft bits have the same sign as calculate_soft_bits() and differ in magnitude by at most one.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::calculate_soft_bits_fixed_point;
+/// use num::complex::Complex;
+///
+/// let mut y = [0i8; 4];
+/// calculate_soft_bits_fixed_point(&[Complex::new(1000, -1000), Complex::new(0, 0)], &mut y);
+/// assert_eq!(y, [-127, 0, -127, 0]);
+/// ```
+pub fn calculate_soft_bits_fixed_point(x: &[Complex<i16>], y: &mut[i8]) {
+    assert!(x.len()*2 == y.len(), "Requires 2 soft bits for each input symbol but arrays are of lengths {} and {}", x.len(), y.len());
+
+    let length = x.len();
+    for i in 0..length {
+        let re = x[i].re as i32;
+        let im = x[i].im as i32;
+        let amplitude = re.unsigned_abs().max(im.unsigned_abs());
+        if amplitude == 0 {
+            y[i] = 0;
+            y[i+length] = 0;
+            continue;
+        }
+        let (reciprocal, shift) = calculate_reciprocal_fixed_point(amplitude);
+        y[i]        = quantise_to_soft_bit_fixed_point( re, reciprocal, shift);
+        y[i+length] = quantise_to_soft_bit_fixed_point(-im, reciprocal, shift);
+    }
+}
+
+/// Converts a DQPSK value into 16bit integers using integer operations on its floating point representation.
+/// Each value gets its own power of two scale so weak carriers aren't truncated to zero next to strong ones.
+/// The scale doesn't change the soft bits since they only depend on the ratio of the components.
+fn convert_dqpsk_to_fixed_point(x: Complex32) -> Complex<i16> {
+    // A single precision float is a 24bit mantissa with an implicit leading one scaled by a biased 8bit exponent
+    // Subnormal values have no implicit leading one and the same scale as the smallest normal exponent
+    let decompose = |x: f32| {
+        let bits = x.to_bits();
+        let exponent = (bits >> 23) & 0xFF;
+        let fraction = bits & 0x7F_FFFF;
+        let (mantissa, exponent) = if exponent == 0 { (fraction, 1) } else { (fraction | 0x80_0000, exponent) };
+        (bits >> 31 == 1, mantissa, exponent)
+    };
+    let re = decompose(x.re);
+    let im = decompose(x.im);
+    let max_exponent = re.2.max(im.2);
+    // The top 15 bits of the mantissa with the largest exponent fit into an i16 with its sign
+    let to_fixed_point = |(is_negative, mantissa, exponent): (bool, u32, u32)| {
+        let magnitude = mantissa.checked_shr(9 + max_exponent - exponent).unwrap_or(0) as i16;
+        if is_negative { -magnitude } else { magnitude }
+    };
+    Complex::new(to_fixed_point(re), to_fixed_point(im))
+}
+
+/// Approximates 1/x for 0 < x <= 2^15 as r*2^(s-32) where r is the reciprocal of x*2^(s-16) in Q16.
+fn calculate_reciprocal_fixed_point(x: u32) -> (u64, u32) {
+    // Shift x into [0.5,1) in Q16 so the reciprocal is in (1,2]
+    let shift = x.leading_zeros() - 16;
+    let d = (x << shift) as u64;
+    // The linear approximation 48/17 - 32/17*d has a relative error of at most 1/17 over the range
+    // Each Newton-Raphson iteration r = r*(2 - d*r) squares the relative error
+    const ONE: u64 = 1 << 16;
+    let mut r = 185043 - ((123362*d) >> 16);
+    for _ in 0..3 {
+        r = (r * (2*ONE - ((d*r) >> 16))) >> 16;
+    }
+    (r, shift)
+}
+
+#[inline(always)]
+fn quantise_to_soft_bit_fixed_point(x: i32, reciprocal: u64, shift: u32) -> i8 {
+    // This matches quantise_to_soft_bit() which truncates -127*x/amplitude towards zero
+    let magnitude = ((127*(x.unsigned_abs() as u64)*reciprocal) << shift) >> 32;
+    let magnitude = magnitude.min(127) as i8;
+    if x > 0 { -magnitude } else { magnitude }
+}
+
 #[inline(always)]
 fn quantise_to_soft_bit(x: f32) -> i8 {
     // Clause 3.4.2 - QPSK symbol mapper
@@ -698,15 +2788,82 @@ fn quantise_to_soft_bit(x: f32) -> i8 {
     y as i8
 }
 
-#[inline(always)]
-fn span_slice(start: usize, length: usize) -> std::ops::Range<usize> {
-    start..start+length
-}
+/// Estimates a bit error rate from the hard decisions of the DQPSK values without decoding them.
+/// The input holds consecutive DQPSK symbols back to back with nb_carriers values in each.
+///
+/// Each DQPSK value is the phase difference between two OFDM symbols, so the product of two consecutive values is the
+/// phase difference across three OFDM symbols. When the signal is clean, slicing that product gives the same quadrant
+/// as adding the sliced quadrants of the two values. A disagreement means the phase errors of the pair added up past a
+/// decision boundary. This returns the fraction of Gray coded bits that disagree, which is 0 for a clean signal, about
+/// 0.125 for uniformly random phases and at most 0.5.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::calculate_raw_ber_estimate;
+/// use num::complex::Complex32;
+///
+/// // The same phase on every carrier of every symbol has no phase errors
+/// let clean = vec![Complex32::from_polar(1.0, std::f32::consts::FRAC_PI_4); 4*64];
+/// assert_eq!(calculate_raw_ber_estimate(&clean, 64), 0.0);
+/// ```
+pub fn calculate_raw_ber_estimate(x: &[Complex32], nb_carriers: usize) -> f32 {
+    assert!(nb_carriers > 0, "Number of carriers must be non-zero");
+    assert!(x.len().is_multiple_of(nb_carriers), "DQPSK buffer of length {} isn't a whole number of symbols of {} carriers", x.len(), nb_carriers);
 
-#[inline(always)]
-fn chunk_slice(index: usize, length: usize) -> std::ops::Range<usize> {
-    let start_index = index*length;
-    span_slice(start_index, length)
-}
+    // Clause 3.4.2 - QPSK symbol mapper
+    // The quadrant q of a point at pi/4 + q*pi/2 is Gray coded so that neighbouring quadrants differ by one bit
+    const GRAY_CODE: [u8; 4] = [0b00, 0b01, 0b11, 0b10];
+    let get_quadrant = |z: Complex32| -> usize {
+        match (z.re >= 0.0, z.im >= 0.0) {
+            (true, true) => 0,
+            (false, true) => 1,
+            (false, false) => 2,
+            (true, false) => 3,
+        }
+    };
+    // Rotate the product of two DQPSK values from multiples of pi/2 onto the same quadrants as single values
+    let rotation = Complex32::from_polar(1.0, std::f32::consts::FRAC_PI_4);
+
+    let mut total_bits = 0usize;
+    let mut total_errors = 0u32;
+    for (x0, x1) in x.chunks_exact(nb_carriers).zip(x.chunks_exact(nb_carriers).skip(1)) {
+        for (z0, z1) in x0.iter().zip(x1.iter()) {
+            // pi/4 + q0*pi/2 + pi/4 + q1*pi/2 = pi/4 + (q0+q1+1)*pi/2 - pi/4
+            let expected = (get_quadrant(*z0) + get_quadrant(*z1) + 1) % 4;
+            let direct = get_quadrant(z0 * z1 * rotation);
+            total_errors += (GRAY_CODE[expected] ^ GRAY_CODE[direct]).count_ones();
+            total_bits += 2;
+        }
+    }
 
+    if total_bits == 0 {
+        return 0.0;
+    }
+    (total_errors as f32) / (total_bits as f32)
+}
 
+/// Converts soft decision bits into hard decision bits and packs them eight per byte.
+/// A positive soft bit is a logical 1 and zero or a negative soft bit is a logical 0.
+/// Bits are packed MSB first so the first soft bit is the MSB of the first byte.
+/// If the number of soft bits isn't a multiple of 8 the unused bits of the last byte are set to 0.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_demodulator::pack_hard_bits;
+///
+/// let soft: Vec<i8> = (0..21).map(|i| if (i*7) % 3 == 0 { 100 } else { -100 }).collect();
+/// let mut packed = vec![0u8; soft.len().div_ceil(8)];
+/// pack_hard_bits(&soft, &mut packed);
+/// assert_eq!(packed[0], 0b1001_0010);
+///
+/// let unpacked: Vec<bool> = (0..soft.len()).map(|i| (packed[i/8] >> (7 - i%8)) & 0b1 == 0b1).collect();
+/// let expected: Vec<bool> = soft.iter().map(|x| *x > 0).collect();
+/// assert_eq!(unpacked, expected);
+/// ```
+pub fn pack_hard_bits(soft: &[i8], out: &mut [u8]) {
+    assert!(out.len() == soft.len().div_ceil(8), "Packing {} soft bits requires {} bytes but got {} bytes", soft.len(), soft.len().div_ceil(8), out.len());
+    for (bits, byte) in soft.chunks(8).zip(out.iter_mut()) {
+        let value = bits.iter().fold(0u8, |acc, bit| (acc << 1) | (*bit > 0) as u8);
+        *byte = value << (8 - bits.len());
+    }
+}
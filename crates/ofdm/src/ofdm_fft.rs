@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use num::complex::Complex32;
+use rustfft::{FftPlanner, Fft};
+
+/// An FFT of a fixed size used by the demodulator so other FFT implementations can be swapped in.
+/// Neither direction is normalised, so an inverse after a forward FFT scales the samples by the FFT size.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_fft::OfdmFft;
+/// use ofdm::ofdm_modulator::OfdmModulator;
+/// use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+/// use ofdm::ofdm_parameters::OfdmParameters;
+/// use num::complex::Complex32;
+/// use std::sync::{Arc, Mutex};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// // A direct DFT that counts how many times it is used
+/// struct NaiveDft {
+///     total_calls: AtomicUsize,
+/// }
+/// impl NaiveDft {
+///     fn transform(&self, buf: &mut [Complex32], sign: f32) {
+///         self.total_calls.fetch_add(1, Ordering::Relaxed);
+///         let n = buf.len();
+///         let x = buf.to_vec();
+///         for (k, y) in buf.iter_mut().enumerate() {
+///             *y = x.iter()
+///                 .enumerate()
+///                 .map(|(i, x)| x * Complex32::from_polar(1.0, sign * 2.0 * std::f32::consts::PI * ((i*k) % n) as f32 / n as f32))
+///                 .sum();
+///         }
+///     }
+/// }
+/// impl OfdmFft for NaiveDft {
+///     fn forward(&self, buf: &mut [Complex32]) { self.transform(buf, -1.0) }
+///     fn inverse(&self, buf: &mut [Complex32]) { self.transform(buf, 1.0) }
+/// }
+///
+/// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+/// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).map(|i| (7*i + 3) % params.nb_fft_data_carriers).collect();
+/// let prs_fft: Vec<Complex32> = (0..params.nb_fft)
+///     .map(|i| Complex32::from_polar(1.0, ((i*i) % 7) as f32))
+///     .collect();
+/// let bits: Vec<bool> = (0..params.nb_output_bits).map(|i| (i*i + 3*i) % 5 < 2).collect();
+/// let mut modulator = OfdmModulator::new(&params, &carrier_map, &prs_fft);
+/// let mut signal = Vec::new();
+/// modulator.modulate_frame(&bits, &mut signal);
+/// signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+///
+/// let dft = Arc::new(NaiveDft { total_calls: AtomicUsize::new(0) });
+/// let mut demod = OfdmDemodulator::try_with_fft(&params, &carrier_map, &prs_fft, dft.clone()).unwrap();
+/// let frames = Arc::new(Mutex::new(Vec::new()));
+/// demod.subscribe_bits_out({
+///     let frames = frames.clone();
+///     move |rx_bits| frames.lock().unwrap().push(rx_bits.iter().map(|x| *x > 0).collect::<Vec<bool>>())
+/// });
+/// demod.settings.coarse_frequency_is_enabled = false;
+/// demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+/// demod.process(&signal);
+/// assert_eq!(*frames.lock().unwrap(), vec![bits]);
+/// assert!(dft.total_calls.load(Ordering::Relaxed) > params.nb_symbols);
+/// ```
+pub trait OfdmFft: Send + Sync {
+    /// Replaces the samples with their forward FFT.
+    fn forward(&self, buf: &mut [Complex32]);
+    /// Replaces the samples with their inverse FFT.
+    fn inverse(&self, buf: &mut [Complex32]);
+}
+
+/// The default FFT implementation using rustfft.
+pub struct RustFft {
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+}
+
+impl RustFft {
+    pub fn new(nb_fft: usize) -> Self {
+        let mut planner = FftPlanner::new();
+        Self {
+            fft: planner.plan_fft_forward(nb_fft),
+            ifft: planner.plan_fft_inverse(nb_fft),
+        }
+    }
+}
+
+impl OfdmFft for RustFft {
+    fn forward(&self, buf: &mut [Complex32]) {
+        self.fft.process(buf);
+    }
+
+    fn inverse(&self, buf: &mut [Complex32]) {
+        self.ifft.process(buf);
+    }
+}
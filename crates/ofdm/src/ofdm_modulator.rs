@@ -0,0 +1,224 @@
+use crate::ofdm_parameters::OfdmParameters;
+use crate::ofdm_demodulator::{frequency_interleave, validate_carrier_map};
+use std::sync::Arc;
+use num::complex::Complex32;
+use rustfft::{FftPlanner, Fft};
+use itertools::izip;
+#[cfg(feature = "noise")]
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "noise")]
+use rand_chacha::ChaCha8Rng;
+
+/// Generates OFDM frames from bits as the reference inverse of OfdmDemodulator.
+/// This is a noise free transmitter for testing the demodulator end to end without a capture.
+///
+/// # Examples
+/// ```
+/// use ofdm::ofdm_modulator::OfdmModulator;
+/// use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+/// use ofdm::ofdm_parameters::OfdmParameters;
+/// use num::complex::Complex32;
+/// use std::sync::{Arc, Mutex};
+///
+/// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+/// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).map(|i| (7*i + 3) % params.nb_fft_data_carriers).collect();
+/// let prs_fft: Vec<Complex32> = (0..params.nb_fft)
+///     .map(|i| Complex32::from_polar(1.0, ((i*i) % 7) as f32))
+///     .collect();
+///
+/// // Modulate two frames of bits followed by the NULL symbol that ends the last frame and an extra sample
+/// let frame_bits: Vec<Vec<bool>> = (0..2)
+///     .map(|frame| (0..params.nb_output_bits).map(|i| (i*i + 3*i + frame) % 5 < 2).collect())
+///     .collect();
+/// let mut modulator = OfdmModulator::new(&params, &carrier_map, &prs_fft);
+/// let mut signal = Vec::new();
+/// for bits in &frame_bits {
+///     modulator.modulate_frame(bits, &mut signal);
+/// }
+/// signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+/// assert_eq!(signal.len(), 2*params.nb_input_samples + params.nb_null_period + 1);
+///
+/// let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+/// let frames = Arc::new(Mutex::new(Vec::new()));
+/// demod.subscribe_bits_out({
+///     let frames = frames.clone();
+///     move |bits| frames.lock().unwrap().push(bits.iter().map(|x| *x > 0).collect::<Vec<bool>>())
+/// });
+/// demod.settings.coarse_frequency_is_enabled = false;
+/// demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+/// demod.process(&signal);
+/// assert_eq!(*frames.lock().unwrap(), frame_bits);
+/// ```
+pub struct OfdmModulator {
+    params: OfdmParameters,
+    carrier_map: Vec<usize>,
+    prs_fft: Vec<Complex32>,
+    ifft: Arc<dyn Fft<f32>>,
+    symbol_fft_buffer: Vec<Complex32>,
+    symbol_time_buffer: Vec<Complex32>,
+    dqpsk_buffer: Vec<Complex32>,
+    dqpsk_interleaved_buffer: Vec<Complex32>,
+}
+
+impl OfdmModulator {
+    /// Creates a modulator with the same carrier map and PRS that the demodulator is created with.
+    /// Panics if the carrier map isn't a permutation of the data carriers.
+    pub fn new(params: &OfdmParameters, carrier_map: &[usize], prs_fft: &[Complex32]) -> Self {
+        if let Err(err) = validate_carrier_map(carrier_map, params.nb_fft_data_carriers) {
+            panic!("{}", err);
+        }
+        assert!(prs_fft.len() == params.nb_fft, "PRS FFT must have {} samples but got {} samples", params.nb_fft, prs_fft.len());
+        let mut planner = FftPlanner::new();
+        Self {
+            params: *params,
+            carrier_map: carrier_map.to_vec(),
+            prs_fft: prs_fft.to_vec(),
+            ifft: planner.plan_fft_inverse(params.nb_fft),
+            symbol_fft_buffer: vec![Complex32::default(); params.nb_fft],
+            symbol_time_buffer: vec![Complex32::default(); params.nb_fft],
+            dqpsk_buffer: vec![Complex32::default(); params.nb_fft_data_carriers],
+            dqpsk_interleaved_buffer: vec![Complex32::default(); params.nb_fft_data_carriers],
+        }
+    }
+
+    /// Appends the nb_input_samples samples of an OFDM frame starting with its NULL symbol.
+    /// The bits are in the same order as the soft decision bits outputted by the demodulator where true is a positive soft bit.
+    pub fn modulate_frame(&mut self, bits: &[bool], out: &mut Vec<Complex32>) {
+        let nb_carriers = self.params.nb_fft_data_carriers;
+        assert!(bits.len() == self.params.nb_output_bits, "Frame requires {} bits but got {} bits", self.params.nb_output_bits, bits.len());
+
+        out.extend((0..self.params.nb_null_period).map(|_| Complex32::default()));
+        self.symbol_fft_buffer.copy_from_slice(&self.prs_fft);
+        self.push_symbol(out);
+
+        for symbol_bits in bits.chunks_exact(2*nb_carriers) {
+            // Clause 3.16 - Data demapper in reverse
+            // The demodulator outputs a positive soft bit for a negative real or positive imaginary component
+            let (real_bits, imag_bits) = symbol_bits.split_at(nb_carriers);
+            for (y, b0, b1) in izip!(self.dqpsk_buffer.iter_mut(), real_bits.iter(), imag_bits.iter()) {
+                let re = if *b0 { -1.0 } else { 1.0 };
+                let im = if *b1 { 1.0 } else { -1.0 };
+                *y = Complex32::new(re, im) * std::f32::consts::FRAC_1_SQRT_2;
+            }
+            frequency_interleave(&self.dqpsk_buffer, &mut self.dqpsk_interleaved_buffer, &self.carrier_map);
+
+            // Clause 3.15 - Differential demodulator in reverse
+            // The demodulator multiplies the last symbol with the conjugate of the next so the phase difference is subtracted
+            for (i, x) in self.dqpsk_interleaved_buffer.iter().enumerate() {
+                let fft_index = get_carrier_fft_index(&self.params, i);
+                self.symbol_fft_buffer[fft_index] *= x.conj();
+            }
+            self.push_symbol(out);
+        }
+    }
+
+    /// Adds complex white gaussian noise to the samples at an Eb/N0 in dB that is reproducible for the same seed.
+    /// Eb/N0 is measured per data carrier after the FFT where each carrier carries 2 bits.
+    /// The energy in the cyclic prefix isn't counted since it is discarded by the demodulator.
+    /// This requires the noise feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_modulator::OfdmModulator;
+    /// use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48);
+    /// let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).map(|i| (7*i + 3) % params.nb_fft_data_carriers).collect();
+    /// let prs_fft: Vec<Complex32> = (0..params.nb_fft)
+    ///     .map(|i| Complex32::from_polar(1.0, ((i*i) % 7) as f32))
+    ///     .collect();
+    /// let frame_bits: Vec<Vec<bool>> = (0..50)
+    ///     .map(|frame| (0..params.nb_output_bits).map(|i| (i*i + 3*i + frame*frame) % 5 < 2).collect())
+    ///     .collect();
+    /// let mut modulator = OfdmModulator::new(&params, &carrier_map, &prs_fft);
+    /// let mut signal = Vec::new();
+    /// for bits in &frame_bits {
+    ///     modulator.modulate_frame(bits, &mut signal);
+    /// }
+    /// signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+    ///
+    /// // The same seed gives the same noise
+    /// let mut noisy_0 = signal.clone();
+    /// let mut noisy_1 = signal.clone();
+    /// modulator.add_noise(&mut noisy_0, 6.0, 1);
+    /// modulator.add_noise(&mut noisy_1, 6.0, 1);
+    /// assert_eq!(noisy_0, noisy_1);
+    ///
+    /// let measure_ber = |ebn0_db: f32, is_tracking: bool| {
+    ///     let mut noisy = signal.clone();
+    ///     modulator.add_noise(&mut noisy, ebn0_db, 1);
+    ///     let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+    ///     demod.settings.coarse_frequency_is_enabled = false;
+    ///     demod.settings.freeze_fine_frequency = !is_tracking;
+    ///     demod.settings.freeze_fine_time = !is_tracking;
+    ///     let errors = Arc::new(Mutex::new((0, 0)));
+    ///     demod.subscribe_frames_out({
+    ///         let errors = errors.clone();
+    ///         let frame_bits = frame_bits.clone();
+    ///         move |rx_bits, info| {
+    ///             let tx_bits = &frame_bits[info.frame_counter as usize];
+    ///             let mut errors = errors.lock().unwrap();
+    ///             errors.0 += rx_bits.iter().zip(tx_bits.iter()).filter(|(rx, tx)| (**rx > 0) != **tx).count();
+    ///             errors.1 += rx_bits.len();
+    ///         }
+    ///     });
+    ///     demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    ///     demod.process(&noisy);
+    ///     let (total_errors, total_bits) = *errors.lock().unwrap();
+    ///     assert_eq!(total_bits, frame_bits.len()*params.nb_output_bits);
+    ///     (total_errors as f32) / (total_bits as f32)
+    /// };
+    ///
+    /// // With the synchronisation held at the correct values this is the theoretical BER of DQPSK
+    /// let ber: Vec<f32> = [2.0, 4.0, 6.0, 8.0].iter().map(|ebn0_db| measure_ber(*ebn0_db, false)).collect();
+    /// assert!(ber.windows(2).all(|x| x[1] < x[0]));
+    /// assert!((ber[1] - 0.049).abs() < 0.01);
+    /// assert!((ber[3] - 0.0035).abs() < 0.0015);
+    ///
+    /// // Estimating the fine time and frequency offsets from the noisy signal costs some performance
+    /// let ber_tracking: Vec<f32> = [2.0, 4.0, 6.0, 8.0].iter().map(|ebn0_db| measure_ber(*ebn0_db, true)).collect();
+    /// assert!(ber_tracking.windows(2).all(|x| x[1] < x[0]));
+    /// assert!(ber_tracking.iter().zip(ber.iter()).all(|(x, y)| x >= y));
+    /// ```
+    #[cfg(feature = "noise")]
+    pub fn add_noise(&self, samples: &mut [Complex32], ebn0_db: f32, seed: u64) {
+        // The inverse FFT is scaled so each carrier has an energy of nb_fft after the forward FFT
+        // The noise of each sample adds up to nb_fft times its variance in each bin so the FFT size cancels out
+        let bits_per_carrier = 2.0;
+        let ebn0 = 10.0f32.powf(ebn0_db/10.0);
+        let noise_variance = 1.0 / (bits_per_carrier * ebn0);
+        let noise_amplitude = (noise_variance * 0.5).sqrt();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        for x in samples.iter_mut() {
+            // Box-Muller transform of two uniform samples into two independent gaussian samples
+            // The first uniform sample is taken from (0,1] so its logarithm is finite
+            let u0: f32 = 1.0 - rng.gen::<f32>();
+            let u1: f32 = rng.gen::<f32>();
+            let noise = Complex32::from_polar((-2.0*u0.ln()).sqrt(), 2.0*std::f32::consts::PI*u1);
+            *x += noise * noise_amplitude;
+        }
+    }
+
+    fn push_symbol(&mut self, out: &mut Vec<Complex32>) {
+        let nb_fft = self.params.nb_fft;
+        self.symbol_time_buffer.copy_from_slice(&self.symbol_fft_buffer);
+        self.ifft.process(&mut self.symbol_time_buffer);
+        // The inverse FFT isn't normalised so we keep the power of the samples independent of the FFT size
+        let scale = 1.0 / (nb_fft as f32).sqrt();
+        let cyclic_prefix = &self.symbol_time_buffer[nb_fft-self.params.nb_cyclic_prefix..];
+        out.extend(cyclic_prefix.iter().chain(self.symbol_time_buffer.iter()).map(|x| x * scale));
+    }
+}
+
+/// The data carriers [-Fa,0) are at the end of the FFT and (0,Fa] skip the DC bin like calculate_dqpsk().
+fn get_carrier_fft_index(params: &OfdmParameters, carrier_index: usize) -> usize {
+    let nb_data_half = params.nb_fft_data_carriers/2;
+    if carrier_index < nb_data_half {
+        params.nb_fft - nb_data_half + carrier_index
+    } else {
+        1 + carrier_index - nb_data_half
+    }
+}
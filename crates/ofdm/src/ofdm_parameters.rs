@@ -33,10 +33,23 @@ pub struct OfdmParameters {
     pub nb_output_bits: usize,
     /// Number of complex samples for the entire OFDM frame.
     pub nb_input_samples: usize,
+    /// Sampling frequency in Hz.
+    /// Defaults to 1 so that durations are measured in samples until it is set with with_sample_rate().
+    pub sample_rate: f32,
 }
 
 impl OfdmParameters {
     /// Creates all derived parameters for OFDM from a required subset.
+    /// Panics if the frame is too large for its number of input samples or output bits to fit in a usize.
+    ///
+    /// # Examples
+    /// ```should_panic
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    ///
+    /// // The frame spans more samples than a usize can hold so this can't wrap around to a tiny buffer size
+    /// let nb_symbols = usize::MAX/2048 + 1;
+    /// let _ = OfdmParameters::new(nb_symbols, 2656, 2552, 2048, 1536);
+    /// ```
     pub fn new(
         nb_symbols: usize,
         nb_null_period: usize,
@@ -49,11 +62,16 @@ impl OfdmParameters {
         assert!(nb_symbol_period >= nb_fft, "Number of samples in symbol is less than FFT resolution");
         assert!(nb_fft >= nb_fft_data_carriers, "Number of data carriers is limited to FFT resolution");
 
-        let nb_input_samples = nb_null_period + nb_symbol_period*nb_symbols;
+        // Custom parameters can be large enough to overflow on targets with a 32bit usize
+        let nb_input_samples = nb_symbol_period.checked_mul(nb_symbols)
+            .and_then(|x| x.checked_add(nb_null_period))
+            .expect("Number of input samples in the frame overflows a usize");
         let nb_cyclic_prefix = nb_symbol_period - nb_fft;
         let nb_dqpsk_symbols = nb_symbols-1;
-        let nb_output_samples = nb_dqpsk_symbols*nb_fft_data_carriers;
-        let nb_output_bits = nb_output_samples*2;
+        let nb_output_bits = nb_dqpsk_symbols.checked_mul(nb_fft_data_carriers)
+            .and_then(|x| x.checked_mul(2))
+            .expect("Number of output bits in the frame overflows a usize");
+        let nb_output_samples = nb_output_bits/2;
 
         Self {
             nb_symbols,
@@ -66,7 +84,116 @@ impl OfdmParameters {
             nb_output_samples,
             nb_output_bits,
             nb_input_samples,
+            sample_rate: 1.0,
         }
     }
 
+    /// Sets the sampling frequency in Hz that the frame structure is defined for.
+    pub fn with_sample_rate(mut self, sample_rate: f32) -> Self {
+        assert!(sample_rate > 0.0, "Sample rate must be positive");
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Number of complex samples spanned by one OFDM frame including the NULL symbol.
+    pub fn samples_per_frame(&self) -> usize {
+        self.nb_input_samples
+    }
+
+    /// Duration of one OFDM frame in seconds at the configured sample rate.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48).with_sample_rate(1e3);
+    /// assert_eq!(params.samples_per_frame(), 80 + 4*72);
+    /// assert!((params.frame_duration_secs() - 0.368).abs() < 1e-6);
+    /// ```
+    pub fn frame_duration_secs(&self) -> f32 {
+        (self.samples_per_frame() as f32) / self.sample_rate
+    }
+
+    /// Converts a frequency normalised to the sample rate into Hz.
+    /// Every frequency in Hz should be converted here so they all follow the configured sample rate.
+    pub fn normalised_to_hz(&self, frequency: f32) -> f32 {
+        frequency * self.sample_rate
+    }
+
+    /// Converts a frequency in Hz into a frequency normalised to the sample rate.
+    pub fn hz_to_normalised(&self, frequency_hz: f32) -> f32 {
+        frequency_hz / self.sample_rate
+    }
+
+    /// Spacing between the FFT bins in Hz.
+    pub fn fft_bin_spacing_hz(&self) -> f32 {
+        self.normalised_to_hz(1.0 / (self.nb_fft as f32))
+    }
+
+    /// Creates the same frame structure at a different sample rate by scaling every period with the sample rate.
+    /// The carriers keep their spacing in Hz so the extra FFT bins of a higher sample rate are left empty.
+    /// Returns None if a period doesn't scale to a whole number of samples or the FFT becomes too small for the data carriers.
+    ///
+    /// # Examples
+    /// ```
+    /// use ofdm::ofdm_modulator::OfdmModulator;
+    /// use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+    /// use ofdm::ofdm_parameters::OfdmParameters;
+    /// use num::complex::Complex32;
+    ///
+    /// let params = OfdmParameters::new(4, 80, 72, 64, 48).with_sample_rate(1e6);
+    /// let params_2x = params.resampled(2e6).unwrap();
+    /// assert_eq!((params_2x.nb_null_period, params_2x.nb_symbol_period, params_2x.nb_fft), (160, 144, 128));
+    /// assert_eq!(params_2x.nb_fft_data_carriers, params.nb_fft_data_carriers);
+    /// assert_eq!(params_2x.fft_bin_spacing_hz(), params.fft_bin_spacing_hz());
+    /// assert_eq!(params_2x.frame_duration_secs(), params.frame_duration_secs());
+    /// assert!(params.resampled(1.1e6).is_none());
+    /// assert!(params.resampled(0.5e6).is_none());
+    ///
+    /// // A carrier that is 2kHz off is measured as 2kHz at either sample rate
+    /// let measure_frequency_offset_hz = |params: &OfdmParameters| {
+    ///     let carrier_map: Vec<usize> = (0..params.nb_fft_data_carriers).collect();
+    ///     let prs_fft: Vec<Complex32> = (0..params.nb_fft)
+    ///         .map(|i| Complex32::from_polar(1.0, ((i*i) % 7) as f32))
+    ///         .collect();
+    ///     let bits = vec![false; params.nb_output_bits];
+    ///     let mut modulator = OfdmModulator::new(params, &carrier_map, &prs_fft);
+    ///     let mut signal = Vec::new();
+    ///     for _ in 0..10 {
+    ///         modulator.modulate_frame(&bits, &mut signal);
+    ///     }
+    ///     signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+    ///     let frequency = params.hz_to_normalised(2e3);
+    ///     for (i, x) in signal.iter_mut().enumerate() {
+    ///         *x *= Complex32::from_polar(1.0, 2.0 * std::f32::consts::PI * frequency * (i as f32));
+    ///     }
+    ///
+    ///     let mut demod = OfdmDemodulator::new(params, &carrier_map, &prs_fft);
+    ///     demod.settings.coarse_frequency_is_enabled = false;
+    ///     demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    ///     demod.process(&signal);
+    ///     assert_eq!(demod.total_frames_read, 10);
+    ///     demod.sync_state().net_frequency_offset_hz
+    /// };
+    /// let offset_hz = measure_frequency_offset_hz(&params);
+    /// let offset_2x_hz = measure_frequency_offset_hz(&params_2x);
+    /// assert!((offset_hz + 2e3).abs() < 50.0);
+    /// assert!((offset_2x_hz + 2e3).abs() < 50.0);
+    /// ```
+    pub fn resampled(&self, sample_rate: f32) -> Option<Self> {
+        let scale = sample_rate / self.sample_rate;
+        let scale_period = |period: usize| {
+            let scaled_period = (period as f32) * scale;
+            let whole_period = scaled_period.round();
+            ((scaled_period - whole_period).abs() < 1e-3).then_some(whole_period as usize)
+        };
+        let nb_null_period = scale_period(self.nb_null_period)?;
+        let nb_symbol_period = scale_period(self.nb_symbol_period)?;
+        let nb_fft = scale_period(self.nb_fft)?;
+        if nb_fft < self.nb_fft_data_carriers {
+            return None;
+        }
+        let params = Self::new(self.nb_symbols, nb_null_period, nb_symbol_period, nb_fft, self.nb_fft_data_carriers);
+        Some(params.with_sample_rate(sample_rate))
+    }
 }
\ No newline at end of file
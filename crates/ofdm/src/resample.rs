@@ -0,0 +1,198 @@
+use num::complex::Complex32;
+use num::integer::gcd;
+use std::f32::consts::PI;
+
+/// Number of filter taps per unit of the larger of the interpolation and decimation factors.
+/// This sets the transition band of the Blackman window to a quarter of the narrower Nyquist band.
+const TAPS_PER_RATE_FACTOR: usize = 44;
+
+/// Converts complex samples between two sampling frequencies whose ratio is a rational number.
+/// The input is upsampled by L, lowpass filtered and then downsampled by M.
+/// A polyphase filter bank is used so only the non-zero upsampled samples are ever multiplied.
+///
+/// # Diagram
+/// ```text
+/// x[n] -> | Upsample L | -> | Lowpass h[k] | -> | Downsample M | -> y[n]
+/// ```
+///
+/// # Examples
+/// Resampling a tone from 2.4MHz to 2.048MHz should keep its frequency and amplitude.
+/// ```
+/// use ofdm::resample::Resampler;
+/// use num::complex::Complex32;
+///
+/// let (input_rate, output_rate) = (2_400_000, 2_048_000);
+/// let mut resampler = Resampler::new(input_rate, output_rate, 0.75);
+/// assert_eq!((resampler.interpolation(), resampler.decimation()), (64, 75));
+///
+/// let tone_frequency = 300e3;
+/// let tone = |rate: u32, i: usize| Complex32::from_polar(1.0, std::f32::consts::TAU * tone_frequency * (i as f32) / (rate as f32));
+/// let input: Vec<Complex32> = (0..24000).map(|i| tone(input_rate, i)).collect();
+/// let mut output = Vec::new();
+/// // Resampling in uneven chunks gives the same result as resampling everything at once
+/// for chunk in input.chunks(1001) {
+///     resampler.process(chunk, &mut output);
+/// }
+/// assert_eq!(output.len(), 24000*64/75);
+///
+/// // Skip the filter delay and compare against an ideal tone at the output rate
+/// let delay = resampler.filter().len() / (2*75);
+/// let phase_error = output[delay+100] * tone(output_rate, delay+100).conj();
+/// for (i, y) in output.iter().enumerate().skip(delay+100) {
+///     let error = y - tone(output_rate, i)*phase_error;
+///     assert!(error.norm() < 1e-2);
+/// }
+/// ```
+pub struct Resampler {
+    interpolation: usize,
+    decimation: usize,
+    filter: Vec<f32>,
+    filter_bank: Vec<Vec<f32>>,
+    history: Vec<Complex32>,
+    history_index: usize,
+    phase: usize,
+}
+
+impl Resampler {
+    /// Creates a resampler between two sampling frequencies in Hz.
+    /// The passband is the fraction of the narrower Nyquist band that is kept flat.
+    /// The rest of the band is used for the transition so the passband should be less than 1.
+    pub fn new(input_rate: u32, output_rate: u32, passband: f32) -> Self {
+        assert!(input_rate > 0 && output_rate > 0, "Sampling frequencies must be non-zero");
+        assert!(passband > 0.0 && passband < 1.0, "Passband must be a fraction between 0 and 1 but got {}", passband);
+
+        let divisor = gcd(input_rate, output_rate);
+        let interpolation = (output_rate / divisor) as usize;
+        let decimation = (input_rate / divisor) as usize;
+
+        // The cutoff sits in the middle of the transition band at the narrower of the two Nyquist frequencies
+        // This is normalised to the upsampled sampling frequency
+        let rate_factor = interpolation.max(decimation);
+        let nyquist = 0.5 / (rate_factor as f32);
+        let cutoff = nyquist * (1.0 + passband) / 2.0;
+        let taps_per_phase = (TAPS_PER_RATE_FACTOR*rate_factor).div_ceil(interpolation);
+        let mut filter = design_lowpass_filter(taps_per_phase*interpolation, cutoff);
+        // Zero stuffing divides the signal power by L so we make up for it with the filter gain
+        filter.iter_mut().for_each(|x| *x *= interpolation as f32);
+
+        // Phase p applies the taps h[p + jL] to the input x[n-j]
+        // We store the taps in reverse so they line up with the history that is stored from oldest to newest
+        let filter_bank: Vec<Vec<f32>> = (0..interpolation)
+            .map(|p| filter.iter().skip(p).step_by(interpolation).rev().copied().collect())
+            .collect();
+
+        Self {
+            interpolation,
+            decimation,
+            filter,
+            filter_bank,
+            history: vec![Complex32::default(); 2*taps_per_phase],
+            history_index: 0,
+            phase: 0,
+        }
+    }
+
+    /// The upsampling factor L.
+    pub fn interpolation(&self) -> usize {
+        self.interpolation
+    }
+
+    /// The downsampling factor M.
+    pub fn decimation(&self) -> usize {
+        self.decimation
+    }
+
+    /// The lowpass filter that runs at the upsampled sampling frequency.
+    /// This includes the gain of L that compensates for upsampling.
+    pub fn filter(&self) -> &[f32] {
+        &self.filter
+    }
+
+    /// Clears the filter history so the next input starts from silence.
+    pub fn reset(&mut self) {
+        self.history.fill(Complex32::default());
+        self.history_index = 0;
+        self.phase = 0;
+    }
+
+    /// Resamples the input and appends the resampled samples to the output.
+    /// The input can be split into arbitrarily sized chunks across multiple calls.
+    pub fn process(&mut self, input: &[Complex32], output: &mut Vec<Complex32>) {
+        let taps_per_phase = self.history.len()/2;
+        output.reserve(input.len()*self.interpolation/self.decimation + 1);
+        for x in input {
+            // The history is stored twice so the most recent taps are always a contiguous slice
+            self.history[self.history_index] = *x;
+            self.history[self.history_index + taps_per_phase] = *x;
+            self.history_index = (self.history_index + 1) % taps_per_phase;
+            let history = &self.history[self.history_index..self.history_index+taps_per_phase];
+
+            // Each input sample spans L upsampled samples, and an output is taken every M upsampled samples
+            while self.phase < self.interpolation {
+                let taps = &self.filter_bank[self.phase];
+                let y: Complex32 = history.iter().zip(taps.iter()).map(|(x, h)| x * h).sum();
+                output.push(y);
+                self.phase += self.decimation;
+            }
+            self.phase -= self.interpolation;
+        }
+    }
+}
+
+/// Designs a linear phase lowpass filter using a windowed sinc with a Blackman window.
+/// The cutoff is normalised to the sampling frequency so it is in the range (0, 0.5).
+/// The filter has unity gain at DC.
+pub fn design_lowpass_filter(total_taps: usize, cutoff: f32) -> Vec<f32> {
+    assert!(total_taps > 0, "Filter must have at least one tap");
+    assert!(cutoff > 0.0 && cutoff < 0.5, "Cutoff must be between 0 and 0.5 but got {}", cutoff);
+
+    let centre = (total_taps as f32 - 1.0) / 2.0;
+    let window_length = total_taps.max(2) as f32 - 1.0;
+    let mut filter: Vec<f32> = (0..total_taps)
+        .map(|i| {
+            let t = i as f32 - centre;
+            let sinc = match t == 0.0 {
+                true => 2.0*cutoff,
+                false => (2.0*PI*cutoff*t).sin() / (PI*t),
+            };
+            let w = (i as f32) / window_length;
+            let window = 0.42 - 0.5*(2.0*PI*w).cos() + 0.08*(4.0*PI*w).cos();
+            sinc*window
+        })
+        .collect();
+    let dc_gain: f32 = filter.iter().sum();
+    filter.iter_mut().for_each(|x| *x /= dc_gain);
+    filter
+}
+
+/// Calculates the magnitude response of a filter at a frequency normalised to its sampling frequency.
+///
+/// # Examples
+/// The resampling filter should be flat across the passband and attenuate images above the narrower Nyquist frequency.
+/// ```
+/// use ofdm::resample::{Resampler, calculate_filter_magnitude};
+///
+/// for (input_rate, output_rate) in [(2_400_000, 2_048_000), (4_096_000, 2_048_000)] {
+///     let resampler = Resampler::new(input_rate, output_rate, 0.75);
+///     let filter = resampler.filter();
+///     let gain = resampler.interpolation() as f32;
+///     let nyquist = 0.5 / (resampler.interpolation().max(resampler.decimation()) as f32);
+///     for i in 0..=100 {
+///         let passband = 0.75*nyquist*(i as f32)/100.0;
+///         let magnitude_db = 20.0*(calculate_filter_magnitude(filter, passband)/gain).log10();
+///         assert!(magnitude_db.abs() < 0.01, "Passband ripple of {}dB at {}", magnitude_db, passband);
+///
+///         let stopband = nyquist*(1.0 + 0.25*(i as f32)/100.0);
+///         let magnitude_db = 20.0*(calculate_filter_magnitude(filter, stopband)/gain).log10();
+///         assert!(magnitude_db < -60.0, "Stopband attenuation of {}dB at {}", magnitude_db, stopband);
+///     }
+/// }
+/// ```
+pub fn calculate_filter_magnitude(filter: &[f32], frequency: f32) -> f32 {
+    let response: Complex32 = filter
+        .iter()
+        .enumerate()
+        .map(|(i, h)| Complex32::from_polar(*h, -2.0*PI*frequency*(i as f32)))
+        .sum();
+    response.norm()
+}
@@ -0,0 +1,70 @@
+use num::complex::Complex32;
+
+/// Creates the range for a slice of a given length starting at an index.
+/// This is useful for reading a symbol out of the public buffers of the demodulator.
+///
+/// # Examples
+/// ```
+/// use ofdm::util::{span_slice, chunk_slice};
+///
+/// let buffer: Vec<usize> = (0..12).collect();
+/// assert_eq!(&buffer[span_slice(2, 3)], &[2, 3, 4]);
+/// // Buffers that hold multiple symbols back to back can be read one symbol at a time
+/// assert_eq!(&buffer[chunk_slice(2, 4)], &[8, 9, 10, 11]);
+/// ```
+#[inline(always)]
+pub fn span_slice(start: usize, length: usize) -> std::ops::Range<usize> {
+    start..start+length
+}
+
+/// Creates the range for the nth chunk of a slice that is split into chunks of equal length.
+#[inline(always)]
+pub fn chunk_slice(index: usize, length: usize) -> std::ops::Range<usize> {
+    let start_index = index*length;
+    span_slice(start_index, length)
+}
+
+/// Calculates the average L1 norm (|re|+|im|) of a block of samples.
+/// The sum is split between independent lanes so the compiler can vectorise it, since a sum in a single accumulator
+/// can't be reordered without changing its rounding.
+/// The result matches calculate_l1_average_scalar() within floating point tolerance.
+///
+/// # Examples
+/// ```
+/// use ofdm::util::{calculate_l1_average, calculate_l1_average_scalar};
+/// use num::complex::Complex32;
+///
+/// // Lengths that aren't a multiple of the number of lanes include the remaining samples
+/// for length in [1, 7, 8, 9, 100, 2048] {
+///     let block: Vec<Complex32> = (0..length)
+///         .map(|i| Complex32::new((0.37*(i as f32)).sin()*100.0, (0.11*(i as f32)).cos()*-50.0))
+///         .collect();
+///     let expected = calculate_l1_average_scalar(&block);
+///     let actual = calculate_l1_average(&block);
+///     assert!((actual - expected).abs() <= expected*1e-5, "length={} expected={} actual={}", length, expected, actual);
+/// }
+/// assert_eq!(calculate_l1_average(&[Complex32::new(-3.0, 4.0)]), 7.0);
+/// ```
+pub fn calculate_l1_average(block: &[Complex32]) -> f32 {
+    const NB_LANES: usize = 16;
+    let mut lanes = [0.0f32; NB_LANES];
+    let mut chunks = block.chunks_exact(NB_LANES);
+    for chunk in &mut chunks {
+        for (lane, x) in lanes.iter_mut().zip(chunk.iter()) {
+            *lane += x.re.abs() + x.im.abs();
+        }
+    }
+    let remainder_sum: f32 = chunks.remainder().iter().map(|x| x.l1_norm()).sum();
+    let l1_sum = lanes.iter().sum::<f32>() + remainder_sum;
+    l1_sum / (block.len() as f32)
+}
+
+/// Calculates the average L1 norm of a block of samples with a single accumulator.
+/// This is the reference for calculate_l1_average().
+pub fn calculate_l1_average_scalar(block: &[Complex32]) -> f32 {
+    let l1_sum: f32 = block
+        .iter()
+        .map(|x| x.l1_norm())
+        .sum();
+    l1_sum / (block.len() as f32)
+}
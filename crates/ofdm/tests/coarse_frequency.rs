@@ -0,0 +1,47 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+
+#[test]
+fn distance_weighting_prefers_true_peak_when_it_is_below_0db() {
+    let params = common::test_params();
+    let frames: Vec<Vec<bool>> = (0..4).map(|i| common::frame_bits(&params, i)).collect();
+    let mut signal = common::modulated_frames(&params, &frames);
+
+    // A weak signal puts the whole coarse frequency impulse response below 0dB
+    // Its peak is 3 FFT bins from zero offset which is well inside the search range of 10 FFT bins
+    let fft_bin = 1.0 / (params.nb_fft as f32);
+    common::frequency_shift(&mut signal, 3.0*fft_bin);
+    for x in signal.iter_mut() {
+        *x *= 1e-4;
+    }
+
+    // The fine time offset is held so only the coarse frequency search depends on the signal level
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_max_range = 2.0 * 10.0 * fft_bin;
+    demod.settings.freeze_fine_time = true;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+    assert_eq!(demod.total_frames_read, 4);
+    assert_eq!(demod.coarse_frequency_offset / fft_bin, -3.0);
+    assert!(!demod.coarse_frequency_saturated);
+}
+
+#[test]
+fn peak_distance_penalty_above_one_is_clamped() {
+    let params = common::test_params();
+    let frames: Vec<Vec<bool>> = (0..4).map(|i| common::frame_bits(&params, i)).collect();
+    let signal = common::modulated_frames(&params, &frames);
+
+    // Peaks past half of the search range would have a negative weight which is NaN in dB
+    let fft_bin = 1.0 / (params.nb_fft as f32);
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_max_range = 2.0 * 10.0 * fft_bin;
+    demod.settings.coarse_frequency_peak_distance_penalty = 2.0;
+    demod.settings.freeze_fine_time = true;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+    assert_eq!(demod.total_frames_read, 4);
+    assert_eq!(demod.coarse_frequency_offset, 0.0);
+    assert!(!demod.coarse_frequency_saturated);
+}
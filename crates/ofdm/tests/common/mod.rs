@@ -0,0 +1,68 @@
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use ofdm::ofdm_demodulator::OfdmDemodulator;
+use ofdm::ofdm_modulator::OfdmModulator;
+use ofdm::ofdm_parameters::OfdmParameters;
+use num::complex::Complex32;
+
+/// Small OFDM parameters so each frame only takes a few hundred samples.
+pub fn test_params() -> OfdmParameters {
+    OfdmParameters::new(4, 80, 72, 64, 48)
+}
+
+/// Carrier map that leaves the carriers in order.
+pub fn test_carrier_map(params: &OfdmParameters) -> Vec<usize> {
+    (0..params.nb_fft_data_carriers).collect()
+}
+
+/// Phase reference symbol with a pseudo random phase on every FFT bin.
+pub fn test_prs_fft(params: &OfdmParameters) -> Vec<Complex32> {
+    (0..params.nb_fft)
+        .map(|i| Complex32::from_polar(1.0, ((i*i) % 7) as f32))
+        .collect()
+}
+
+/// Creates a demodulator with the default settings for the test carrier map and PRS.
+pub fn test_demodulator(params: &OfdmParameters) -> OfdmDemodulator {
+    OfdmDemodulator::new(params, &test_carrier_map(params), &test_prs_fft(params))
+}
+
+/// Creates a modulator for the test carrier map and PRS.
+pub fn test_modulator(params: &OfdmParameters) -> OfdmModulator {
+    OfdmModulator::new(params, &test_carrier_map(params), &test_prs_fft(params))
+}
+
+/// Pseudo random bits for a frame that differ between frames.
+pub fn frame_bits(params: &OfdmParameters, frame: usize) -> Vec<bool> {
+    (0..params.nb_output_bits).map(|i| (i*i + 3*i + frame*frame) % 5 < 2).collect()
+}
+
+/// Creates a demodulator whose PRS is flat so the impulse response of the received PRS is the received PRS itself.
+pub fn flat_prs_demodulator(params: &OfdmParameters) -> OfdmDemodulator {
+    let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+    OfdmDemodulator::new(params, &test_carrier_map(params), &prs_fft)
+}
+
+/// Modulates the frames followed by the NULL symbol that ends the last frame and an extra sample so every frame is read.
+pub fn modulated_frames(params: &OfdmParameters, frames: &[Vec<bool>]) -> Vec<Complex32> {
+    let mut modulator = test_modulator(params);
+    let mut signal = Vec::new();
+    for bits in frames {
+        modulator.modulate_frame(bits, &mut signal);
+    }
+    signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+    signal
+}
+
+/// Shifts the signal by a frequency normalised to the sampling frequency.
+pub fn frequency_shift(signal: &mut [Complex32], frequency: f32) {
+    for (i, x) in signal.iter_mut().enumerate() {
+        *x *= Complex32::from_polar(1.0, 2.0*std::f32::consts::PI*frequency*(i as f32));
+    }
+}
+
+/// Returns whether the soft bits decide to the transmitted bits.
+pub fn is_bits_match(rx_bits: &[i8], tx_bits: &[bool]) -> bool {
+    rx_bits.len() == tx_bits.len() && rx_bits.iter().zip(tx_bits.iter()).all(|(rx, tx)| (*rx > 0) == *tx)
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn wrap_margin_hands_whole_bins_to_coarse_frequency() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let mut signal = common::modulated_frames(&params, &vec![bits.clone(); 40]);
+
+    // The carrier drifts by one and a half FFT bins across the signal
+    let fft_bin = 1.0 / (params.nb_fft as f32);
+    let drift_rate = 1.5 * fft_bin / (signal.len() as f32);
+    let mut phase = 0.0f64;
+    for (i, x) in signal.iter_mut().enumerate() {
+        *x *= Complex32::from_polar(1.0, (2.0 * std::f64::consts::PI * phase) as f32);
+        phase += (drift_rate * i as f32) as f64;
+    }
+
+    let run_drift = |wrap_margin: Option<f32>| {
+        let mut demod = common::test_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.fine_frequency_wrap_margin = wrap_margin;
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        demod.subscribe_frames_out({
+            let frames = frames.clone();
+            let bits = bits.clone();
+            move |rx_bits, info| {
+                let is_match = common::is_bits_match(rx_bits, &bits);
+                frames.lock().unwrap().push((info.net_frequency_offset / fft_bin, is_match));
+            }
+        });
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        let frames = frames.lock().unwrap().clone();
+        frames
+    };
+
+    // Wrapping keeps the offset within half a bin even though the carrier has drifted past it
+    // The whole bins are left for coarse frequency correction so the frames after the wrap are lost without it
+    let wrapped = run_drift(Some(1.01));
+    assert!(wrapped.len() > 30);
+    assert!(wrapped.iter().all(|(offset, _)| offset.abs() <= 0.505));
+    assert!(wrapped.iter().any(|(_, is_match)| !*is_match));
+
+    // Without wrapping the offset follows the carrier past one bin and every frame is demodulated
+    let unwrapped = run_drift(None);
+    assert_eq!(unwrapped.len(), 40);
+    assert!(unwrapped.last().unwrap().0 < -1.0);
+    assert!(unwrapped.iter().all(|(_, is_match)| *is_match));
+}
+
+#[test]
+fn frequency_offset_jitter_measures_alternating_offsets() {
+    // The FFT bins are 1kHz apart
+    let params = common::test_params().with_sample_rate(64e3);
+    let bits = common::frame_bits(&params, 0);
+
+    // The carrier is 100Hz off and swings by a number of Hz in alternate frames
+    let measure_jitter_hz = |swing_hz: f32| {
+        let mut signal = common::modulated_frames(&params, &vec![bits.clone(); 40]);
+        let mut phase = 0.0f32;
+        for (i, x) in signal.iter_mut().enumerate() {
+            let frequency_hz = match (i / params.nb_input_samples) % 2 {
+                0 => 100.0 + swing_hz,
+                _ => 100.0 - swing_hz,
+            };
+            phase += 2.0 * std::f32::consts::PI * params.hz_to_normalised(frequency_hz);
+            *x *= Complex32::from_polar(1.0, phase);
+        }
+
+        let mut demod = common::test_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        assert_eq!(demod.frequency_offset_jitter_hz(), 0.0);
+        demod.process(&signal);
+        assert_eq!(demod.total_frames_read, 40);
+        assert_eq!(demod.stats_snapshot().frequency_offset_jitter_hz, demod.frequency_offset_jitter_hz());
+        demod.frequency_offset_jitter_hz()
+    };
+    assert!(measure_jitter_hz(0.0) < 1.0);
+    assert!(measure_jitter_hz(50.0) > 25.0);
+}
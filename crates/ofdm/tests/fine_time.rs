@@ -0,0 +1,299 @@
+mod common;
+
+use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState, FineTimeCorrelationMethod, FineTimeImpulseScale, DemodError};
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn search_radius_ignores_distant_peaks() {
+    let params = common::test_params();
+
+    // The expected peak is right after the cyclic prefix and a larger spurious peak is far away from it
+    let mut signal = vec![Complex32::new(1e-3, 0.0); params.nb_null_period + params.nb_symbol_period + 1];
+    let prs_start = params.nb_null_period;
+    signal[prs_start + params.nb_cyclic_prefix] = Complex32::new(1.0, 0.0);
+    signal[prs_start + 50] = Complex32::new(1000.0, 0.0);
+
+    let run_fine_time_sync = |radius: Option<usize>| {
+        let mut demod = common::flat_prs_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.fine_time_search_radius = radius;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        assert!(matches!(demod.state, OfdmDemodulatorState::ReadingSymbols));
+        demod.fine_time_offset
+    };
+
+    assert_eq!(run_fine_time_sync(None), 50 - params.nb_cyclic_prefix as isize);
+    assert_eq!(run_fine_time_sync(Some(4)), 0);
+}
+
+#[test]
+fn every_impulse_scale_finds_the_same_peak() {
+    let params = common::test_params();
+
+    // The peak is shortly after the cyclic prefix above a noise floor that varies
+    let mut signal: Vec<Complex32> = (0..params.nb_null_period + params.nb_symbol_period + 1)
+        .map(|i| Complex32::new(1e-3 * (1.0 + (i % 7) as f32), 0.0))
+        .collect();
+    signal[params.nb_null_period + params.nb_cyclic_prefix + 3] = Complex32::new(1.0, 0.0);
+
+    let run_fine_time_sync = |scale: FineTimeImpulseScale| {
+        let mut demod = common::flat_prs_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.fine_time_impulse_scale = scale;
+        // A 64 point impulse response has at most 18dB of peak to average power
+        demod.settings.fine_time_impulse_peak_threshold_db = 10.0;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        assert!(matches!(demod.state, OfdmDemodulatorState::ReadingSymbols));
+        (demod.fine_time_offset, demod.fine_time_peak_height_db)
+    };
+
+    let (db_offset, db_height) = run_fine_time_sync(FineTimeImpulseScale::Decibel);
+    let (linear_offset, linear_height) = run_fine_time_sync(FineTimeImpulseScale::Linear);
+    let (power_offset, power_height) = run_fine_time_sync(FineTimeImpulseScale::Power);
+    assert_eq!(db_offset, 3);
+    assert_eq!(linear_offset, db_offset);
+    assert_eq!(power_offset, db_offset);
+    assert!(linear_height > 10.0);
+    // The peak to average power reads a few dB lower than the average of the decibel values
+    assert!(power_height > 10.0 && power_height < db_height);
+}
+
+#[test]
+fn direct_correlation_matches_fft_correlation() {
+    let params = common::test_params();
+    let carrier_map = common::test_carrier_map(&params);
+    let prs_fft: Vec<Complex32> = (0..params.nb_fft)
+        .map(|i| Complex32::from_polar(1.0, std::f32::consts::FRAC_PI_2 * ((i*i) % 4) as f32))
+        .collect();
+
+    // The PRS is delayed by a few samples with its cyclic prefix after a quiet NULL symbol
+    let mut prs_time = prs_fft.clone();
+    rustfft::FftPlanner::new().plan_fft_inverse(params.nb_fft).process(&mut prs_time);
+    let delay = 3;
+    let mut signal = vec![Complex32::new(1e-3, 0.0); params.nb_null_period + delay];
+    signal.extend_from_slice(&prs_time[params.nb_fft-params.nb_cyclic_prefix..]);
+    signal.extend_from_slice(&prs_time);
+    signal.push(Complex32::default());
+    // The sidelobes of a noiseless PRS can cancel exactly which makes their decibel values infinite
+    for (i, x) in signal.iter_mut().enumerate() {
+        *x += Complex32::from_polar(1e-2, (i % 17) as f32);
+    }
+
+    let run_fine_time_sync = |method: FineTimeCorrelationMethod, radius: Option<usize>| {
+        let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.fine_time_correlation_method = method;
+        demod.settings.fine_time_search_radius = radius;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal[..params.nb_null_period + params.nb_symbol_period + 1]);
+        assert!(matches!(demod.state, OfdmDemodulatorState::ReadingSymbols));
+        (demod.fine_time_offset, demod.fine_time_peak_height_db)
+    };
+
+    let (fft_offset, fft_height) = run_fine_time_sync(FineTimeCorrelationMethod::FftMultiply, None);
+    let (direct_offset, direct_height) = run_fine_time_sync(FineTimeCorrelationMethod::DirectTime, None);
+    assert_eq!(fft_offset, delay as isize);
+    assert_eq!(direct_offset, fft_offset);
+    assert!((direct_height - fft_height).abs() < 0.1);
+
+    // The direct method only calculates the lags inside the search window
+    let (windowed_offset, _) = run_fine_time_sync(FineTimeCorrelationMethod::DirectTime, Some(8));
+    assert_eq!(windowed_offset, fft_offset);
+}
+
+#[test]
+fn decibel_height_is_measured_from_the_weighted_peak() {
+    let params = common::test_params();
+
+    // The only peak is away from the expected location so its weight is below one
+    let mut signal: Vec<Complex32> = (0..params.nb_null_period + params.nb_symbol_period + 1)
+        .map(|i| Complex32::new(1e-2 * (1.0 + (i % 7) as f32), 0.0))
+        .collect();
+    let distance = 20;
+    signal[params.nb_null_period + params.nb_cyclic_prefix + distance] = Complex32::new(100.0, 0.0);
+
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.settings.fine_time_impulse_peak_threshold_db = 0.0;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+    assert_eq!(demod.fine_time_offset, distance as isize);
+
+    let impulse = &demod.fine_time_impulse_response_buffer;
+    let decay_weight = 1.0 - demod.settings.fine_time_impulse_peak_distance_probability;
+    let probability = 1.0 - decay_weight * (distance as f32) / (params.nb_symbol_period as f32);
+    let impulse_average = impulse.iter().sum::<f32>() / (impulse.len() as f32);
+    let expected_height = probability*impulse[params.nb_cyclic_prefix + distance] - impulse_average;
+    assert!((demod.fine_time_peak_height_db - expected_height).abs() < 1e-3);
+}
+
+#[test]
+fn silent_input_gives_finite_impulse_response_without_lock() {
+    let params = common::test_params();
+
+    // A degenerate input gives an all zero correlation which shouldn't produce NaNs or a false lock
+    let signal = vec![Complex32::new(0.0, 0.0); params.nb_null_period + params.nb_symbol_period + 1];
+    for scale in [FineTimeImpulseScale::Decibel, FineTimeImpulseScale::Linear, FineTimeImpulseScale::Power] {
+        let mut demod = common::flat_prs_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.fine_time_impulse_scale = scale;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        assert!(demod.fine_time_impulse_response_buffer.iter().all(|x| x.is_finite()));
+        assert!(demod.fine_time_peak_height_db.abs() < 1e-3);
+        assert!(matches!(demod.state, OfdmDemodulatorState::FindingNullPowerDip));
+    }
+}
+
+#[test]
+fn early_or_late_first_frame_keeps_later_frames_aligned() {
+    let params = common::test_params();
+    let frames: Vec<Vec<bool>> = (0..3).map(|i| common::frame_bits(&params, i)).collect();
+    let signal = common::modulated_frames(&params, &frames);
+
+    // Start reading part way through the first NULL symbol or before it so the first frame is early or late
+    for delay in [-5, 7] {
+        let rx: Vec<Complex32> = match delay {
+            d if d < 0 => signal[(-d) as usize..].to_vec(),
+            d => (0..d).map(|_| Complex32::default()).chain(signal.iter().copied()).collect(),
+        };
+        let mut demod = common::test_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        let rx_frames = Arc::new(Mutex::new(Vec::new()));
+        demod.subscribe_frames_out({
+            let rx_frames = rx_frames.clone();
+            move |bits, info| rx_frames.lock().unwrap().push((info.prs_start_sample, bits.to_vec()))
+        });
+        demod.process(&rx);
+
+        // Every frame is read from where its PRS actually starts
+        let rx_frames = rx_frames.lock().unwrap();
+        assert_eq!(rx_frames.len(), frames.len());
+        for (i, ((prs_start_sample, rx_bits), tx_bits)) in rx_frames.iter().zip(frames.iter()).enumerate() {
+            assert_eq!(*prs_start_sample as isize, (params.nb_null_period + i*params.nb_input_samples) as isize + delay);
+            assert!(common::is_bits_match(rx_bits, tx_bits));
+        }
+        assert_eq!(demod.total_frames_desync, 0);
+        assert_eq!(demod.fine_time_offset, 0);
+    }
+}
+
+#[test]
+fn max_offset_treats_large_jump_as_desync() {
+    let params = common::test_params();
+
+    // The peak is far away from the expected location
+    let mut signal = vec![Complex32::new(1e-3, 0.0); params.nb_null_period + params.nb_symbol_period + 1];
+    signal[params.nb_null_period + 50] = Complex32::new(1000.0, 0.0);
+    let offset = 50 - params.nb_cyclic_prefix as isize;
+
+    let run_fine_time_sync = |max_offset: Option<usize>| {
+        let mut demod = common::flat_prs_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.max_fine_time_offset = max_offset;
+        let captures = Arc::new(Mutex::new(Vec::new()));
+        demod.subscribe_desync_events({
+            let captures = captures.clone();
+            move |_, info| captures.lock().unwrap().push(info.prs_start_offset)
+        });
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        let captures = captures.lock().unwrap().clone();
+        (demod.state, captures)
+    };
+
+    assert_eq!(run_fine_time_sync(None), (OfdmDemodulatorState::ReadingSymbols, vec![]));
+    assert_eq!(run_fine_time_sync(Some(offset as usize)), (OfdmDemodulatorState::ReadingSymbols, vec![]));
+    assert_eq!(run_fine_time_sync(Some(10)), (OfdmDemodulatorState::FindingNullPowerDip, vec![offset]));
+}
+
+#[test]
+fn fractional_delay_correction_removes_residual_half_sample() {
+    let params = common::test_params();
+    let prs_fft = common::test_prs_fft(&params);
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+
+    // Averaging neighbouring samples delays the signal by exactly half a sample
+    let delayed: Vec<Complex32> = (0..signal.len())
+        .map(|i| 0.5*(signal[i] + if i > 0 { signal[i-1] } else { Complex32::default() }))
+        .collect();
+
+    let run_demodulator = |is_enabled: bool| {
+        let mut demod = common::test_demodulator(&params);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.fine_time_fractional_delay_is_enabled = is_enabled;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        demod.subscribe_bits_out({
+            let frames = frames.clone();
+            move |bits| frames.lock().unwrap().push(bits.to_vec())
+        });
+        demod.process(&delayed);
+        // A delay rotates the phase of the channel estimate between neighbouring carriers
+        let channel: Vec<Complex32> = demod.symbol_fft(0).iter().zip(prs_fft.iter()).map(|(x, y)| x * y.conj()).collect();
+        let phase_slope = (2..=params.nb_fft_data_carriers/2)
+            .map(|k| channel[k] * channel[k-1].conj())
+            .sum::<Complex32>()
+            .arg();
+        let frames = frames.lock().unwrap().clone();
+        (demod.fine_time_offset, demod.fine_time_offset_subsample, phase_slope, frames)
+    };
+
+    // The sidelobes of the PRS correlation make the interpolated offset slightly inexact
+    let (offset, subsample_offset, delayed_phase_slope, frames) = run_demodulator(false);
+    assert_eq!(offset, 0);
+    assert!((subsample_offset - 0.5).abs() < 0.05);
+    assert!((delayed_phase_slope + std::f32::consts::PI/(params.nb_fft as f32)).abs() < 1e-3);
+    assert_eq!(frames.len(), 1);
+    assert!(common::is_bits_match(&frames[0], &bits));
+
+    // Most of the residual half sample is removed while the bits are unchanged
+    let (_, _, phase_slope, frames) = run_demodulator(true);
+    assert!(phase_slope.abs() < 0.1*delayed_phase_slope.abs());
+    assert_eq!(frames.len(), 1);
+    assert!(common::is_bits_match(&frames[0], &bits));
+}
+
+#[test]
+fn try_process_returns_an_error_after_too_many_desyncs() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.settings.max_desyncs_before_error = Some(2);
+
+    // Require an impulse peak that noise can't reach so every fine time synchronisation fails
+    demod.settings.fine_time_impulse_peak_threshold_db = 100.0;
+    let signal: Vec<Complex32> = (0..params.nb_null_period+params.nb_symbol_period+1)
+        .map(|i| Complex32::from_polar(1.0, ((i*i*7 + i*3) % 31) as f32))
+        .collect();
+    let mut run_fine_time_sync = || {
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.try_process(&signal)
+    };
+
+    assert_eq!(run_fine_time_sync(), Ok(()));
+    assert_eq!(run_fine_time_sync(), Err(DemodError::TooManyDesyncs { total_desyncs: 2 }));
+    // The count starts again after the error
+    assert_eq!(run_fine_time_sync(), Ok(()));
+}
+
+#[test]
+fn process_into_returns_an_error_after_too_many_desyncs() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.settings.max_desyncs_before_error = Some(1);
+    demod.settings.fine_time_impulse_peak_threshold_db = 100.0;
+    let signal: Vec<Complex32> = (0..params.nb_null_period+params.nb_symbol_period+1)
+        .map(|i| Complex32::from_polar(1.0, ((i*i*7 + i*3) % 31) as f32))
+        .collect();
+
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let result = demod.process_into(&signal, &mut |_| panic!("No frame should be read"));
+    assert_eq!(result, Err(DemodError::TooManyDesyncs { total_desyncs: 1 }));
+}
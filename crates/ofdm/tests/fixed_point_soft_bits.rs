@@ -0,0 +1,68 @@
+mod common;
+
+use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState, calculate_soft_bits, calculate_soft_bits_fixed_point};
+use ofdm::ofdm_modulator::OfdmModulator;
+use num::complex::{Complex, Complex32};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn fixed_point_demodulator_keeps_weak_carriers_next_to_strong_ones() {
+    let params = common::test_params();
+    let carrier_map = common::test_carrier_map(&params);
+    // A few carriers just above DC are 60dB weaker than the rest
+    let mut prs_fft = common::test_prs_fft(&params);
+    for x in prs_fft[1..5].iter_mut() {
+        *x *= 1e-3;
+    }
+    let bits = common::frame_bits(&params, 0);
+    let mut signal = Vec::new();
+    OfdmModulator::new(&params, &carrier_map, &prs_fft).modulate_frame(&bits, &mut signal);
+    signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+
+    let get_soft_bits = |is_fixed_point: bool| {
+        let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.soft_bits_fixed_point_is_enabled = is_fixed_point;
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        demod.subscribe_bits_out({
+            let frames = frames.clone();
+            move |rx_bits| frames.lock().unwrap().push(rx_bits.to_vec())
+        });
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        let frame = frames.lock().unwrap().pop().unwrap();
+        frame
+    };
+
+    let float_bits = get_soft_bits(false);
+    let fixed_bits = get_soft_bits(true);
+    assert!(common::is_bits_match(&float_bits, &bits));
+    assert!(common::is_bits_match(&fixed_bits, &bits));
+    for (a, b) in float_bits.iter().zip(fixed_bits.iter()) {
+        assert_eq!(a.signum(), b.signum());
+        assert!((*a as i32 - *b as i32).abs() <= 1, "float={} fixed={}", a, b);
+    }
+}
+
+#[test]
+fn fixed_point_soft_bits_match_float_soft_bits() {
+    let mut seed = 7u32;
+    let mut random = || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (seed >> 16) as u16 as i16
+    };
+    // Small values check that the reciprocal is accurate over the whole range of the shift
+    let mut x: Vec<Complex<i16>> = (0..4096).map(|_| Complex::new(random(), random())).collect();
+    x.extend((0..4096).map(|_| Complex::new(random() >> 10, random() >> 12)));
+    x.extend([Complex::new(i16::MIN, i16::MAX), Complex::new(1, -1), Complex::new(0, 5), Complex::new(-3, 0)]);
+
+    let x_float: Vec<Complex32> = x.iter().map(|x| Complex32::new(x.re as f32, x.im as f32)).collect();
+    let mut y_float = vec![0i8; 2*x.len()];
+    let mut y_fixed = vec![0i8; 2*x.len()];
+    calculate_soft_bits(&x_float, &mut y_float);
+    calculate_soft_bits_fixed_point(&x, &mut y_fixed);
+    for (a, b) in y_float.iter().zip(y_fixed.iter()) {
+        assert_eq!(a.signum(), b.signum());
+        assert!((*a as i32 - *b as i32).abs() <= 1, "float={} fixed={}", a, b);
+    }
+}
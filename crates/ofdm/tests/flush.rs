@@ -0,0 +1,68 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn flush_outputs_partial_frame_to_the_callbacks() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_frames_out({
+        let frames = frames.clone();
+        move |bits, info| frames.lock().unwrap().push((bits.to_vec(), info.is_partial))
+    });
+
+    // The input ends after the PRS and two of the three data symbols of the frame
+    let total_samples = params.nb_null_period + 3*params.nb_symbol_period + 10;
+    demod.process(&signal[..total_samples]);
+    assert!(frames.lock().unwrap().is_empty());
+
+    assert!(demod.flush());
+    {
+        let frames = frames.lock().unwrap();
+        assert_eq!(frames.len(), 1);
+        assert!(common::is_bits_match(&frames[0].0, &bits[..2*2*params.nb_fft_data_carriers]));
+        assert!(frames[0].1);
+    }
+    assert_eq!(demod.state, OfdmDemodulatorState::FindingNullPowerDip);
+
+    // There is nothing left to flush
+    assert!(!demod.flush());
+    assert_eq!(frames.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn flush_into_passes_partial_frame_to_the_sink() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let callback_frames = Arc::new(Mutex::new(0));
+    demod.subscribe_bits_out({
+        let callback_frames = callback_frames.clone();
+        move |_| *callback_frames.lock().unwrap() += 1
+    });
+
+    // The input ends after the PRS and two of the three data symbols of the frame
+    let mut frames = Vec::new();
+    let total_samples = params.nb_null_period + 3*params.nb_symbol_period + 10;
+    demod.process_into(&signal[..total_samples], &mut |rx_bits| frames.push(rx_bits.to_vec())).unwrap();
+    assert!(frames.is_empty());
+
+    assert!(demod.flush_into(&mut |rx_bits| frames.push(rx_bits.to_vec())));
+    assert_eq!(frames.len(), 1);
+    assert!(common::is_bits_match(&frames[0], &bits[..2*2*params.nb_fft_data_carriers]));
+    assert_eq!(*callback_frames.lock().unwrap(), 0);
+
+    // There is nothing left to flush
+    assert!(!demod.flush_into(&mut |rx_bits| frames.push(rx_bits.to_vec())));
+}
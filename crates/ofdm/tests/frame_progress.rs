@@ -0,0 +1,24 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+
+#[test]
+fn frame_progress_increases_while_reading_symbols() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    assert_eq!(demod.frame_progress(), 0.0);
+    assert_eq!(demod.state_name(), "Finding NULL power dip");
+
+    // Read the NULL, the PRS and part of the first data symbol
+    demod.settings.fine_time_impulse_peak_threshold_db = 0.0;
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let signal: Vec<Complex32> = (0..params.nb_null_period + params.nb_symbol_period + 30)
+        .map(|i| Complex32::from_polar(1.0, (i % 13) as f32))
+        .collect();
+    demod.process(&signal);
+    assert_eq!(demod.state_name(), "Reading symbols");
+    let progress = demod.frame_progress();
+    assert!(progress > 0.0 && progress < 1.0);
+}
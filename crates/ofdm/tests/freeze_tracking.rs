@@ -0,0 +1,29 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+
+#[test]
+fn frozen_loops_hold_their_manual_values() {
+    let params = common::test_params();
+
+    // The impulse peak is shortly after the cyclic prefix
+    let mut signal = vec![Complex32::new(1e-3, 0.0); params.nb_null_period + params.nb_symbol_period + 1];
+    signal[params.nb_null_period + params.nb_cyclic_prefix + 3] = Complex32::new(1.0, 0.0);
+
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.freeze_coarse_frequency = true;
+    demod.settings.manual_coarse_frequency_offset = 2.0 / (params.nb_fft as f32);
+    demod.settings.freeze_fine_frequency = true;
+    demod.settings.manual_fine_frequency_offset = 0.1 / (params.nb_fft as f32);
+    demod.settings.freeze_fine_time = true;
+    demod.settings.manual_fine_time_offset = -2;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+
+    // The held values are used even though the impulse peak says otherwise
+    assert!(matches!(demod.state, OfdmDemodulatorState::ReadingSymbols));
+    assert_eq!(demod.coarse_frequency_offset, demod.settings.manual_coarse_frequency_offset);
+    assert_eq!(demod.fine_frequency_offset, demod.settings.manual_fine_frequency_offset);
+    assert_eq!(demod.fine_time_offset, -2);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use ofdm::ofdm_modulator::OfdmModulator;
+use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn disabling_deinterleaving_outputs_carriers_in_physical_order() {
+    let params = common::test_params();
+    let nb_carriers = params.nb_fft_data_carriers;
+    // The test carrier map leaves the carriers in order so it can't tell the two orders apart
+    let carrier_map: Vec<usize> = (0..nb_carriers).map(|i| (7*i + 3) % nb_carriers).collect();
+    let prs_fft = common::test_prs_fft(&params);
+    let bits = common::frame_bits(&params, 0);
+    let mut signal = Vec::new();
+    OfdmModulator::new(&params, &carrier_map, &prs_fft).modulate_frame(&bits, &mut signal);
+    signal.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+
+    let get_soft_bits = |is_deinterleaved: bool| {
+        let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+        demod.settings.coarse_frequency_is_enabled = false;
+        demod.settings.frequency_deinterleaving_is_enabled = is_deinterleaved;
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        demod.subscribe_bits_out({
+            let frames = frames.clone();
+            move |bits| frames.lock().unwrap().push(bits.to_vec())
+        });
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        demod.process(&signal);
+        let frame = frames.lock().unwrap().pop().unwrap();
+        frame
+    };
+    let logical = get_soft_bits(true);
+    let physical = get_soft_bits(false);
+    assert!(common::is_bits_match(&logical, &bits));
+    assert_ne!(logical, physical);
+
+    // Each symbol is permuted by the carrier map in both the real and imaginary halves
+    for (y_logical, y_physical) in logical.chunks_exact(2*nb_carriers).zip(physical.chunks_exact(2*nb_carriers)) {
+        for (i, i_mapped) in carrier_map.iter().enumerate() {
+            assert_eq!(y_logical[i], y_physical[*i_mapped]);
+            assert_eq!(y_logical[nb_carriers+i], y_physical[nb_carriers+i_mapped]);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn agc_brings_different_levels_to_the_target() {
+    let params = common::test_params();
+
+    // Inputs that are 100 times apart in level end up with the same L1 average
+    let get_agc = |level: f32| {
+        let mut demod = common::test_demodulator(&params);
+        demod.settings.agc_enabled = true;
+        demod.settings.agc_target_level = 32.0;
+        let signal: Vec<Complex32> = (0..100000)
+            .map(|i| Complex32::from_polar(level, (i % 13) as f32))
+            .collect();
+        for chunk in signal.chunks(10000) {
+            demod.process(chunk);
+        }
+        (demod.signal_l1_average, demod.agc_gain())
+    };
+    let (quiet_l1_average, quiet_gain) = get_agc(0.1);
+    let (loud_l1_average, loud_gain) = get_agc(10.0);
+    assert!((quiet_l1_average - 32.0).abs() < 0.5);
+    assert!((loud_l1_average - 32.0).abs() < 0.5);
+    assert!((quiet_gain / loud_gain - 100.0).abs() < 1.0);
+}
+
+#[test]
+fn non_finite_samples_are_replaced_and_counted() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let mut signal = common::modulated_frames(&params, &vec![bits.clone(); 4]);
+
+    // NaNs inside the PRS of the second frame and infinities inside the symbols of the third frame
+    let frame_start = |i: usize| i*params.nb_input_samples + params.nb_null_period;
+    signal[frame_start(1)+20..frame_start(1)+28].fill(Complex32::new(f32::NAN, 0.0));
+    signal[frame_start(2)+200..frame_start(2)+204].fill(Complex32::new(f32::INFINITY, f32::NAN));
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_bits_out({
+        let frames = frames.clone();
+        move |rx_bits| frames.lock().unwrap().push(rx_bits.to_vec())
+    });
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    for chunk in signal.chunks(100) {
+        demod.process(chunk);
+    }
+
+    // Every frame stays locked to the real impulse peak and the garbage is reported
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), 4);
+    assert!(frames.iter().all(|rx_bits| common::is_bits_match(rx_bits, &bits)));
+    assert_eq!(demod.fine_time_offset, 0);
+    assert_eq!(demod.total_non_finite_samples, 12);
+}
+
+#[test]
+fn unsanitised_non_finite_samples_do_not_stop_the_demodulator() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.null_power_total_samples = 100;
+    demod.settings.dc_offset_correction_enabled = true;
+    demod.settings.non_finite_sanitisation_enabled = false;
+    let values = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 0.0, 1.0, -1e30];
+    let corrupted: Vec<Complex32> = (0..5000)
+        .map(|i| Complex32::new(values[i % values.len()], values[(i*7) % values.len()]))
+        .collect();
+    for chunk in corrupted.chunks(333) {
+        demod.process(chunk);
+    }
+    assert!(demod.signal_l1_average.is_finite());
+    assert!(demod.dc_offset().is_finite());
+
+    // The demodulator finds the NULL symbol once the samples are valid again
+    demod.settings.dc_offset_correction_enabled = false;
+    let signal: Vec<Complex32> = (0..1450)
+        .map(|i| if (1000..1300).contains(&i) { 0.0 } else { 1.0 })
+        .map(|x| Complex32::new(x, 0.0))
+        .collect();
+    demod.process(&signal);
+    assert_eq!(demod.state, OfdmDemodulatorState::ReadingNullAndPrs);
+}
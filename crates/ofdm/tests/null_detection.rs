@@ -0,0 +1,122 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+
+#[test]
+fn min_dip_blocks_rejects_brief_fades() {
+    let params = common::test_params();
+    let signal = |length: usize| (0..length).map(|i| Complex32::from_polar(1.0, ((i*i) % 13) as f32));
+    let silence = |length: usize| (0..length).map(|_| Complex32::default());
+
+    // The fade lasts for two blocks and the NULL symbol for many more
+    // Only the start of the PRS follows so the demodulator stays in the next state once the NULL symbol is detected
+    let block_size = 3;
+    let fade: Vec<Complex32> = signal(300).chain(silence(2*block_size)).chain(signal(10)).collect();
+    let null: Vec<Complex32> = signal(300).chain(silence(params.nb_null_period)).chain(signal(10)).collect();
+
+    let run_null_detection = |min_dip_blocks: usize| {
+        let mut demod = common::flat_prs_demodulator(&params);
+        demod.settings.null_power_total_samples = block_size;
+        demod.settings.null_power_min_dip_blocks = min_dip_blocks;
+        let mut is_detected = Vec::new();
+        for samples in [&fade, &null] {
+            demod.reset();
+            // Learn the average power of the signal first
+            for _ in 0..10 {
+                demod.process(&signal(300).collect::<Vec<_>>());
+            }
+            assert_eq!(demod.state, OfdmDemodulatorState::FindingNullPowerDip);
+            demod.process(samples);
+            is_detected.push(demod.state != OfdmDemodulatorState::FindingNullPowerDip);
+        }
+        is_detected
+    };
+
+    // A single block below the threshold mistakes the fade for a NULL symbol
+    assert_eq!(run_null_detection(1), [true, true]);
+    assert_eq!(run_null_detection(4), [false, true]);
+}
+
+#[test]
+fn consecutive_desyncs_widen_the_null_search_until_the_next_lock() {
+    let params = common::test_params();
+    let frames: Vec<Vec<bool>> = (0..10).map(|i| common::frame_bits(&params, i)).collect();
+    let clean = common::modulated_frames(&params, &frames);
+    let prs_amplitude = clean[params.nb_null_period..params.nb_input_samples].iter().map(|x| x.norm()).sum::<f32>() / (params.nb_symbol_period as f32);
+    // Noise in place of every PRS makes each fine time synchronisation fail
+    let mut desyncs = clean.clone();
+    for frame in 0..frames.len() {
+        let prs_start = frame*params.nb_input_samples + params.nb_null_period;
+        for (i, x) in desyncs[prs_start..prs_start+params.nb_symbol_period].iter_mut().enumerate() {
+            *x = Complex32::from_polar(prs_amplitude, ((i*i) % 11) as f32);
+        }
+    }
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    // The signal average is updated once per call so a full reset needs the following chunks to learn it again
+    for chunk in desyncs.chunks(params.nb_input_samples) {
+        demod.process(chunk);
+    }
+    assert_eq!(demod.total_frames_read, 0);
+    assert!(demod.is_null_search_widened);
+
+    let is_locked = clean.chunks(params.nb_symbol_period).any(|chunk| {
+        demod.process(chunk);
+        demod.state == OfdmDemodulatorState::ReadingSymbols
+    });
+    assert!(is_locked);
+    assert!(!demod.is_null_search_widened);
+}
+
+#[test]
+fn widened_null_search_detects_shallow_dips() {
+    let params = common::test_params();
+    let signal = |length: usize| (0..length).map(|i| Complex32::from_polar(1.0, ((i*i) % 13) as f32));
+    let shallow = |length: usize| (0..length).map(|i| Complex32::from_polar(0.45, ((i*i) % 13) as f32));
+    let null: Vec<Complex32> = signal(300).chain(shallow(params.nb_null_period)).chain(signal(10)).collect();
+
+    let run_null_detection = |is_null_search_widened: bool| {
+        let mut demod = common::flat_prs_demodulator(&params);
+        // Learn the average power of the signal first
+        for _ in 0..10 {
+            demod.process(&signal(300).collect::<Vec<_>>());
+        }
+        assert_eq!(demod.state, OfdmDemodulatorState::FindingNullPowerDip);
+        demod.is_null_search_widened = is_null_search_widened;
+        demod.process(&null);
+        demod.state != OfdmDemodulatorState::FindingNullPowerDip
+    };
+
+    assert!(!run_null_detection(false));
+    assert!(run_null_detection(true));
+}
+
+#[test]
+fn chunked_input_finds_the_same_null_as_the_whole_input() {
+    let params = common::test_params();
+    let create_demodulator = || {
+        let mut demod = common::flat_prs_demodulator(&params);
+        demod.settings.null_power_total_samples = 100;
+        demod.signal_l1_average = 1.0;
+        demod
+    };
+
+    // Signal with a NULL symbol in the middle
+    let signal: Vec<Complex32> = (0..1450)
+        .map(|i| if (1000..1300).contains(&i) { 0.0 } else { 1.0 })
+        .map(|x| Complex32::new(x, 0.0))
+        .collect();
+
+    let mut demod_single = create_demodulator();
+    demod_single.process(&signal);
+    let mut demod_chunked = create_demodulator();
+    for chunk in signal.chunks(7) {
+        demod_chunked.process(chunk);
+    }
+
+    assert_eq!(demod_single.state, OfdmDemodulatorState::ReadingNullAndPrs);
+    assert_eq!(demod_chunked.state, OfdmDemodulatorState::ReadingNullAndPrs);
+    assert_eq!(demod_single.null_prs_buffer.iter(), demod_chunked.null_prs_buffer.iter());
+}
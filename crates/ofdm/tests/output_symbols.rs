@@ -0,0 +1,66 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn max_output_symbols_only_outputs_the_first_symbols() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.settings.max_output_symbols = Some(1);
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_bits_out({
+        let frames = frames.clone();
+        move |bits| frames.lock().unwrap().push(bits.to_vec())
+    });
+    demod.process(&signal);
+
+    // Only the bits of the first DQPSK symbol are outputted
+    let nb_symbol_bits = 2*params.nb_fft_data_carriers;
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), 1);
+    assert!(common::is_bits_match(&frames[0], &bits[..nb_symbol_bits]));
+    assert_eq!(demod.frame_soft_bits_by_symbol().count(), 1);
+}
+
+#[test]
+fn symbol_streaming_outputs_each_symbol_before_the_frame() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.settings.symbol_streaming_is_enabled = true;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let symbols = Arc::new(Mutex::new(Vec::new()));
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_symbols_out({
+        let symbols = symbols.clone();
+        move |bits, info| symbols.lock().unwrap().push((info.symbol_index, bits.to_vec()))
+    });
+    demod.subscribe_bits_out({
+        let frames = frames.clone();
+        move |bits| frames.lock().unwrap().push(bits.to_vec())
+    });
+
+    // The first DQPSK symbol is outputted once the first data symbol after the PRS is read
+    let nb_symbol_bits = 2*params.nb_fft_data_carriers;
+    let first_symbol_end = params.nb_null_period + 2*params.nb_symbol_period;
+    demod.process(&signal[..first_symbol_end]);
+    assert_eq!(symbols.lock().unwrap().len(), 1);
+    assert!(common::is_bits_match(&symbols.lock().unwrap()[0].1, &bits[..nb_symbol_bits]));
+    assert!(frames.lock().unwrap().is_empty());
+
+    // The whole frame is outputted with the same bits once it is complete
+    demod.process(&signal[first_symbol_end..]);
+    let symbols = symbols.lock().unwrap();
+    let frames = frames.lock().unwrap();
+    assert_eq!(symbols.iter().map(|(index, _)| *index).collect::<Vec<_>>(), (0..params.nb_symbols-1).collect::<Vec<_>>());
+    assert_eq!(symbols.iter().flat_map(|(_, bits)| bits.iter().copied()).collect::<Vec<i8>>(), frames[0]);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+use ofdm::ofdm_parameters::OfdmParameters;
+use num::complex::Complex32;
+use rustfft::FftPlanner;
+use std::f32::consts::PI;
+
+#[test]
+fn rolloff_reduces_phase_error_of_doppler_shifted_echo() {
+    // The cyclic prefix is as long as the FFT so the window has room to roll off
+    let params = OfdmParameters::new(4, 96, 96, 64, 48);
+    let carrier_map = common::test_carrier_map(&params);
+    let prs_fft = vec![Complex32::new(1.0, 0.0); params.nb_fft];
+
+    // Frames of random DQPSK symbols after a flat PRS
+    let ifft = FftPlanner::new().plan_fft_inverse(params.nb_fft);
+    let mut tx = Vec::new();
+    let mut seed = 1u32;
+    for _ in 0..8 {
+        tx.extend((0..params.nb_null_period).map(|_| Complex32::default()));
+        let mut symbol = prs_fft.clone();
+        for i in 0..params.nb_symbols {
+            if i > 0 {
+                for x in &mut symbol {
+                    seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                    *x *= Complex32::from_polar(1.0, PI/4.0 + ((seed >> 30) as f32)*PI/2.0);
+                }
+            }
+            let mut time = symbol.clone();
+            ifft.process(&mut time);
+            tx.extend_from_slice(&time[params.nb_fft-params.nb_cyclic_prefix..]);
+            tx.extend_from_slice(&time);
+        }
+    }
+    tx.extend((0..params.nb_null_period+1).map(|_| Complex32::default()));
+
+    // The echo is Doppler shifted by a fraction of the carrier spacing so it leaks into the neighbouring carriers
+    let (delay, gain, doppler) = (2, 0.5, 2.0/3.0);
+    let rx: Vec<Complex32> = (0..tx.len())
+        .map(|n| {
+            let echo = if n >= delay { tx[n-delay] } else { Complex32::default() };
+            tx[n] + gain * echo * Complex32::from_polar(1.0, 2.0*PI*doppler*(n as f32)/(params.nb_fft as f32))
+        })
+        .collect();
+
+    // The RMS phase error of the DQPSK constellations
+    let get_phase_error = |rolloff: f32| {
+        let mut demod = OfdmDemodulator::new(&params, &carrier_map, &prs_fft);
+        demod.settings.freeze_coarse_frequency = true;
+        demod.settings.freeze_fine_frequency = true;
+        demod.settings.fine_time_impulse_peak_threshold_db = 0.0;
+        demod.settings.prefix_window_rolloff = rolloff;
+        demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+        let mut errors = Vec::new();
+        for chunk in rx.chunks(params.nb_input_samples) {
+            let total_frames = demod.total_frames_read;
+            demod.process(chunk);
+            if demod.total_frames_read > total_frames {
+                errors.extend(demod.dqpsk_symbols().flatten().map(|x| x.arg().rem_euclid(PI/2.0) - PI/4.0));
+            }
+        }
+        assert_eq!(demod.total_frames_read, 8);
+        (errors.iter().map(|x| x*x).sum::<f32>() / (errors.len() as f32)).sqrt()
+    };
+    assert!(get_phase_error(0.5) < 0.95*get_phase_error(0.0));
+}
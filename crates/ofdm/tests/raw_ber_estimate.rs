@@ -0,0 +1,35 @@
+use ofdm::ofdm_demodulator::calculate_raw_ber_estimate;
+use num::complex::Complex32;
+
+/// Splitmix64 hash of the index as a uniform number in [0,1).
+fn random(i: usize) -> f32 {
+    let mut z = (i as u64).wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    ((z ^ (z >> 31)) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn qpsk(i: usize) -> Complex32 {
+    Complex32::from_polar(1.0, std::f32::consts::FRAC_PI_4 * (1 + 2*(i*i*31 % 4)) as f32)
+}
+
+#[test]
+fn small_phase_errors_never_disagree() {
+    let nb_carriers = 64;
+    // Phase errors smaller than half of the decision distance never disagree
+    let clean: Vec<Complex32> = (0..nb_carriers*8)
+        .map(|i| qpsk(i) * Complex32::from_polar(1.0, 0.3*(random(i)-0.5)))
+        .collect();
+    assert_eq!(calculate_raw_ber_estimate(&clean, nb_carriers), 0.0);
+}
+
+#[test]
+fn random_phases_estimate_an_eighth_of_the_bits_in_error() {
+    let nb_carriers = 64;
+    // Uniformly random phases cross a decision boundary for a quarter of the pairs which flips one of the two bits
+    let noise: Vec<Complex32> = (0..nb_carriers*200)
+        .map(|i| Complex32::from_polar(1.0, std::f32::consts::TAU * random(i)))
+        .collect();
+    let noise_estimate = calculate_raw_ber_estimate(&noise, nb_carriers);
+    assert!((noise_estimate - 0.125).abs() < 0.01, "Estimate of {} for noise", noise_estimate);
+}
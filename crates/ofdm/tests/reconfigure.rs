@@ -0,0 +1,93 @@
+mod common;
+
+use ofdm::ofdm_demodulator::{OfdmDemodulator, OfdmDemodulatorState};
+use ofdm::ofdm_fft::{OfdmFft, RustFft};
+use ofdm::ofdm_parameters::OfdmParameters;
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// rustfft wrapped so the tests can tell which FFT the demodulator uses.
+struct CountingFft {
+    fft: RustFft,
+    total_calls: AtomicUsize,
+}
+
+impl CountingFft {
+    fn new(nb_fft: usize) -> Arc<Self> {
+        Arc::new(Self { fft: RustFft::new(nb_fft), total_calls: AtomicUsize::new(0) })
+    }
+}
+
+impl OfdmFft for CountingFft {
+    fn forward(&self, buf: &mut [Complex32]) {
+        self.total_calls.fetch_add(1, Ordering::Relaxed);
+        self.fft.forward(buf);
+    }
+
+    fn inverse(&self, buf: &mut [Complex32]) {
+        self.total_calls.fetch_add(1, Ordering::Relaxed);
+        self.fft.inverse(buf);
+    }
+}
+
+/// Twice the FFT size of the test parameters.
+fn larger_params() -> OfdmParameters {
+    OfdmParameters::new(4, 160, 144, 128, 96)
+}
+
+fn custom_fft_demodulator(params: &OfdmParameters, fft: Arc<CountingFft>) -> OfdmDemodulator {
+    OfdmDemodulator::try_with_fft(params, &common::test_carrier_map(params), &common::test_prs_fft(params), fft).unwrap()
+}
+
+#[test]
+fn reconfigure_with_fft_uses_the_new_fft() {
+    let params = common::test_params();
+    let old_fft = CountingFft::new(params.nb_fft);
+    let mut demod = custom_fft_demodulator(&params, old_fft.clone());
+
+    let params = larger_params();
+    let new_fft = CountingFft::new(params.nb_fft);
+    demod.reconfigure_with_fft(&params, &common::test_carrier_map(&params), &common::test_prs_fft(&params), new_fft.clone());
+    let old_fft_calls = old_fft.total_calls.load(Ordering::Relaxed);
+
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_bits_out({
+        let frames = frames.clone();
+        move |rx_bits| frames.lock().unwrap().push(rx_bits.to_vec())
+    });
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), 1);
+    assert!(common::is_bits_match(&frames[0], &bits));
+    assert_eq!(old_fft.total_calls.load(Ordering::Relaxed), old_fft_calls);
+    assert!(new_fft.total_calls.load(Ordering::Relaxed) > params.nb_symbols);
+}
+
+#[test]
+fn reconfigure_keeps_custom_fft_of_the_same_size() {
+    let params = common::test_params();
+    let fft = CountingFft::new(params.nb_fft);
+    let mut demod = custom_fft_demodulator(&params, fft.clone());
+    demod.reconfigure(&params, &common::test_carrier_map(&params), &common::test_prs_fft(&params));
+
+    let calls_before = fft.total_calls.load(Ordering::Relaxed);
+    let signal = common::modulated_frames(&params, &[common::frame_bits(&params, 0)]);
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+    assert!(fft.total_calls.load(Ordering::Relaxed) > calls_before);
+}
+
+#[test]
+#[should_panic(expected = "use reconfigure_with_fft()")]
+fn reconfigure_rejects_custom_fft_of_another_size() {
+    let params = common::test_params();
+    let mut demod = custom_fft_demodulator(&params, CountingFft::new(params.nb_fft));
+    let params = larger_params();
+    demod.reconfigure(&params, &common::test_carrier_map(&params), &common::test_prs_fft(&params));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn replay_does_not_invoke_subscribers() {
+    let params = common::test_params();
+    let frames: Vec<Vec<bool>> = (0..3).map(|i| common::frame_bits(&params, i)).collect();
+    let mut signal = common::modulated_frames(&params, &frames);
+    // Noise in place of the PRS of the last frame forces a desync
+    let last_prs = 2*params.nb_input_samples + params.nb_null_period;
+    for (i, x) in signal[last_prs..last_prs+params.nb_symbol_period].iter_mut().enumerate() {
+        *x = Complex32::from_polar(1.0, ((i*i) % 11) as f32);
+    }
+
+    let mut demod = common::test_demodulator(&params);
+    demod.set_replay_length(signal.len());
+    let counts = Arc::new(Mutex::new([0usize; 5]));
+    demod.subscribe_bits_out({
+        let counts = counts.clone();
+        move |_| counts.lock().unwrap()[0] += 1
+    });
+    demod.subscribe_prs_out({
+        let counts = counts.clone();
+        move |_| counts.lock().unwrap()[1] += 1
+    });
+    demod.subscribe_desync_capture({
+        let counts = counts.clone();
+        move |_| counts.lock().unwrap()[2] += 1
+    });
+    demod.subscribe_symbols_out({
+        let counts = counts.clone();
+        move |_, _| counts.lock().unwrap()[3] += 1
+    });
+    demod.subscribe_process_trace({
+        let counts = counts.clone();
+        move |_, _| counts.lock().unwrap()[4] += 1
+    });
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+    let processed = *counts.lock().unwrap();
+    assert!(processed.iter().all(|count| *count > 0), "Every subscriber should be invoked: {:?}", processed);
+
+    demod.reprocess_last();
+    assert_eq!(*counts.lock().unwrap(), processed);
+
+    // The subscribers are restored after the replay
+    demod.process(&signal);
+    assert!(counts.lock().unwrap().iter().zip(processed.iter()).all(|(count, processed)| count > processed));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use ofdm::ofdm_demodulator::OfdmDemodulatorState;
+use num::complex::Complex32;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn unsubscribed_bits_out_callbacks_are_not_called() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.fine_time_impulse_peak_threshold_db = 0.0;
+    demod.settings.coarse_frequency_is_enabled = false;
+
+    // Rebind the output as if the receiver was retuned
+    let outputs = Arc::new(Mutex::new(Vec::new()));
+    let old_id = demod.subscribe_bits_out({
+        let outputs = outputs.clone();
+        move |_| outputs.lock().unwrap().push("old")
+    });
+    let new_id = demod.subscribe_bits_out({
+        let outputs = outputs.clone();
+        move |_| outputs.lock().unwrap().push("new")
+    });
+    assert_ne!(old_id, new_id);
+    demod.unsubscribe_bits_out(old_id);
+    demod.unsubscribe_bits_out(old_id);
+
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let total_samples = 2*params.nb_null_period + params.nb_symbols*params.nb_symbol_period + 1;
+    let signal: Vec<Complex32> = (0..total_samples)
+        .map(|i| Complex32::from_polar(1.0, (i % 13) as f32))
+        .collect();
+    demod.process(&signal);
+    assert_eq!(demod.total_frames_read, 1);
+    assert_eq!(*outputs.lock().unwrap(), ["new"]);
+}
+
+#[test]
+fn process_trace_follows_the_states_of_a_frame() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let mut signal = Vec::new();
+    common::test_modulator(&params).modulate_frame(&bits, &mut signal);
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_process_trace({
+        let trace = trace.clone();
+        move |state, total_read| trace.lock().unwrap().push((*state, total_read))
+    });
+    demod.process(&signal);
+
+    use OfdmDemodulatorState::*;
+    let nb_data_samples = (params.nb_symbols-1)*params.nb_symbol_period;
+    assert_eq!(*trace.lock().unwrap(), vec![
+        (ReadingNullAndPrs, params.nb_null_period + params.nb_symbol_period),
+        (RunningCoarseFrequencySynchronisation, 0),
+        (RunningFineTimeSync, 0),
+        (ReadingSymbols, nb_data_samples),
+    ]);
+}
+
+#[test]
+fn prs_out_is_the_input_aligned_to_the_fine_time_offset() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    let prs_captures = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_prs_out({
+        let prs_captures = prs_captures.clone();
+        move |prs| prs_captures.lock().unwrap().push(prs.to_vec())
+    });
+
+    demod.settings.fine_time_impulse_peak_threshold_db = 0.0;
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let total_samples = 2*params.nb_null_period + params.nb_symbols*params.nb_symbol_period + 1;
+    let signal: Vec<Complex32> = (0..total_samples)
+        .map(|i| Complex32::from_polar(1.0, (i % 13) as f32))
+        .collect();
+    demod.process(&signal);
+
+    // There is no frequency offset so the PRS is the input aligned to the fine time offset
+    let prs_start = (params.nb_null_period as isize + demod.fine_time_offset) as usize;
+    assert_eq!(demod.total_frames_read, 1);
+    assert_eq!(*prs_captures.lock().unwrap(), [&signal[prs_start..prs_start+params.nb_symbol_period]]);
+}
+
+#[test]
+fn desync_capture_receives_the_searched_null_and_prs() {
+    let params = common::test_params();
+    let mut demod = common::flat_prs_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    let captures = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_desync_capture({
+        let captures = captures.clone();
+        move |buf| captures.lock().unwrap().push(buf.to_vec())
+    });
+
+    // Require an impulse peak that noise can't reach so fine time synchronisation fails
+    demod.settings.fine_time_impulse_peak_threshold_db = 100.0;
+    let nb_null_prs = params.nb_null_period + params.nb_symbol_period;
+    let signal: Vec<Complex32> = (0..nb_null_prs+1)
+        .map(|i| Complex32::from_polar(1.0, ((i*i*7 + i*3) % 31) as f32))
+        .collect();
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    demod.process(&signal);
+
+    assert_eq!(demod.total_frames_desync, 1);
+    assert_eq!(*captures.lock().unwrap(), [&signal[..nb_null_prs]]);
+}
+
+#[test]
+fn process_into_skips_only_the_bits_out_callbacks() {
+    let params = common::test_params();
+    let bits = common::frame_bits(&params, 0);
+    let signal = common::modulated_frames(&params, std::slice::from_ref(&bits));
+
+    let mut demod = common::test_demodulator(&params);
+    demod.settings.coarse_frequency_is_enabled = false;
+    demod.state = OfdmDemodulatorState::ReadingNullAndPrs;
+    let outputs = Arc::new(Mutex::new(Vec::new()));
+    demod.subscribe_bits_out({
+        let outputs = outputs.clone();
+        move |_| outputs.lock().unwrap().push("bits")
+    });
+    demod.subscribe_frames_out({
+        let outputs = outputs.clone();
+        move |_, _| outputs.lock().unwrap().push("frame")
+    });
+    demod.subscribe_prs_out({
+        let outputs = outputs.clone();
+        move |_| outputs.lock().unwrap().push("prs")
+    });
+
+    let mut frames = Vec::new();
+    demod.process_into(&signal, &mut |rx_bits| frames.push(rx_bits.to_vec())).unwrap();
+    assert_eq!(frames.len(), 1);
+    assert!(common::is_bits_match(&frames[0], &bits));
+    assert_eq!(*outputs.lock().unwrap(), ["prs"]);
+}